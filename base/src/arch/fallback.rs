@@ -1,19 +1,19 @@
 use crate::{
-    Escapes, EscapesBuilder,
+    Escapes, EscapesBuilder, UnescapeFailure, Unescapes, UnescapesBuilder,
     writer::{Result, Writer},
 };
 
 /// A function that performs escape operations using fallback implementation.
 ///
 /// # Parameters
-/// - `haystack`: The input string to be escaped.
+/// - `haystack`: The input bytes to be escaped.
 /// - `writer`: The writer function.
 ///
 /// # Returns
 /// A result indicating success or failure of the escape operation.
 #[inline(always)]
 pub fn escape_fallback<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
-    haystack: &str,
+    haystack: &[u8],
     writer: W,
 ) -> Result<W::Error> {
     // TODO: implement "1.21 Scanning for zero bytes" from Matters Computational by J. Arndt
@@ -23,3 +23,43 @@ pub fn escape_fallback<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
     // But this is fallback implementation, so it's not priority
     E::Escapes::<()>::byte_byte_escape(haystack, writer)
 }
+
+/// A function that computes the escaped length using fallback implementation.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be measured.
+///
+/// # Returns
+/// The exact number of bytes the escaped output would take.
+#[inline(always)]
+pub fn escaped_len_fallback<E: EscapesBuilder>(haystack: &[u8]) -> usize {
+    E::Escapes::<()>::byte_byte_escaped_len(haystack)
+}
+
+/// A function that finds the first byte to escape using fallback implementation.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be scanned.
+///
+/// # Returns
+/// The offset of the first byte to escape, or `None` if there is none.
+#[inline(always)]
+pub fn first_escape_fallback<E: EscapesBuilder>(haystack: &[u8]) -> Option<usize> {
+    E::Escapes::<()>::byte_byte_first_escape(haystack)
+}
+
+/// A function that performs unescape operations using fallback implementation.
+///
+/// # Parameters
+/// - `haystack`: The input string to be unescaped.
+/// - `writer`: The writer function.
+///
+/// # Returns
+/// A result indicating success or failure of the unescape operation.
+#[inline(always)]
+pub fn unescape_fallback<U: UnescapesBuilder, const FMT: bool, W: Writer<FMT>>(
+    haystack: &str,
+    writer: W,
+) -> core::result::Result<(), UnescapeFailure<W::Error>> {
+    U::Unescapes::<()>::byte_byte_unescape(haystack, writer)
+}
@@ -18,8 +18,16 @@ pub mod fallback;
 
 /// A macro for creating a escape functions
 ///
+/// With `@bytes`, only the functions writing bytes are created, for tables
+/// escaping bytes above `0x7F`: replacing one of them splits a multi-byte
+/// character, so the output is not valid UTF-8. They take `&[u8]` instead
+/// of `&str`, so any bytes can be escaped.
+///
 /// # Parameters
 /// - `$builder`: The type [`crate::EscapesBuilder`] of the builder
+/// - `$sse2`: The optional builder used on x86_64 CPUs with SSE2 but not
+///   SSSE3, when `$builder` uses [`crate::Vector::shuffle`]. Without it, they
+///   fall back to scalar code
 #[cfg(not(any(
     target_arch = "x86_64",
     target_arch = "aarch64",
@@ -27,7 +35,85 @@ pub mod fallback;
 )))]
 #[macro_export]
 macro_rules! escape_builder {
-    ($builder:ty) => {
+    (@common $builder:ty, $haystack:ty) => {
+        $crate::struct_bytes!($crate::builder_bytes!(
+            escape_bytes,
+            $crate::arch::fallback::escape_fallback,
+            escape_fallback,
+            $builder,
+            $haystack
+        ));
+
+        $crate::builder_max_len!($builder);
+
+        $crate::struct_bytes!($crate::builder_bytes_reserved!(
+            escape_bytes_reserved,
+            $crate::arch::fallback::escape_fallback,
+            escape_fallback,
+            $builder,
+            $haystack
+        ));
+
+        $crate::struct_bytes!($crate::builder_try_bytes!(
+            try_escape_bytes,
+            $crate::arch::fallback::escape_fallback,
+            escape_fallback,
+            $builder,
+            $haystack
+        ));
+
+        $crate::builder_len!(
+            escaped_len,
+            $crate::arch::fallback::escaped_len_fallback,
+            escaped_len_fallback,
+            $builder,
+            $haystack
+        );
+
+        $crate::builder_first_escape!(
+            first_escape_position,
+            $crate::arch::fallback::first_escape_fallback,
+            first_escape_fallback,
+            $builder,
+            $haystack
+        );
+
+        $crate::builder_needs_escape!(needs_escape, first_escape_position, $haystack);
+
+        $crate::struct_io!(
+            escape_io,
+            escape_io_internal,
+            $crate::builder_io!(
+                escape_io_internal,
+                $crate::arch::fallback::escape_fallback,
+                escape_fallback,
+                $builder,
+                $haystack
+            ),
+            $builder,
+            $haystack
+        );
+
+        $crate::struct_vectored!(
+            escape_vectored,
+            escape_vectored_internal,
+            $crate::builder_vectored!(
+                escape_vectored_internal,
+                $crate::arch::fallback::escape_fallback,
+                escape_fallback,
+                $builder,
+                $haystack
+            ),
+            $builder,
+            $haystack
+        );
+    };
+    (@bytes $builder:ty $(, $sse2:ty)?) => {
+        $crate::escape_builder!(@common $builder, [u8]);
+    };
+    ($builder:ty $(, $sse2:ty)?) => {
+        $crate::escape_builder!(@common $builder, str);
+
         $crate::struct_string!($crate::builder_string!(
             escape_string,
             $crate::arch::fallback::escape_fallback,
@@ -35,8 +121,15 @@ macro_rules! escape_builder {
             $builder
         ));
 
-        $crate::struct_bytes!($crate::builder_bytes!(
-            escape_bytes,
+        $crate::struct_string!($crate::builder_string_reserved!(
+            escape_string_reserved,
+            $crate::arch::fallback::escape_fallback,
+            escape_fallback,
+            $builder
+        ));
+
+        $crate::struct_string!($crate::builder_try_string!(
+            try_escape_string,
             $crate::arch::fallback::escape_fallback,
             escape_fallback,
             $builder
@@ -53,5 +146,83 @@ macro_rules! escape_builder {
             ),
             $builder
         );
+
+        $crate::struct_fmt_write!(
+            escape_fmt_into,
+            escape_fmt_into_internal,
+            $crate::builder_fmt_write!(
+                escape_fmt_into_internal,
+                $crate::arch::fallback::escape_fallback,
+                escape_fallback,
+                $builder
+            ),
+            $builder
+        );
+
+        $crate::builder_slice!(
+            escape_slice,
+            $crate::arch::fallback::escape_fallback,
+            escape_fallback,
+            $builder
+        );
+
+        $crate::builder_iter!(escape_iter, first_escape_position, $builder);
+
+        $crate::struct_string!($crate::builder_cow!(
+            escape_cow,
+            escape_string,
+            first_escape_position,
+            escaped_len
+        ));
+    };
+}
+
+/// A macro for creating unescape functions
+///
+/// # Parameters
+/// - `$builder`: The type [`crate::UnescapesBuilder`] of the builder
+#[cfg(not(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+)))]
+#[macro_export]
+macro_rules! unescape_builder {
+    ($builder:ty) => {
+        $crate::struct_unescape_string!(
+            unescape_string,
+            unescape_string_internal,
+            $crate::builder_unescape_vec!(
+                unescape_string_internal,
+                $crate::arch::fallback::unescape_fallback,
+                unescape_fallback,
+                $builder
+            ),
+            $builder
+        );
+
+        $crate::struct_unescape_bytes!(
+            unescape_bytes,
+            unescape_bytes_internal,
+            $crate::builder_unescape_vec!(
+                unescape_bytes_internal,
+                $crate::arch::fallback::unescape_fallback,
+                unescape_fallback,
+                $builder
+            ),
+            $builder
+        );
+
+        $crate::struct_unescape_display!(
+            unescape_fmt,
+            unescape_fmt_internal,
+            $crate::builder_unescape_fmt!(
+                unescape_fmt_internal,
+                $crate::arch::fallback::unescape_fallback,
+                unescape_fallback,
+                $builder
+            ),
+            $builder
+        );
     };
 }
@@ -1,8 +1,8 @@
 use core::arch::wasm32::v128;
 
 use crate::{
-    Escapes, EscapesBuilder, Vector,
-    generic::Generic,
+    Escapes, EscapesBuilder, UnescapeFailure, Unescapes, UnescapesBuilder, Vector,
+    generic::{Generic, GenericUnescape},
     writer::{Result, Writer},
 };
 
@@ -10,14 +10,14 @@ type WasmVector = v128;
 /// A function that performs escape operations using Wasm SIMD vectorization.
 ///
 /// # Parameters
-/// - `haystack`: The input string to be escaped.
+/// - `haystack`: The input bytes to be escaped.
 /// - `writer`: The writer function.
 ///
 /// # Returns
 /// A result indicating success or failure of the escape operation.
 #[inline(always)]
 pub fn escape<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
-    haystack: &str,
+    haystack: &[u8],
     writer: W,
 ) -> Result<W::Error> {
     let len = haystack.len();
@@ -30,13 +30,145 @@ pub fn escape<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
     Generic::new(E::new::<WasmVector>()).escape(haystack, writer)
 }
 
+/// A function that computes the escaped length using Wasm SIMD vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be measured.
+///
+/// # Returns
+/// The exact number of bytes the escaped output would take.
+#[inline(always)]
+pub fn escaped_len<E: EscapesBuilder>(haystack: &[u8]) -> usize {
+    let len = haystack.len();
+    if len < WasmVector::BYTES {
+        return <E::Escapes<()> as Escapes>::byte_byte_escaped_len(haystack);
+    }
+
+    Generic::new(E::new::<WasmVector>()).escaped_len(haystack)
+}
+
+/// A function that finds the first byte to escape using Wasm SIMD vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be scanned.
+///
+/// # Returns
+/// The offset of the first byte to escape, or `None` if there is none.
+#[inline(always)]
+pub fn first_escape<E: EscapesBuilder>(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    if len < WasmVector::BYTES {
+        return <E::Escapes<()> as Escapes>::byte_byte_first_escape(haystack);
+    }
+
+    Generic::new(E::new::<WasmVector>()).first_escape(haystack)
+}
+
+/// A function that performs unescape operations using Wasm SIMD vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input string to be unescaped.
+/// - `writer`: The writer function.
+///
+/// # Returns
+/// A result indicating success or failure of the unescape operation.
+#[inline(always)]
+pub fn unescape<U: UnescapesBuilder, const FMT: bool, W: Writer<FMT>>(
+    haystack: &str,
+    writer: W,
+) -> core::result::Result<(), UnescapeFailure<W::Error>> {
+    let len = haystack.len();
+    if len < WasmVector::BYTES {
+        return <U::Unescapes<()> as Unescapes>::byte_byte_unescape(haystack, writer);
+    }
+
+    GenericUnescape::new(U::new::<WasmVector>()).unescape(haystack, writer)
+}
+
 /// A macro for creating a escape functions
 ///
+/// With `@bytes`, only the functions writing bytes are created, for tables
+/// escaping bytes above `0x7F`: replacing one of them splits a multi-byte
+/// character, so the output is not valid UTF-8. They take `&[u8]` instead
+/// of `&str`, so any bytes can be escaped.
+///
 /// # Parameters
 /// - `$builder`: The type [`crate::EscapesBuilder`] of the builder
+/// - `$sse2`: The optional builder used on x86_64 CPUs with SSE2 but not
+///   SSSE3, when `$builder` uses [`crate::Vector::shuffle`]. Without it, they
+///   fall back to scalar code
 #[macro_export]
 macro_rules! escape_builder {
-    ($builder:ty) => {
+    (@common $builder:ty, $haystack:ty) => {
+        $crate::struct_bytes!($crate::builder_bytes!(
+            escape_bytes,
+            $crate::arch::wasm32::escape,
+            escape,
+            $builder,
+            $haystack
+        ));
+        $crate::builder_max_len!($builder);
+        $crate::struct_bytes!($crate::builder_bytes_reserved!(
+            escape_bytes_reserved,
+            $crate::arch::wasm32::escape,
+            escape,
+            $builder,
+            $haystack
+        ));
+        $crate::struct_bytes!($crate::builder_try_bytes!(
+            try_escape_bytes,
+            $crate::arch::wasm32::escape,
+            escape,
+            $builder,
+            $haystack
+        ));
+        $crate::builder_len!(
+            escaped_len,
+            $crate::arch::wasm32::escaped_len,
+            escaped_len,
+            $builder,
+            $haystack
+        );
+        $crate::builder_first_escape!(
+            first_escape_position,
+            $crate::arch::wasm32::first_escape,
+            first_escape,
+            $builder,
+            $haystack
+        );
+        $crate::builder_needs_escape!(needs_escape, first_escape_position, $haystack);
+        $crate::struct_io!(
+            escape_io,
+            escape_io_internal,
+            $crate::builder_io!(
+                escape_io_internal,
+                $crate::arch::wasm32::escape,
+                escape,
+                $builder,
+                $haystack
+            ),
+            $builder,
+            $haystack
+        );
+        $crate::struct_vectored!(
+            escape_vectored,
+            escape_vectored_internal,
+            $crate::builder_vectored!(
+                escape_vectored_internal,
+                $crate::arch::wasm32::escape,
+                escape,
+                $builder,
+                $haystack
+            ),
+            $builder,
+            $haystack
+        );
+    };
+    (@bytes $builder:ty $(, $sse2:ty)?) => {
+        $crate::escape_builder!(@common $builder, [u8]);
+    };
+    ($builder:ty $(, $sse2:ty)?) => {
+        $crate::escape_builder!(@common $builder, str);
         $crate::struct_string!($crate::builder_string!(
             escape_string,
             $crate::arch::wasm32::escape,
@@ -44,8 +176,14 @@ macro_rules! escape_builder {
             $builder
         ));
 
-        $crate::struct_bytes!($crate::builder_bytes!(
-            escape_bytes,
+        $crate::struct_string!($crate::builder_string_reserved!(
+            escape_string_reserved,
+            $crate::arch::wasm32::escape,
+            escape,
+            $builder
+        ));
+        $crate::struct_string!($crate::builder_try_string!(
+            try_escape_string,
             $crate::arch::wasm32::escape,
             escape,
             $builder
@@ -61,5 +199,67 @@ macro_rules! escape_builder {
             ),
             $builder
         );
+        $crate::struct_fmt_write!(
+            escape_fmt_into,
+            escape_fmt_into_internal,
+            $crate::builder_fmt_write!(
+                escape_fmt_into_internal,
+                $crate::arch::wasm32::escape,
+                escape,
+                $builder
+            ),
+            $builder
+        );
+        $crate::builder_slice!(escape_slice, $crate::arch::wasm32::escape, escape, $builder);
+        $crate::builder_iter!(escape_iter, first_escape_position, $builder);
+        $crate::struct_string!($crate::builder_cow!(
+            escape_cow,
+            escape_string,
+            first_escape_position,
+            escaped_len
+        ));
+    };
+}
+
+/// A macro for creating unescape functions
+///
+/// # Parameters
+/// - `$builder`: The type [`crate::UnescapesBuilder`] of the builder
+#[macro_export]
+macro_rules! unescape_builder {
+    ($builder:ty) => {
+        $crate::struct_unescape_string!(
+            unescape_string,
+            unescape_string_internal,
+            $crate::builder_unescape_vec!(
+                unescape_string_internal,
+                $crate::arch::wasm32::unescape,
+                unescape,
+                $builder
+            ),
+            $builder
+        );
+        $crate::struct_unescape_bytes!(
+            unescape_bytes,
+            unescape_bytes_internal,
+            $crate::builder_unescape_vec!(
+                unescape_bytes_internal,
+                $crate::arch::wasm32::unescape,
+                unescape,
+                $builder
+            ),
+            $builder
+        );
+        $crate::struct_unescape_display!(
+            unescape_fmt,
+            unescape_fmt_internal,
+            $crate::builder_unescape_fmt!(
+                unescape_fmt_internal,
+                $crate::arch::wasm32::unescape,
+                unescape,
+                $builder
+            ),
+            $builder
+        );
     };
 }
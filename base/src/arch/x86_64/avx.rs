@@ -1,8 +1,8 @@
 use core::arch::x86_64::{__m128i, __m256i};
 
 use crate::{
-    Escapes, EscapesBuilder, Vector,
-    generic::Generic,
+    Escapes, EscapesBuilder, UnescapeFailure, Unescapes, UnescapesBuilder, Vector,
+    generic::{Generic, GenericUnescape},
     writer::{Result, Writer},
 };
 
@@ -39,14 +39,14 @@ type SseVector = __m128i;
 /// A function that performs escape operations using AVX and SSE vectorization.
 ///
 /// # Parameters
-/// - `haystack`: The input string to be escaped.
+/// - `haystack`: The input bytes to be escaped.
 /// - `writer`: The writer function.
 ///
 /// # Returns
 /// A result indicating success or failure of the escape operation.
 #[inline(always)]
 pub fn escape<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
-    haystack: &str,
+    haystack: &[u8],
     writer: W,
 ) -> Result<W::Error> {
     let len = haystack.len();
@@ -59,3 +59,67 @@ pub fn escape<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
 
     Generic::new(E::new::<AvxVector>()).escape(haystack, writer)
 }
+
+/// A function that computes the escaped length using AVX and SSE vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be measured.
+///
+/// # Returns
+/// The exact number of bytes the escaped output would take.
+#[inline(always)]
+pub fn escaped_len<E: EscapesBuilder>(haystack: &[u8]) -> usize {
+    let len = haystack.len();
+    if len < AvxVector::BYTES {
+        if len < SseVector::BYTES {
+            return <E::Escapes<()> as Escapes>::byte_byte_escaped_len(haystack);
+        }
+        return Generic::new(E::new::<SseVector>()).escaped_len(haystack);
+    }
+
+    Generic::new(E::new::<AvxVector>()).escaped_len(haystack)
+}
+
+/// A function that finds the first byte to escape using AVX and SSE vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be scanned.
+///
+/// # Returns
+/// The offset of the first byte to escape, or `None` if there is none.
+#[inline(always)]
+pub fn first_escape<E: EscapesBuilder>(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    if len < AvxVector::BYTES {
+        if len < SseVector::BYTES {
+            return <E::Escapes<()> as Escapes>::byte_byte_first_escape(haystack);
+        }
+        return Generic::new(E::new::<SseVector>()).first_escape(haystack);
+    }
+
+    Generic::new(E::new::<AvxVector>()).first_escape(haystack)
+}
+
+/// A function that performs unescape operations using AVX and SSE vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input string to be unescaped.
+/// - `writer`: The writer function.
+///
+/// # Returns
+/// A result indicating success or failure of the unescape operation.
+#[inline(always)]
+pub fn unescape<U: UnescapesBuilder, const FMT: bool, W: Writer<FMT>>(
+    haystack: &str,
+    writer: W,
+) -> core::result::Result<(), UnescapeFailure<W::Error>> {
+    let len = haystack.len();
+    if len < AvxVector::BYTES {
+        if len < SseVector::BYTES {
+            return <U::Unescapes<()> as Unescapes>::byte_byte_unescape(haystack, writer);
+        }
+        return GenericUnescape::new(U::new::<SseVector>()).unescape(haystack, writer);
+    }
+
+    GenericUnescape::new(U::new::<AvxVector>()).unescape(haystack, writer)
+}
@@ -9,7 +9,18 @@ pub mod sse;
 /// # Parameters
 /// - `$name`: The name of the function.
 /// - `$writer_builder`: The function to use for the builder.
+/// - `$fn_name`: The name of the function in the SIMD backend modules,
+///   `escape` unless given after `@named`.
+/// - `$fallback_name`: The name of the function in the fallback module,
+///   `escape_fallback` unless given after `@named`.
+/// - `$shuffle`: Whether the masking uses [`crate::Vector::shuffle`], which
+///   needs SSSE3 instead of SSE2, given after `@named`.
 /// - `$builder`: The type of the builder.
+/// - `$sse2`: The type of the builder used on CPUs with SSE2 but not SSSE3,
+///   which never shuffles when the masking of `$builder` does.
+/// - `$sse2_shuffle`: Whether the masking of `$sse2` shuffles, then SSE2
+///   falls back to scalar code, given after `@named`.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
 /// - `$buffer`: The type of the buffer.
 #[doc(hidden)]
 #[macro_export]
@@ -18,36 +29,71 @@ macro_rules! ifun {
         $name:ident,
         $writer_builder:path,
         $builder:ty,
+        $sse2:ty,
+        $haystack:ty,
+        $buffer:ty
+        $(,$retty:ty)?
+    ) => {
+        $crate::ifun!(
+            @named
+            $name,
+            $writer_builder,
+            escape,
+            escape_fallback,
+            <<$builder as $crate::EscapesBuilder>::Escapes<()> as $crate::Escapes>::SHUFFLE,
+            $builder,
+            $sse2,
+            <<$sse2 as $crate::EscapesBuilder>::Escapes<()> as $crate::Escapes>::SHUFFLE,
+            $haystack,
+            $buffer
+            $(,$retty)?
+        );
+    };
+    (
+        @named
+        $name:ident,
+        $writer_builder:path,
+        $fn_name:ident,
+        $fallback_name:ident,
+        $shuffle:expr,
+        $builder:ty,
+        $sse2:ty,
+        $sse2_shuffle:expr,
+        $haystack:ty,
         $buffer:ty
         $(,$retty:ty)?
     ) => {
         /// Escape `haystack` into `buffer`, dispatching to the best SIMD backend
         /// available on the current CPU.
         ///
-        /// The first call performs a one-time CPU-feature check (AVX2, SSE2 or a
-        /// scalar fallback) and caches the chosen implementation in an atomic
+        /// The first call performs a one-time CPU-feature check (AVX2, SSE2,
+        /// SSSE3 or a scalar fallback) and caches the chosen implementation in an atomic
         /// pointer; subsequent calls jump directly to the selected backend.
         ///
         /// See the crate-level documentation for the table of characters that
         /// get rewritten and their replacements.
-        pub fn $name(haystack: &str, buffer: &mut $buffer) $(-> $retty)? {
+        pub fn $name(haystack: &$haystack, buffer: &mut $buffer) $(-> $retty)? {
             use core::sync::atomic::{AtomicPtr, Ordering};
 
             type Fn = *mut ();
-            type RealFn = fn(haystack: &str, buffer: &mut $buffer) $(-> $retty)?;
+            type RealFn = fn(haystack: &$haystack, buffer: &mut $buffer) $(-> $retty)?;
             static FN: AtomicPtr<()> = AtomicPtr::new(detect as Fn);
 
             #[cfg(target_feature = "sse2")]
             #[target_feature(enable = "sse2", enable = "avx2")]
-            $writer_builder!(escape_avx2, $crate::arch::x86_64::avx::escape, escape, $builder);
+            $writer_builder!(escape_avx2, $crate::arch::x86_64::avx::$fn_name, $fn_name, $builder, $haystack);
 
             #[cfg(target_feature = "sse2")]
             #[target_feature(enable = "sse2")]
-            $writer_builder!(escape_sse2, $crate::arch::x86_64::sse::escape, escape, $builder);
+            $writer_builder!(escape_sse2, $crate::arch::x86_64::sse::$fn_name, $fn_name, $sse2, $haystack);
 
-            $writer_builder!(escape_fallback, $crate::arch::fallback::escape_fallback, escape_fallback, $builder);
+            #[cfg(target_feature = "sse2")]
+            #[target_feature(enable = "sse2", enable = "ssse3")]
+            $writer_builder!(escape_ssse3, $crate::arch::x86_64::sse::$fn_name, $fn_name, $builder, $haystack);
 
-            unsafe fn detect(haystack: &str, buffer: &mut $buffer) $(-> $retty)? {
+            $writer_builder!(escape_fallback, $crate::arch::fallback::$fallback_name, $fallback_name, $builder, $haystack);
+
+            unsafe fn detect(haystack: &$haystack, buffer: &mut $buffer) $(-> $retty)? {
                 let fun = {
                     #[cfg(not(target_feature = "sse2"))]
                     {
@@ -57,7 +103,9 @@ macro_rules! ifun {
                     {
                         if $crate::arch::x86_64::avx::is_available() {
                             escape_avx2
-                        } else if $crate::arch::x86_64::sse::is_available() {
+                        } else if $shuffle && $crate::arch::x86_64::sse::is_ssse3_available() {
+                            escape_ssse3
+                        } else if !$sse2_shuffle && $crate::arch::x86_64::sse::is_available() {
                             escape_sse2
                         } else {
                             escape_fallback
@@ -88,13 +136,239 @@ macro_rules! ifun {
     };
 }
 
+/// A macro for creating a functions that only scan the input
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$query_builder`: The function to use for the builder.
+/// - `$fn_name`: The name of the function in the SIMD backend modules.
+/// - `$fallback_name`: The name of the function in the fallback module.
+/// - `$builder`: The type of the builder.
+/// - `$sse2`: The type of the builder used on CPUs with SSE2 but not SSSE3.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+/// - `$retty`: The return type of the function.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! ifun_query {
+    (
+        $name:ident,
+        $query_builder:path,
+        $fn_name:ident,
+        $fallback_name:ident,
+        $builder:ty,
+        $sse2:ty,
+        $haystack:ty,
+        $retty:ty
+    ) => {
+        /// Scan `haystack`, dispatching to the best SIMD backend available on
+        /// the current CPU.
+        ///
+        /// The first call performs a one-time CPU-feature check (AVX2, SSE2,
+        /// SSSE3 or a scalar fallback) and caches the chosen implementation in an atomic
+        /// pointer; subsequent calls jump directly to the selected backend.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(haystack: &$haystack) -> $retty {
+            use core::sync::atomic::{AtomicPtr, Ordering};
+
+            type Fn = *mut ();
+            type RealFn = fn(haystack: &$haystack) -> $retty;
+            static FN: AtomicPtr<()> = AtomicPtr::new(detect as Fn);
+
+            #[cfg(target_feature = "sse2")]
+            #[target_feature(enable = "sse2", enable = "avx2")]
+            $query_builder!(
+                query_avx2,
+                $crate::arch::x86_64::avx::$fn_name,
+                $fn_name,
+                $builder,
+                $haystack
+            );
+
+            #[cfg(target_feature = "sse2")]
+            #[target_feature(enable = "sse2")]
+            $query_builder!(
+                query_sse2,
+                $crate::arch::x86_64::sse::$fn_name,
+                $fn_name,
+                $sse2,
+                $haystack
+            );
+
+            #[cfg(target_feature = "sse2")]
+            #[target_feature(enable = "sse2", enable = "ssse3")]
+            $query_builder!(
+                query_ssse3,
+                $crate::arch::x86_64::sse::$fn_name,
+                $fn_name,
+                $builder,
+                $haystack
+            );
+
+            $query_builder!(
+                query_fallback,
+                $crate::arch::fallback::$fallback_name,
+                $fallback_name,
+                $builder,
+                $haystack
+            );
+
+            unsafe fn detect(haystack: &$haystack) -> $retty {
+                let fun = {
+                    #[cfg(not(target_feature = "sse2"))]
+                    {
+                        query_fallback
+                    }
+                    #[cfg(target_feature = "sse2")]
+                    {
+                        if $crate::arch::x86_64::avx::is_available() {
+                            query_avx2
+                        } else if <<$builder as $crate::EscapesBuilder>::Escapes<()> as $crate::Escapes>::SHUFFLE
+                            && $crate::arch::x86_64::sse::is_ssse3_available()
+                        {
+                            query_ssse3
+                        } else if !<<$sse2 as $crate::EscapesBuilder>::Escapes<()> as $crate::Escapes>::SHUFFLE
+                            && $crate::arch::x86_64::sse::is_available()
+                        {
+                            query_sse2
+                        } else {
+                            query_fallback
+                        }
+                    }
+                };
+                FN.store(fun as Fn, Ordering::Relaxed);
+                // SAFETY: The only thing we need to uphold here is the
+                // `#[target_feature]` requirements. Since we check is_available
+                // above before using the corresponding implementation, we are
+                // guaranteed to only call code that is supported on the current
+                // CPU.
+                fun(haystack)
+            }
+
+            // SAFETY: By virtue of the caller contract, RealFn is a function
+            // pointer, which is always safe to transmute with a *mut ().
+            unsafe {
+                let fun = FN.load(Ordering::Relaxed);
+                core::mem::transmute::<Fn, RealFn>(fun)(haystack)
+            }
+        }
+    };
+}
+
 /// A macro for creating a escape functions
 ///
+/// With `@bytes`, only the functions writing bytes are created, for tables
+/// escaping bytes above `0x7F`: replacing one of them splits a multi-byte
+/// character, so the output is not valid UTF-8. They take `&[u8]` instead
+/// of `&str`, so any bytes can be escaped.
+///
 /// # Parameters
 /// - `$builder`: The type [`crate::EscapesBuilder`] of the builder
+/// - `$sse2`: The optional builder used on x86_64 CPUs with SSE2 but not
+///   SSSE3, when `$builder` uses [`crate::Vector::shuffle`]. Without it, they
+///   fall back to scalar code
 #[macro_export]
 macro_rules! escape_builder {
+    (@common $builder:ty, $sse2:ty, $haystack:ty) => {
+        $crate::struct_bytes!($crate::ifun!(
+            escape_bytes,
+            $crate::builder_bytes,
+            $builder,
+            $sse2,
+            $haystack,
+            $crate::__alloc::vec::Vec<u8>
+        ));
+
+        $crate::builder_max_len!($builder);
+
+        $crate::struct_bytes!($crate::ifun!(
+            escape_bytes_reserved,
+            $crate::builder_bytes_reserved,
+            $builder,
+            $sse2,
+            $haystack,
+            $crate::__alloc::vec::Vec<u8>
+        ));
+
+        $crate::struct_bytes!($crate::ifun!(
+            try_escape_bytes,
+            $crate::builder_try_bytes,
+            $builder,
+            $sse2,
+            $haystack,
+            $crate::__alloc::vec::Vec<u8>,
+            Result<(), $crate::__alloc::collections::TryReserveError>
+        ));
+
+        $crate::ifun_query!(
+            escaped_len,
+            $crate::builder_len,
+            escaped_len,
+            escaped_len_fallback,
+            $builder,
+            $sse2,
+            $haystack,
+            usize
+        );
+
+        $crate::ifun_query!(
+            first_escape_position,
+            $crate::builder_first_escape,
+            first_escape,
+            first_escape_fallback,
+            $builder,
+            $sse2,
+            $haystack,
+            Option<usize>
+        );
+
+        $crate::builder_needs_escape!(needs_escape, first_escape_position, $haystack);
+
+        $crate::struct_io!(
+            escape_io,
+            escape_io_internal,
+            $crate::ifun!(
+                escape_io_internal,
+                $crate::builder_io,
+                $builder,
+                $sse2,
+                $haystack,
+                dyn std::io::Write,
+                std::io::Result<()>
+            ),
+            $builder,
+            $haystack
+        );
+
+        $crate::struct_vectored!(
+            escape_vectored,
+            escape_vectored_internal,
+            $crate::ifun!(
+                escape_vectored_internal,
+                $crate::builder_vectored,
+                $builder,
+                $sse2,
+                $haystack,
+                dyn std::io::Write,
+                std::io::Result<()>
+            ),
+            $builder,
+            $haystack
+        );
+    };
+    (@bytes $builder:ty, $sse2:ty) => {
+        $crate::escape_builder!(@common $builder, $sse2, [u8]);
+    };
+    (@bytes $builder:ty) => {
+        $crate::escape_builder!(@bytes $builder, $builder);
+    };
     ($builder:ty) => {
+        $crate::escape_builder!($builder, $builder);
+    };
+    ($builder:ty, $sse2:ty) => {
+        $crate::escape_builder!(@common $builder, $sse2, str);
+
         $crate::struct_display!(
             escape_fmt,
             escape_fmt_internal,
@@ -102,24 +376,143 @@ macro_rules! escape_builder {
                 escape_fmt_internal,
                 $crate::builder_fmt,
                 $builder,
+                $sse2,
+                str,
                 core::fmt::Formatter<'_>,
                 core::fmt::Result
             ),
             $builder
         );
 
+        $crate::struct_fmt_write!(
+            escape_fmt_into,
+            escape_fmt_into_internal,
+            $crate::ifun!(
+                escape_fmt_into_internal,
+                $crate::builder_fmt_write,
+                $builder,
+                $sse2,
+                str,
+                dyn core::fmt::Write,
+                core::fmt::Result
+            ),
+            $builder
+        );
+
         $crate::struct_string!($crate::ifun!(
             escape_string,
             $crate::builder_string,
             $builder,
-            String
+            $sse2,
+            str,
+            $crate::__alloc::string::String
         ));
 
-        $crate::struct_bytes!($crate::ifun!(
-            escape_bytes,
-            $crate::builder_bytes,
+        $crate::struct_string!($crate::ifun!(
+            escape_string_reserved,
+            $crate::builder_string_reserved,
             $builder,
-            Vec<u8>
+            $sse2,
+            str,
+            $crate::__alloc::string::String
         ));
+
+        $crate::struct_string!($crate::ifun!(
+            try_escape_string,
+            $crate::builder_try_string,
+            $builder,
+            $sse2,
+            str,
+            $crate::__alloc::string::String,
+            Result<(), $crate::__alloc::collections::TryReserveError>
+        ));
+
+        $crate::ifun!(
+            escape_slice,
+            $crate::builder_slice,
+            $builder,
+            $sse2,
+            str,
+            [u8],
+            core::result::Result<usize, $crate::writer::Overflow>
+        );
+
+        $crate::builder_iter!(escape_iter, first_escape_position, $builder);
+
+        $crate::struct_string!($crate::builder_cow!(
+            escape_cow,
+            escape_string,
+            first_escape_position,
+            escaped_len
+        ));
+    };
+}
+
+/// A macro for creating unescape functions
+///
+/// # Parameters
+/// - `$builder`: The type [`crate::UnescapesBuilder`] of the builder
+#[macro_export]
+macro_rules! unescape_builder {
+    ($builder:ty) => {
+        $crate::struct_unescape_string!(
+            unescape_string,
+            unescape_string_internal,
+            $crate::ifun!(
+                @named
+                unescape_string_internal,
+                $crate::builder_unescape_vec,
+                unescape,
+                unescape_fallback,
+                false,
+                $builder,
+                $builder,
+                false,
+                str,
+                $crate::__alloc::vec::Vec<u8>,
+                core::result::Result<(), $crate::UnescapeError>
+            ),
+            $builder
+        );
+
+        $crate::struct_unescape_bytes!(
+            unescape_bytes,
+            unescape_bytes_internal,
+            $crate::ifun!(
+                @named
+                unescape_bytes_internal,
+                $crate::builder_unescape_vec,
+                unescape,
+                unescape_fallback,
+                false,
+                $builder,
+                $builder,
+                false,
+                str,
+                $crate::__alloc::vec::Vec<u8>,
+                core::result::Result<(), $crate::UnescapeError>
+            ),
+            $builder
+        );
+
+        $crate::struct_unescape_display!(
+            unescape_fmt,
+            unescape_fmt_internal,
+            $crate::ifun!(
+                @named
+                unescape_fmt_internal,
+                $crate::builder_unescape_fmt,
+                unescape,
+                unescape_fallback,
+                false,
+                $builder,
+                $builder,
+                false,
+                str,
+                core::fmt::Formatter<'_>,
+                core::fmt::Result
+            ),
+            $builder
+        );
     };
 }
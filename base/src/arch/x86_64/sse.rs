@@ -1,8 +1,8 @@
 use core::arch::x86_64::__m128i;
 
 use crate::{
-    Escapes, EscapesBuilder, Vector,
-    generic::Generic,
+    Escapes, EscapesBuilder, UnescapeFailure, Unescapes, UnescapesBuilder, Vector,
+    generic::{Generic, GenericUnescape},
     writer::{Result, Writer},
 };
 
@@ -18,19 +18,46 @@ pub fn is_available() -> bool {
     }
 }
 
+/// Returns true if SSSE3 is available in the current environment, needed by
+/// [`Vector::shuffle`] on `__m128i`.
+pub fn is_ssse3_available() -> bool {
+    #[cfg(not(target_feature = "sse2"))]
+    {
+        false
+    }
+    #[cfg(target_feature = "sse2")]
+    {
+        #[cfg(target_feature = "ssse3")]
+        {
+            true
+        }
+        #[cfg(not(target_feature = "ssse3"))]
+        {
+            #[cfg(feature = "std")]
+            {
+                std::is_x86_feature_detected!("ssse3")
+            }
+            #[cfg(not(feature = "std"))]
+            {
+                false
+            }
+        }
+    }
+}
+
 type SseVector = __m128i;
 
 /// A function that performs escape operations using SSE vectorization.
 ///
 /// # Parameters
-/// - `haystack`: The input string to be escaped.
+/// - `haystack`: The input bytes to be escaped.
 /// - `writer`: The writer function.
 ///
 /// # Returns
 /// A result indicating success or failure of the escape operation.
 #[inline(always)]
 pub fn escape<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
-    haystack: &str,
+    haystack: &[u8],
     writer: W,
 ) -> Result<W::Error> {
     let len = haystack.len();
@@ -40,3 +67,58 @@ pub fn escape<E: EscapesBuilder, const FMT: bool, W: Writer<FMT>>(
 
     Generic::new(E::new::<SseVector>()).escape(haystack, writer)
 }
+
+/// A function that computes the escaped length using SSE vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be measured.
+///
+/// # Returns
+/// The exact number of bytes the escaped output would take.
+#[inline(always)]
+pub fn escaped_len<E: EscapesBuilder>(haystack: &[u8]) -> usize {
+    let len = haystack.len();
+    if len < SseVector::BYTES {
+        return <E::Escapes<()> as Escapes>::byte_byte_escaped_len(haystack);
+    }
+
+    Generic::new(E::new::<SseVector>()).escaped_len(haystack)
+}
+
+/// A function that finds the first byte to escape using SSE vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input bytes to be scanned.
+///
+/// # Returns
+/// The offset of the first byte to escape, or `None` if there is none.
+#[inline(always)]
+pub fn first_escape<E: EscapesBuilder>(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    if len < SseVector::BYTES {
+        return <E::Escapes<()> as Escapes>::byte_byte_first_escape(haystack);
+    }
+
+    Generic::new(E::new::<SseVector>()).first_escape(haystack)
+}
+
+/// A function that performs unescape operations using SSE vectorization.
+///
+/// # Parameters
+/// - `haystack`: The input string to be unescaped.
+/// - `writer`: The writer function.
+///
+/// # Returns
+/// A result indicating success or failure of the unescape operation.
+#[inline(always)]
+pub fn unescape<U: UnescapesBuilder, const FMT: bool, W: Writer<FMT>>(
+    haystack: &str,
+    writer: W,
+) -> core::result::Result<(), UnescapeFailure<W::Error>> {
+    let len = haystack.len();
+    if len < SseVector::BYTES {
+        return <U::Unescapes<()> as Unescapes>::byte_byte_unescape(haystack, writer);
+    }
+
+    GenericUnescape::new(U::new::<SseVector>()).unescape(haystack, writer)
+}
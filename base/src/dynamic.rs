@@ -0,0 +1,524 @@
+use core::fmt;
+
+#[cfg(feature = "fmt")]
+use crate::writer::WriterFMT;
+#[cfg(any(feature = "string", feature = "bytes"))]
+use crate::writer::WriterVec;
+use crate::{
+    Vector,
+    escapes::Search,
+    generic::Generic,
+    writer::{Overflow, Writer, WriterSlice},
+};
+
+/// The bit of each high nibble of ASCII in the tables of a [`DynamicEscaper`].
+/// High nibbles above `0x7` never match.
+const HIGH: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 0, 0, 0, 0, 0, 0, 0, 0];
+
+/// Error returned by [`DynamicEscaper::new`] when a byte of the table is above
+/// `0x7F`.
+///
+/// Replacing one of them would split a multi-byte character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonAsciiByte(pub u8);
+
+impl fmt::Display for NonAsciiByte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "byte {:#04x} is not ASCII and can not be escaped",
+            self.0
+        )
+    }
+}
+
+impl core::error::Error for NonAsciiByte {}
+
+/// An escaper built at runtime from a table of bytes and their replacements.
+///
+/// It is the runtime counterpart of the tables generated by `escape!`, run by
+/// the same search and writers: the replacements are looked up by byte, and
+/// the bytes to escape are found by shuffling two 16 entries tables, indexed
+/// by the low and the high nibble of each byte. Every call dispatches to the
+/// best SIMD backend available on the current CPU (AVX2, SSSE3, NEON or wasm
+/// simd128) or to a scalar fallback. SSE2 alone has no shuffle, so it masks
+/// the range from the lowest to the highest escaped byte instead.
+///
+/// Only ASCII bytes can be escaped. A byte repeated in the table keeps its
+/// last replacement.
+///
+/// # Examples
+///
+/// ```rust
+/// use v_escape_base::DynamicEscaper;
+///
+/// let escaper = DynamicEscaper::new(&[(b'<', "&lt;"), (b'>', "&gt;")]).unwrap();
+///
+/// assert_eq!(escaper.escaped_len("<a>"), 9);
+/// assert_eq!(escaper.first_escape_position("a<b"), Some(1));
+/// # #[cfg(feature = "fmt")]
+/// assert_eq!(escaper.escape_fmt("<a>").to_string(), "&lt;a&gt;");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicEscaper<'a> {
+    /// The replacement of each ASCII byte, if it is escaped
+    replacements: [Option<&'a str>; 128],
+    /// For each low nibble, the bits of [`HIGH`] of the escaped bytes
+    low: [u8; 16],
+    /// The lowest and the highest escaped bytes
+    range: (u8, u8),
+    /// The length of the longest replacement
+    max_replacement_len: usize,
+}
+
+impl<'a> DynamicEscaper<'a> {
+    /// Builds an escaper replacing each byte of `mappings` by its string.
+    ///
+    /// # Errors
+    /// Returns the first byte above `0x7F`, if any.
+    pub fn new(mappings: &[(u8, &'a str)]) -> Result<Self, NonAsciiByte> {
+        let mut replacements = [None; 128];
+        let mut low = [0; 16];
+        for &(byte, replacement) in mappings {
+            if !byte.is_ascii() {
+                return Err(NonAsciiByte(byte));
+            }
+            replacements[byte as usize] = Some(replacement);
+            low[(byte & 0x0F) as usize] |= HIGH[(byte >> 4) as usize];
+        }
+        let max_replacement_len = replacements
+            .iter()
+            .flatten()
+            .map(|replacement| replacement.len())
+            .max()
+            .unwrap_or(0);
+        // An empty table masks a byte never escaped
+        let mut escaped = mappings.iter().map(|&(byte, _)| byte);
+        let range = match escaped.next() {
+            Some(first) => escaped.fold((first, first), |(min, max), byte| {
+                (min.min(byte), max.max(byte))
+            }),
+            None => (0xFF, 0xFF),
+        };
+
+        Ok(DynamicEscaper {
+            replacements,
+            low,
+            range,
+            max_replacement_len,
+        })
+    }
+
+    /// Returns the replacement of `byte`, or `None` if it is not escaped.
+    #[inline]
+    pub fn replacement(&self, byte: u8) -> Option<&'a str> {
+        self.replacements.get(byte as usize).copied().flatten()
+    }
+
+    /// Returns the length of the longest replacement in the table.
+    #[inline]
+    pub fn max_replacement_len(&self) -> usize {
+        self.max_replacement_len
+    }
+
+    /// Returns an upper bound of the escaped length of any input of `len`
+    /// bytes, assuming every byte is replaced by the longest replacement.
+    ///
+    /// Use [`DynamicEscaper::escaped_len`] to compute the exact length of a
+    /// given input.
+    #[inline]
+    pub fn max_escaped_len(&self, len: usize) -> usize {
+        if self.max_replacement_len > 1 {
+            len.saturating_mul(self.max_replacement_len)
+        } else {
+            len
+        }
+    }
+
+    /// Returns the exact number of bytes the escaped `haystack` would take.
+    pub fn escaped_len(&self, haystack: &str) -> usize {
+        self.run(haystack.as_bytes(), EscapedLen)
+    }
+
+    /// Returns the offset of the first byte of `haystack` to escape, or `None`
+    /// if there is none.
+    pub fn first_escape_position(&self, haystack: &str) -> Option<usize> {
+        self.run(haystack.as_bytes(), FirstEscape)
+    }
+
+    /// Returns `true` if `haystack` has any byte to escape.
+    pub fn needs_escape(&self, haystack: &str) -> bool {
+        self.first_escape_position(haystack).is_some()
+    }
+
+    /// Escapes `haystack` into `buffer` and returns the number of bytes
+    /// written.
+    ///
+    /// No allocation is performed. If `buffer` is too small an [`Overflow`] is
+    /// returned describing how much of `haystack` was escaped, so the caller
+    /// can drain `buffer` and resume from there. Bytes of `buffer` past
+    /// [`Overflow::written`] are unspecified.
+    pub fn escape_slice(&self, haystack: &str, buffer: &mut [u8]) -> Result<usize, Overflow> {
+        let mut writer = WriterSlice::new(buffer);
+        match self.run(haystack.as_bytes(), Escape(&mut writer)) {
+            Ok(()) => Ok(writer.len()),
+            Err(()) => {
+                let generic = Generic::new(Lookup::<(), false>::new(self));
+                Err(Overflow::escaped(&generic, haystack, writer.len()))
+            }
+        }
+    }
+
+    /// Escapes `haystack` and appends the result to `buffer`.
+    #[cfg(feature = "string")]
+    pub fn escape_string(&self, haystack: &str, buffer: &mut alloc::string::String) {
+        // SAFETY: `haystack` is forwarded verbatim between ASCII bytes and
+        // every replacement is a string, so `buffer` stays valid UTF-8.
+        let vec = unsafe { buffer.as_mut_vec() };
+        let _ = self.run(haystack.as_bytes(), Escape(WriterVec::new(vec)));
+    }
+
+    /// Escapes `haystack` and appends the result to `buffer`.
+    #[cfg(feature = "bytes")]
+    pub fn escape_bytes(&self, haystack: &str, buffer: &mut alloc::vec::Vec<u8>) {
+        let _ = self.run(haystack.as_bytes(), Escape(WriterVec::new(buffer)));
+    }
+
+    /// Escapes `haystack` and appends the result to `writer`.
+    ///
+    /// Errors reported by `writer` are returned as soon as they occur, in
+    /// which case only a prefix of the escaped output has been written.
+    #[cfg(feature = "fmt")]
+    pub fn escape_fmt_into<W: fmt::Write + ?Sized>(
+        &self,
+        haystack: &str,
+        writer: &mut W,
+    ) -> fmt::Result {
+        self.run(haystack.as_bytes(), Escape(WriterFMT::new(writer)))
+    }
+
+    /// Returns a value implementing [`core::fmt::Display`] that escapes
+    /// `haystack` lazily into the formatter it is rendered to.
+    #[cfg(feature = "fmt")]
+    pub fn escape_fmt<'b>(&'b self, haystack: &'b str) -> impl fmt::Display + 'b {
+        struct Display<'a, 'b>(&'b DynamicEscaper<'a>, &'b str);
+
+        impl fmt::Display for Display<'_, '_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.run(self.1.as_bytes(), Escape(WriterFMT::new(f)))
+            }
+        }
+
+        Display(self, haystack)
+    }
+
+    /// Runs `op` over `haystack` with the best backend available on the
+    /// current CPU.
+    ///
+    /// SSE2 without SSSE3 has no shuffle, so its masking is the range from
+    /// the lowest to the highest escaped byte, whose false positives are
+    /// looked up in the table.
+    #[inline]
+    fn run<R: Run>(&self, haystack: &[u8], op: R) -> R::Output {
+        #[cfg(target_arch = "x86_64")]
+        {
+            use crate::arch::x86_64::{avx, sse};
+
+            // SAFETY: The target features of each backend are checked first
+            if haystack.len() >= 32 && avx::is_available() {
+                return unsafe { x86_64::run_avx2(self, haystack, op) };
+            }
+            if haystack.len() >= 16 && sse::is_ssse3_available() {
+                return unsafe { x86_64::run_ssse3(self, haystack, op) };
+            }
+            if haystack.len() >= 16 && sse::is_available() {
+                return unsafe { x86_64::run_sse2(self, haystack, op) };
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            if haystack.len() >= 16 {
+                let escapes = Lookup::<core::arch::aarch64::int8x16_t, true>::new(self);
+                return op.vector(escapes, haystack);
+            }
+        }
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        {
+            if haystack.len() >= 16 {
+                let escapes = Lookup::<core::arch::wasm32::v128, true>::new(self);
+                return op.vector(escapes, haystack);
+            }
+        }
+
+        op.scalar(Lookup::<(), false>::new(self), haystack)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use core::arch::x86_64::{__m128i, __m256i};
+
+    use super::{DynamicEscaper, Lookup, Run};
+
+    /// [`Run::vector`] with AVX2.
+    ///
+    /// # Safety
+    /// The current CPU must support AVX2.
+    #[target_feature(enable = "sse2", enable = "avx2")]
+    pub(super) unsafe fn run_avx2<R: Run>(
+        escaper: &DynamicEscaper<'_>,
+        haystack: &[u8],
+        op: R,
+    ) -> R::Output {
+        op.vector(Lookup::<__m256i, true>::new(escaper), haystack)
+    }
+
+    /// [`Run::vector`] with SSSE3.
+    ///
+    /// # Safety
+    /// The current CPU must support SSSE3.
+    #[target_feature(enable = "sse2", enable = "ssse3")]
+    pub(super) unsafe fn run_ssse3<R: Run>(
+        escaper: &DynamicEscaper<'_>,
+        haystack: &[u8],
+        op: R,
+    ) -> R::Output {
+        op.vector(Lookup::<__m128i, true>::new(escaper), haystack)
+    }
+
+    /// [`Run::vector`] with SSE2, masking the range of the escaped bytes.
+    ///
+    /// # Safety
+    /// The current CPU must support SSE2.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn run_sse2<R: Run>(
+        escaper: &DynamicEscaper<'_>,
+        haystack: &[u8],
+        op: R,
+    ) -> R::Output {
+        op.vector(Lookup::<__m128i, false>::new(escaper), haystack)
+    }
+}
+
+/// The [`Search`] of a [`DynamicEscaper`], borrowing its table.
+///
+/// With `SHUFFLE`, the escaped bytes are masked exactly: a byte is escaped
+/// when its bit of [`HIGH`] is set in the entry of its low nibble, so the two
+/// tables are shuffled by the nibbles of a whole vector at once. Otherwise
+/// the range from the lowest to the highest escaped byte is masked, and its
+/// false positives are rejected by the table.
+#[derive(Debug, Clone, Copy)]
+struct Lookup<'e, 'a, V, const SHUFFLE: bool> {
+    escaper: &'e DynamicEscaper<'a>,
+    /// The table of the low nibbles, or the translation of the range
+    low: V,
+    /// The table of the high nibbles, or the bound of the range
+    high: V,
+    nibble: V,
+    zero: V,
+}
+
+impl<'e, 'a, V: Vector, const SHUFFLE: bool> Lookup<'e, 'a, V, SHUFFLE> {
+    #[inline(always)]
+    fn new(escaper: &'e DynamicEscaper<'a>) -> Self {
+        let (low, high) = if SHUFFLE {
+            (V::from_table(&escaper.low), V::from_table(&HIGH))
+        } else {
+            // Moves `max` to `i8::MAX`, so only the bytes of the range land
+            // above the bound with a signed comparison
+            let (min, max) = escaper.range;
+            let translation = (i8::MAX as u8).wrapping_sub(max);
+            let below = (i8::MAX as u8).wrapping_sub(max - min + 1);
+            (V::splat(translation), V::splat(below))
+        };
+        Lookup {
+            escaper,
+            low,
+            high,
+            nibble: V::splat(0x0F),
+            zero: V::splat(0),
+        }
+    }
+}
+
+impl<V: Vector, const SHUFFLE: bool> Search for Lookup<'_, '_, V, SHUFFLE> {
+    const FALSE_POSITIVE: bool = !SHUFFLE;
+
+    const UTF8: bool = true;
+
+    type Vector = V;
+
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        if SHUFFLE {
+            // The lanes of the escaped bytes are the non-zero ones
+            self.low
+                .shuffle(vector2.and(self.nibble))
+                .and(self.high.shuffle(vector2.high_nibble()))
+                .cmpeq(self.zero)
+                .cmpeq(self.zero)
+        } else {
+            vector2.add(self.low).gt(self.high)
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn replacement_at(&self, at: *const u8, _end: *const u8) -> Option<(&str, usize)> {
+        // SAFETY: `at` is in the haystack
+        let byte = unsafe { *at };
+        self.escaper
+            .replacement(byte)
+            .map(|replacement| (replacement, 1))
+    }
+
+    #[inline(always)]
+    fn compare(&self, c: u8) -> bool {
+        self.escaper.replacement(c).is_some()
+    }
+}
+
+/// An operation of a [`DynamicEscaper`] over a haystack, run with the
+/// [`Lookup`] of the backend picked by [`DynamicEscaper::run`].
+trait Run {
+    type Output;
+
+    /// Runs the operation with the vectors of `escapes`. The haystack is at
+    /// least a vector long.
+    fn vector<E: Search>(self, escapes: E, haystack: &[u8]) -> Self::Output;
+
+    /// Runs the operation one byte at a time.
+    fn scalar<E: Search>(self, escapes: E, haystack: &[u8]) -> Self::Output;
+}
+
+/// Escapes the haystack into the writer.
+struct Escape<W, const FMT: bool>(W);
+
+impl<const FMT: bool, W: Writer<FMT>> Run for Escape<W, FMT> {
+    type Output = Result<(), W::Error>;
+
+    #[inline(always)]
+    fn vector<E: Search>(self, escapes: E, haystack: &[u8]) -> Self::Output {
+        Generic::new(escapes).escape(haystack, self.0)
+    }
+
+    #[inline(always)]
+    fn scalar<E: Search>(self, escapes: E, haystack: &[u8]) -> Self::Output {
+        Generic::new(escapes).byte_byte_escape(haystack, self.0)
+    }
+}
+
+/// Computes the escaped length of the haystack.
+struct EscapedLen;
+
+impl Run for EscapedLen {
+    type Output = usize;
+
+    #[inline(always)]
+    fn vector<E: Search>(self, escapes: E, haystack: &[u8]) -> usize {
+        Generic::new(escapes).escaped_len(haystack)
+    }
+
+    #[inline(always)]
+    fn scalar<E: Search>(self, escapes: E, haystack: &[u8]) -> usize {
+        Generic::new(escapes).byte_byte_escaped_len(haystack)
+    }
+}
+
+/// Finds the first byte of the haystack to escape.
+struct FirstEscape;
+
+impl Run for FirstEscape {
+    type Output = Option<usize>;
+
+    #[inline(always)]
+    fn vector<E: Search>(self, escapes: E, haystack: &[u8]) -> Option<usize> {
+        Generic::new(escapes).first_escape(haystack)
+    }
+
+    #[inline(always)]
+    fn scalar<E: Search>(self, escapes: E, haystack: &[u8]) -> Option<usize> {
+        Generic::new(escapes).byte_byte_first_escape(haystack)
+    }
+}
+
+#[cfg(all(test, target_arch = "x86_64", feature = "std"))]
+mod test {
+    use alloc::{format, string::String, vec::Vec};
+    use core::arch::x86_64::{__m128i, __m256i};
+
+    use super::*;
+
+    const TABLES: [&[(u8, &str)]; 3] = [
+        &[
+            (b'"', "&quot;"),
+            (b'&', "&amp;"),
+            (b'\'', "&#x27;"),
+            (b'/', "&#x2f;"),
+            (b'<', "&lt;"),
+            (b'>', "&gt;"),
+        ],
+        &[(b'\0', "\\0"), (0x7F, "\\x7f")],
+        &[(b'A', "a")],
+    ];
+
+    fn reference(escaper: &DynamicEscaper, haystack: &str) -> String {
+        let mut escaped = String::new();
+        for c in haystack.chars() {
+            match u8::try_from(c)
+                .ok()
+                .and_then(|byte| escaper.replacement(byte))
+            {
+                Some(replacement) => escaped.push_str(replacement),
+                None => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    /// Escapes `haystack` with the vector `V`, which the CPU must support.
+    unsafe fn escape<V: Vector, const SHUFFLE: bool>(
+        escaper: &DynamicEscaper,
+        haystack: &str,
+    ) -> Vec<u8> {
+        let mut escaped = Vec::new();
+        let op = Escape(WriterVec::new(&mut escaped));
+        let escapes = Lookup::<V, SHUFFLE>::new(escaper);
+        let _ = if haystack.len() < V::BYTES {
+            op.scalar(escapes, haystack.as_bytes())
+        } else {
+            op.vector(escapes, haystack.as_bytes())
+        };
+        escaped
+    }
+
+    #[test]
+    fn test_backends() {
+        // The range masked by SSE2 spans from the lowest to the highest
+        // escaped byte, with false positives in between
+        for table in TABLES {
+            let escaper = DynamicEscaper::new(table).unwrap();
+            for size in [16, 17, 31, 32, 33, 64, 130] {
+                for byte in 0..0x80u8 {
+                    let c = char::from(byte);
+                    let clean = "é".repeat(size / 4) + &"b".repeat(size % 4);
+                    let haystack = format!("{clean}{c}{clean}{c}");
+                    let expected = reference(&escaper, &haystack);
+
+                    // SAFETY: SSE2 is part of x86_64
+                    let escaped = unsafe { escape::<__m128i, false>(&escaper, &haystack) };
+                    assert_eq!(escaped, expected.as_bytes(), "sse2 {size} {byte:#04x}");
+                    if std::is_x86_feature_detected!("ssse3") {
+                        // SAFETY: SSSE3 is available
+                        let escaped = unsafe { escape::<__m128i, true>(&escaper, &haystack) };
+                        assert_eq!(escaped, expected.as_bytes(), "ssse3 {size} {byte:#04x}");
+                    }
+                    if std::is_x86_feature_detected!("avx2") {
+                        // SAFETY: AVX2 is available
+                        let escaped = unsafe { escape::<__m256i, true>(&escaper, &haystack) };
+                        assert_eq!(escaped, expected.as_bytes(), "avx2 {size} {byte:#04x}");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::Overflow;
+
+/// An escaper generated from an escape table.
+///
+/// Implemented by the unit struct created with [`escaper!`](crate::escaper!),
+/// alongside the functions created with [`escape_builder!`], so that generic
+/// code can be written over any escaper. Every method forwards to the function
+/// of the same name, gated by the same features.
+///
+/// Only escapers writing valid UTF-8 implement it, not those escaping bytes
+/// above `0x7F`.
+pub trait Escaper {
+    /// The escaped bytes and their replacements, sorted by byte.
+    const MAPPINGS: &'static [(u8, &'static str)];
+
+    /// The escaped sequences and their replacements, sorted by sequence.
+    ///
+    /// Sequences of several bytes, such as `]]>`, and characters outside ASCII
+    /// are escaped as a whole.
+    const SEQUENCES: &'static [(&'static [u8], &'static str)];
+
+    /// The length of the longest replacement of the table, or `usize::MAX` if
+    /// it is unknown.
+    const MAX_REPLACEMENT_LEN: usize;
+
+    /// Returns the exact length of `haystack` once escaped.
+    fn escaped_len(haystack: &str) -> usize;
+
+    /// Returns an upper bound of the escaped length of any input of `len` bytes.
+    fn max_escaped_len(len: usize) -> usize;
+
+    /// Returns whether `haystack` contains anything to escape.
+    fn needs_escape(haystack: &str) -> bool;
+
+    /// Returns the byte offset of the first escape in `haystack`.
+    fn first_escape_position(haystack: &str) -> Option<usize>;
+
+    /// Escapes `haystack` into `buffer`, returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Overflow`] when `buffer` is too small.
+    fn escape_slice(haystack: &str, buffer: &mut [u8]) -> Result<usize, Overflow>;
+
+    /// Escapes `haystack` and appends the result to `buffer`.
+    #[cfg(feature = "string")]
+    fn escape_string(haystack: &str, buffer: &mut alloc::string::String);
+
+    /// Escapes `haystack` and appends the result to `buffer`.
+    #[cfg(feature = "bytes")]
+    fn escape_bytes(haystack: &str, buffer: &mut alloc::vec::Vec<u8>);
+
+    /// Returns a value escaping `haystack` lazily when formatted.
+    #[cfg(feature = "fmt")]
+    fn escape_fmt(haystack: &str) -> impl core::fmt::Display + '_;
+
+    /// Escapes `haystack` into `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `writer` fails.
+    #[cfg(feature = "fmt")]
+    fn escape_fmt_into<W: core::fmt::Write + ?Sized>(
+        haystack: &str,
+        writer: &mut W,
+    ) -> core::fmt::Result;
+}
+
+/// A macro for creating a unit struct implementing [`Escaper`]
+///
+/// The functions created with [`escape_builder!`] must be in scope.
+///
+/// # Parameters
+/// - `$name`: The name of the struct, with its attributes and visibility
+/// - `$builder`: The type [`crate::EscapesBuilder`] of the builder
+#[macro_export]
+macro_rules! escaper {
+    ($(#[$meta:meta])* $vis:vis $name:ident, $builder:ty) => {
+        /// The escaper of the functions of this module, implementing `Escaper`.
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        $vis struct $name;
+
+        impl $crate::Escaper for $name {
+            const MAPPINGS: &'static [(u8, &'static str)] =
+                <<$builder as $crate::EscapesBuilder>::Escapes<()> as $crate::Escapes>::MAPPINGS;
+
+            const SEQUENCES: &'static [(&'static [u8], &'static str)] =
+                <<$builder as $crate::EscapesBuilder>::Escapes<()> as $crate::Escapes>::SEQUENCES;
+
+            const MAX_REPLACEMENT_LEN: usize = MAX_REPLACEMENT_LEN;
+
+            #[inline]
+            fn escaped_len(haystack: &str) -> usize {
+                escaped_len(haystack)
+            }
+
+            #[inline]
+            fn max_escaped_len(len: usize) -> usize {
+                max_escaped_len(len)
+            }
+
+            #[inline]
+            fn needs_escape(haystack: &str) -> bool {
+                needs_escape(haystack)
+            }
+
+            #[inline]
+            fn first_escape_position(haystack: &str) -> Option<usize> {
+                first_escape_position(haystack)
+            }
+
+            #[inline]
+            fn escape_slice(
+                haystack: &str,
+                buffer: &mut [u8],
+            ) -> core::result::Result<usize, $crate::Overflow> {
+                escape_slice(haystack, buffer)
+            }
+
+            $crate::escaper_string!();
+
+            $crate::escaper_bytes!();
+
+            $crate::escaper_fmt!();
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "string")]
+macro_rules! escaper_string {
+    () => {
+        #[inline]
+        fn escape_string(haystack: &str, buffer: &mut $crate::__alloc::string::String) {
+            escape_string(haystack, buffer)
+        }
+    };
+}
+
+#[cfg(not(feature = "string"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! escaper_string {
+    () => {};
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "bytes")]
+macro_rules! escaper_bytes {
+    () => {
+        #[inline]
+        fn escape_bytes(haystack: &str, buffer: &mut $crate::__alloc::vec::Vec<u8>) {
+            escape_bytes(haystack, buffer)
+        }
+    };
+}
+
+#[cfg(not(feature = "bytes"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! escaper_bytes {
+    () => {};
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "fmt")]
+macro_rules! escaper_fmt {
+    () => {
+        #[inline]
+        fn escape_fmt(haystack: &str) -> impl core::fmt::Display + '_ {
+            escape_fmt(haystack)
+        }
+
+        #[inline]
+        fn escape_fmt_into<W: core::fmt::Write + ?Sized>(
+            haystack: &str,
+            writer: &mut W,
+        ) -> core::fmt::Result {
+            escape_fmt_into(haystack, writer)
+        }
+    };
+}
+
+#[cfg(not(feature = "fmt"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! escaper_fmt {
+    () => {};
+}
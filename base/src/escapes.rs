@@ -1,8 +1,8 @@
-use core::{fmt, str};
+use core::fmt;
 
 use crate::{
     Vector,
-    writer::{Result, Writer, write, write_slice},
+    writer::{Result, Writer, write, write_span},
 };
 
 /// A builder trait for creating instances of types that implement the `Escapes` trait.
@@ -34,6 +34,37 @@ pub trait Escapes: Copy + fmt::Debug {
     /// Indicates whether the escape sequence may produce false positives.
     const FALSE_POSITIVE: bool;
 
+    /// The length of the longest replacement returned by [`Escapes::escape`].
+    ///
+    /// Only `max_escaped_len` and the `*_reserved` functions rely on it.
+    /// Defaults to `usize::MAX`, an unknown length: `max_escaped_len` then
+    /// saturates, and the `*_reserved` functions grow their buffer as they
+    /// write instead of reserving the worst case up front. Understating it
+    /// is not unsound, the `*_reserved` functions grow their buffer past the
+    /// reservation as well.
+    const MAX_REPLACEMENT_LEN: usize = usize::MAX;
+
+    /// Indicates whether [`Escapes::masking`] uses [`Vector::shuffle`], which
+    /// needs SSSE3 besides SSE2 on x86_64. CPUs with SSE2 only then run the
+    /// second builder given to `escape_builder!`, or the scalar fallback.
+    const SHUFFLE: bool = false;
+
+    /// Indicates whether valid UTF-8 input stays valid once escaped.
+    ///
+    /// Tables escaping bytes above `0x7F` unset it: replacing one of them
+    /// splits a multi-byte character, so the spans between escapes are
+    /// written with [`Writer::write_bytes`] and only the functions of
+    /// `escape_builder!(@bytes ..)`, taking bytes, may be used.
+    const UTF8: bool = true;
+
+    /// The escaped bytes and their replacements, sorted by byte, as exposed by
+    /// [`crate::Escaper::MAPPINGS`].
+    const MAPPINGS: &'static [(u8, &'static str)] = &[];
+
+    /// The escaped sequences and their replacements, sorted by sequence, as
+    /// exposed by [`crate::Escaper::SEQUENCES`].
+    const SEQUENCES: &'static [(&'static [u8], &'static str)] = &[];
+
     /// The vector type used for masking operations.
     type Vector: Vector;
 
@@ -64,17 +95,50 @@ pub trait Escapes: Copy + fmt::Debug {
     /// The position of the character.
     fn position(c: u8) -> usize;
 
-    /// Escapes a string by applying escape sequences and writing the result using a writer.
+    /// Returns the replacement of the escaped sequence starting at `at` and
+    /// the number of bytes it replaces.
+    ///
+    /// Only meaningful at the bytes flagged by [`Escapes::masking`] or
+    /// [`Escapes::byte_byte_compare`]. By default a single byte is replaced
+    /// through [`Escapes::position`], and false positives are rejected when
+    /// [`Escapes::FALSE_POSITIVE`] is set. Tables escaping sequences of bytes,
+    /// such as `]]>` or a character outside ASCII, flag their first bytes and
+    /// override this method to match the whole sequence before `end`,
+    /// returning `None` for input only sharing the first byte.
+    ///
+    /// # Parameters
+    /// - `at`: A pointer to the flagged byte.
+    /// - `end`: A pointer to the end of the haystack.
+    ///
+    /// # Returns
+    /// The replacement and the length of the escaped sequence, or `None` if
+    /// nothing is escaped at `at`.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that `at` is below `end`, both pointing into the same valid haystack.
+    #[inline(always)]
+    unsafe fn escape_at(at: *const u8, end: *const u8) -> Option<(&'static str, usize)> {
+        let _ = end;
+        let c = Self::position(unsafe { *at });
+        if !Self::FALSE_POSITIVE || c < Self::ESCAPE_LEN {
+            Some((Self::escape(c), 1))
+        } else {
+            None
+        }
+    }
+
+    /// Escapes a slice of bytes by applying escape sequences and writing the result using a writer.
     ///
     /// # Parameters
-    /// - `haystack`: The input string to be processed for escape sequences.
+    /// - `haystack`: The input bytes to be processed for escape sequences.
     /// - `writer`: A mutable writer function to handle the escaped output.
     ///
     /// # Returns
     /// A `Result` indicating the success or failure of the escape operation.
     #[inline(always)]
     fn byte_byte_escape<const FMT: bool, W: Writer<FMT>>(
-        haystack: &str,
+        haystack: &[u8],
         mut writer: W,
     ) -> Result<W::Error> {
         let len = haystack.len();
@@ -94,7 +158,8 @@ pub trait Escapes: Copy + fmt::Debug {
     ///
     /// # Safety
     /// This function is unsafe because it operates on raw pointers and assumes
-    /// that the memory between `haystack` and `end` is valid and properly aligned.
+    /// that the memory between `haystack` and `end` is valid and properly aligned,
+    /// and valid UTF-8 when [`Escapes::UTF8`] is set.
     #[inline(always)]
     unsafe fn byte_byte_escape_raw<const FMT: bool, W: Writer<FMT>>(
         start: *const u8,
@@ -105,20 +170,20 @@ pub trait Escapes: Copy + fmt::Debug {
             let mut written = start;
             let mut cur = start;
             while cur < end {
-                let c = *cur;
                 // TODO: improve performance
-                if Self::byte_byte_compare(c) {
+                if let Some((escaped, len)) = Self::byte_byte_escape_at(cur, end) {
                     if written < cur {
-                        write_slice(written, cur, writer)?;
+                        write_span::<Self, FMT, W>(written, cur, writer)?;
                     }
-                    let escaped = Self::escape(Self::position(c));
                     write(escaped, writer)?;
-                    written = cur.add(1);
+                    cur = cur.add(len);
+                    written = cur;
+                } else {
+                    cur = cur.add(1);
                 }
-                cur = cur.add(1);
             }
             if written < end {
-                write_slice(written, end, writer)?;
+                write_span::<Self, FMT, W>(written, end, writer)?;
             }
             Ok(())
         }
@@ -132,4 +197,124 @@ pub trait Escapes: Copy + fmt::Debug {
     /// # Returns
     /// `true` if the byte should be escaped, `false` otherwise.
     fn byte_byte_compare(c: u8) -> bool;
+
+    /// Returns the replacement of the escaped sequence starting at `at` and
+    /// the number of bytes it replaces, checking the byte with
+    /// [`Escapes::byte_byte_compare`] first.
+    ///
+    /// # Parameters
+    /// - `at`: A pointer to the byte to check.
+    /// - `end`: A pointer to the end of the haystack.
+    ///
+    /// # Returns
+    /// The replacement and the length of the escaped sequence, or `None` if
+    /// nothing is escaped at `at`.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that `at` is below `end`, both pointing into the same valid haystack.
+    #[inline(always)]
+    unsafe fn byte_byte_escape_at(at: *const u8, end: *const u8) -> Option<(&'static str, usize)> {
+        unsafe {
+            if Self::byte_byte_compare(*at) {
+                Self::escape_at(at, end)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Finds the first byte of a slice that should be escaped, one byte at a time.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input bytes to be scanned.
+    ///
+    /// # Returns
+    /// The offset of the first byte to escape, or `None` if there is none.
+    #[inline(always)]
+    fn byte_byte_first_escape(haystack: &[u8]) -> Option<usize> {
+        let start = haystack.as_ptr();
+        let end = unsafe { start.add(haystack.len()) };
+        (0..haystack.len())
+            .find(|&i| unsafe { Self::byte_byte_escape_at(start.add(i), end) }.is_some())
+    }
+
+    /// Computes the length of the escaped form of a slice, one byte at a time.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input bytes to be measured.
+    ///
+    /// # Returns
+    /// The exact number of bytes the escaped output would take.
+    #[inline(always)]
+    fn byte_byte_escaped_len(haystack: &[u8]) -> usize {
+        let start = haystack.as_ptr();
+        let end = unsafe { start.add(haystack.len()) };
+        let mut len = haystack.len();
+        let mut i = 0;
+        while i < haystack.len() {
+            match unsafe { Self::byte_byte_escape_at(start.add(i), end) } {
+                Some((escaped, n)) => {
+                    len = len - n + escaped.len();
+                    i += n;
+                }
+                None => i += 1,
+            }
+        }
+        len
+    }
+}
+
+/// The search run by the vectorized escape functions: the masking of the
+/// vectors, and the replacements looked up through the value.
+///
+/// Implemented by every [`Escapes`], and by the escapers built at runtime,
+/// whose tables are held by the value rather than in statics.
+pub(crate) trait Search: Copy {
+    /// Indicates whether the masking may produce false positives.
+    const FALSE_POSITIVE: bool;
+
+    /// Indicates whether valid UTF-8 input stays valid once escaped.
+    const UTF8: bool;
+
+    /// The vector type used for the masking.
+    type Vector: Vector;
+
+    /// Masks the bytes of `vector2` that may need to be escaped.
+    fn masking(&self, vector2: Self::Vector) -> Self::Vector;
+
+    /// Returns the replacement of the escaped sequence starting at `at` and
+    /// the number of bytes it replaces, or `None` if nothing is escaped at
+    /// `at`.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that `at` is below `end`, both pointing into the same valid haystack.
+    unsafe fn replacement_at(&self, at: *const u8, end: *const u8) -> Option<(&str, usize)>;
+
+    /// Compares a byte to determine if it should be escaped.
+    fn compare(&self, c: u8) -> bool;
+}
+
+impl<E: Escapes> Search for E {
+    const FALSE_POSITIVE: bool = <E as Escapes>::FALSE_POSITIVE;
+
+    const UTF8: bool = <E as Escapes>::UTF8;
+
+    type Vector = <E as Escapes>::Vector;
+
+    #[inline(always)]
+    fn masking(&self, vector2: Self::Vector) -> Self::Vector {
+        Escapes::masking(self, vector2)
+    }
+
+    #[inline(always)]
+    unsafe fn replacement_at(&self, at: *const u8, end: *const u8) -> Option<(&str, usize)> {
+        unsafe { E::escape_at(at, end) }
+    }
+
+    #[inline(always)]
+    fn compare(&self, c: u8) -> bool {
+        E::byte_byte_compare(c)
+    }
 }
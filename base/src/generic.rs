@@ -2,16 +2,28 @@
 // Adapted from https://github.com/BurntSushi/memchr/blob/master/src/arch/generic/memchr.rs
 
 use crate::{
-    Escapes, Vector,
+    Unescapes, Vector,
+    escapes::Search,
     ext::Pointer,
+    unescapes::{UnescapeFailure, byte_byte_unescape_raw, unescape_at},
     vector::MoveMask,
-    writer::{Result, Writer, write, write_slice},
+    writer::{Result, Writer, write, write_slice, write_span},
 };
 
+/// Running totals used while computing the escaped length of a haystack.
+struct Count {
+    /// Number of bytes that get replaced.
+    matched: usize,
+    /// Total length of their replacements.
+    replaced: usize,
+    /// The end of the last escaped sequence.
+    escaped: *const u8,
+}
+
 /// A generic structure for handling escape sequences in a vectorized manner.
 ///
 /// # Type Parameters
-/// - `E`: The escape type implementing the `Escapes` trait.
+/// - `E`: The escape type implementing the `Search` trait.
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Generic<E> {
     escapes: E,
@@ -19,7 +31,7 @@ pub(crate) struct Generic<E> {
 
 impl<E> Generic<E>
 where
-    E: Escapes,
+    E: Search,
 {
     /// The number of bytes processed per iteration in the search loop.
     const LOOP_SIZE: usize = 4 * E::Vector::BYTES;
@@ -33,10 +45,10 @@ where
         Generic { escapes }
     }
 
-    /// Escapes the input string by applying the escape sequences defined in the `Escapes` trait.
+    /// Escapes the input bytes by applying the escape sequences of `E`.
     ///
     /// # Parameters
-    /// - `haystack`: The input string to be processed for escape sequences.
+    /// - `haystack`: The input bytes to be processed for escape sequences.
     /// - `writer`: The function to write the escaped output.
     ///
     /// # Returns
@@ -44,7 +56,7 @@ where
     #[inline(always)]
     pub(crate) fn escape<const FMT: bool, W: Writer<FMT>>(
         &mut self,
-        haystack: &str,
+        haystack: &[u8],
         mut writer: W,
     ) -> Result<W::Error> {
         let len = haystack.len();
@@ -87,7 +99,7 @@ where
             if align > 0 {
                 let x = E::Vector::load_unaligned(start);
                 let mask = self.escapes.masking(x).movemask();
-                self.write_mask_unaligned(mask, start, align, &mut written, writer)?;
+                self.write_mask_unaligned(mask, start, align, end, &mut written, writer)?;
             }
 
             // Set `cur` to the first V-aligned pointer greater than `start`.
@@ -110,11 +122,12 @@ where
                     let or2 = eqc.or(eqd);
                     let or3 = or1.or(or2);
                     if or3.movemask_will_have_non_zero() {
-                        self.write_mask(a, eqa.movemask(), cur, &mut written, writer)?;
+                        self.write_mask(a, eqa.movemask(), cur, end, &mut written, writer)?;
                         self.write_mask(
                             b,
                             eqb.movemask(),
                             cur.add(E::Vector::BYTES),
+                            end,
                             &mut written,
                             writer,
                         )?;
@@ -122,6 +135,7 @@ where
                             c,
                             eqc.movemask(),
                             cur.add(E::Vector::BYTES * 2),
+                            end,
                             &mut written,
                             writer,
                         )?;
@@ -129,18 +143,20 @@ where
                             d,
                             eqd.movemask(),
                             cur.add(E::Vector::BYTES * 3),
+                            end,
                             &mut written,
                             writer,
                         )?;
                     } else {
-                        if !FMT {
+                        // An escaped sequence may end past `cur`, its rest is already written
+                        if !FMT && written <= cur {
                             if written < cur {
-                                write_slice(written, cur, writer)?;
+                                write_span::<E, FMT, W>(written, cur, writer)?;
                             }
-                            writer.write_vector(a);
-                            writer.write_vector(b);
-                            writer.write_vector(c);
-                            writer.write_vector(d);
+                            writer.write_vector(a)?;
+                            writer.write_vector(b)?;
+                            writer.write_vector(c)?;
+                            writer.write_vector(d)?;
                             written = cur.add(Self::LOOP_SIZE);
                         }
                     }
@@ -153,7 +169,7 @@ where
                 let v = E::Vector::load_aligned(cur);
                 let mask = self.escapes.masking(v).movemask();
 
-                self.write_mask(v, mask, cur, &mut written, writer)?;
+                self.write_mask(v, mask, cur, end, &mut written, writer)?;
                 cur = cur.add(E::Vector::BYTES);
             }
 
@@ -167,23 +183,434 @@ where
                 let x = E::Vector::load_unaligned(start);
                 let mask = self.escapes.masking(x).movemask().shr(rest);
 
-                self.write_mask_unaligned(mask, cur, remaining, &mut written, writer)?;
+                self.write_mask_unaligned(mask, cur, remaining, end, &mut written, writer)?;
             }
 
             if written < end {
-                write_slice(written, end, writer)?;
+                write_span::<E, FMT, W>(written, end, writer)?;
             }
 
             Ok(())
         }
     }
 
+    /// Computes the length of the escaped form of the input bytes without
+    /// producing any output.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input bytes to be measured.
+    ///
+    /// # Returns
+    /// The exact number of bytes the escaped output would take.
+    #[inline(always)]
+    pub(crate) fn escaped_len(&self, haystack: &[u8]) -> usize {
+        let len = haystack.len();
+        let cur = haystack.as_ptr();
+        unsafe { self.escaped_len_raw(cur, cur.add(len)) }
+    }
+
+    /// Computes the length of the escaped form of the data between the `start`
+    /// and `end` pointers.
+    ///
+    /// # Parameters
+    /// - `start`: The starting pointer of the data to be measured.
+    /// - `end`: The ending pointer of the data to be measured.
+    ///
+    /// # Returns
+    /// The exact number of bytes the escaped output would take.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that the memory between `start` and `end` is valid and properly aligned.
+    #[inline(always)]
+    pub(crate) unsafe fn escaped_len_raw(&self, start: *const u8, end: *const u8) -> usize {
+        unsafe {
+            let len = end.distance(start);
+            // Matched bytes are subtracted and their replacements added back.
+            let mut count = Count {
+                matched: 0,
+                replaced: 0,
+                escaped: start,
+            };
+
+            debug_assert!(
+                len >= E::Vector::BYTES,
+                "haystack has length {}, but must be at least {}",
+                len,
+                E::Vector::BYTES
+            );
+
+            let align = E::Vector::BYTES - (start.to_usize() & E::Vector::ALIGN);
+            if align > 0 {
+                let x = E::Vector::load_unaligned(start);
+                let mask = self.escapes.masking(x).movemask();
+                self.count_mask(mask, start, align, end, &mut count);
+            }
+
+            let mut cur = start.add(align);
+            debug_assert!(cur > start && end.sub(E::Vector::BYTES) >= start);
+
+            if len >= Self::LOOP_SIZE {
+                while cur <= end.sub(Self::LOOP_SIZE) {
+                    debug_assert_eq!(0, cur.to_usize() % E::Vector::BYTES);
+
+                    let a = E::Vector::load_aligned(cur);
+                    let b = E::Vector::load_aligned(cur.add(E::Vector::BYTES));
+                    let c = E::Vector::load_aligned(cur.add(2 * E::Vector::BYTES));
+                    let d = E::Vector::load_aligned(cur.add(3 * E::Vector::BYTES));
+                    let eqa = self.escapes.masking(a);
+                    let eqb = self.escapes.masking(b);
+                    let eqc = self.escapes.masking(c);
+                    let eqd = self.escapes.masking(d);
+                    let or1 = eqa.or(eqb);
+                    let or2 = eqc.or(eqd);
+                    let or3 = or1.or(or2);
+                    if or3.movemask_will_have_non_zero() {
+                        self.count_mask(eqa.movemask(), cur, E::Vector::BYTES, end, &mut count);
+                        self.count_mask(
+                            eqb.movemask(),
+                            cur.add(E::Vector::BYTES),
+                            E::Vector::BYTES,
+                            end,
+                            &mut count,
+                        );
+                        self.count_mask(
+                            eqc.movemask(),
+                            cur.add(E::Vector::BYTES * 2),
+                            E::Vector::BYTES,
+                            end,
+                            &mut count,
+                        );
+                        self.count_mask(
+                            eqd.movemask(),
+                            cur.add(E::Vector::BYTES * 3),
+                            E::Vector::BYTES,
+                            end,
+                            &mut count,
+                        );
+                    }
+                    cur = cur.add(Self::LOOP_SIZE);
+                }
+            }
+            while cur <= end.sub(E::Vector::BYTES) {
+                let v = E::Vector::load_aligned(cur);
+                let mask = self.escapes.masking(v).movemask();
+                self.count_mask(mask, cur, E::Vector::BYTES, end, &mut count);
+                cur = cur.add(E::Vector::BYTES);
+            }
+
+            if cur < end {
+                let remaining = end.distance(cur);
+                let rest = (E::Vector::BYTES - remaining) as u32;
+                let x = E::Vector::load_unaligned(cur.sub(E::Vector::BYTES - remaining));
+                let mask = self.escapes.masking(x).movemask().shr(rest);
+                self.count_mask(mask, cur, remaining, end, &mut count);
+            }
+
+            len - count.matched + count.replaced
+        }
+    }
+
+    /// Finds the first byte of the input bytes that should be escaped.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input bytes to be scanned.
+    ///
+    /// # Returns
+    /// The offset of the first byte to escape, or `None` if there is none.
+    #[inline(always)]
+    pub(crate) fn first_escape(&self, haystack: &[u8]) -> Option<usize> {
+        let len = haystack.len();
+        let cur = haystack.as_ptr();
+        unsafe { self.first_escape_raw(cur, cur.add(len)) }
+    }
+
+    /// Finds the first byte between the `start` and `end` pointers that should
+    /// be escaped.
+    ///
+    /// # Parameters
+    /// - `start`: The starting pointer of the data to be scanned.
+    /// - `end`: The ending pointer of the data to be scanned.
+    ///
+    /// # Returns
+    /// The offset from `start` of the first byte to escape, or `None` if there
+    /// is none.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that the memory between `start` and `end` is valid and properly aligned.
+    #[inline(always)]
+    pub(crate) unsafe fn first_escape_raw(
+        &self,
+        start: *const u8,
+        end: *const u8,
+    ) -> Option<usize> {
+        unsafe {
+            let len = end.distance(start);
+
+            debug_assert!(
+                len >= E::Vector::BYTES,
+                "haystack has length {}, but must be at least {}",
+                len,
+                E::Vector::BYTES
+            );
+
+            let align = E::Vector::BYTES - (start.to_usize() & E::Vector::ALIGN);
+            if align > 0 {
+                let x = E::Vector::load_unaligned(start);
+                let mask = self.escapes.masking(x).movemask();
+                if let Some(at) = self.first_in_mask(mask, start, align, end) {
+                    return Some(at.distance(start));
+                }
+            }
+
+            let mut cur = start.add(align);
+            debug_assert!(cur > start && end.sub(E::Vector::BYTES) >= start);
+
+            if len >= Self::LOOP_SIZE {
+                while cur <= end.sub(Self::LOOP_SIZE) {
+                    debug_assert_eq!(0, cur.to_usize() % E::Vector::BYTES);
+
+                    let a = E::Vector::load_aligned(cur);
+                    let b = E::Vector::load_aligned(cur.add(E::Vector::BYTES));
+                    let c = E::Vector::load_aligned(cur.add(2 * E::Vector::BYTES));
+                    let d = E::Vector::load_aligned(cur.add(3 * E::Vector::BYTES));
+                    let eqa = self.escapes.masking(a);
+                    let eqb = self.escapes.masking(b);
+                    let eqc = self.escapes.masking(c);
+                    let eqd = self.escapes.masking(d);
+                    let or1 = eqa.or(eqb);
+                    let or2 = eqc.or(eqd);
+                    let or3 = or1.or(or2);
+                    if or3.movemask_will_have_non_zero() {
+                        for (i, eq) in [eqa, eqb, eqc, eqd].into_iter().enumerate() {
+                            let at = cur.add(i * E::Vector::BYTES);
+                            if let Some(at) =
+                                self.first_in_mask(eq.movemask(), at, E::Vector::BYTES, end)
+                            {
+                                return Some(at.distance(start));
+                            }
+                        }
+                    }
+                    cur = cur.add(Self::LOOP_SIZE);
+                }
+            }
+            while cur <= end.sub(E::Vector::BYTES) {
+                let v = E::Vector::load_aligned(cur);
+                let mask = self.escapes.masking(v).movemask();
+                if let Some(at) = self.first_in_mask(mask, cur, E::Vector::BYTES, end) {
+                    return Some(at.distance(start));
+                }
+                cur = cur.add(E::Vector::BYTES);
+            }
+
+            if cur < end {
+                let remaining = end.distance(cur);
+                let rest = (E::Vector::BYTES - remaining) as u32;
+                let x = E::Vector::load_unaligned(cur.sub(E::Vector::BYTES - remaining));
+                let mask = self.escapes.masking(x).movemask().shr(rest);
+                if let Some(at) = self.first_in_mask(mask, cur, remaining, end) {
+                    return Some(at.distance(start));
+                }
+            }
+
+            None
+        }
+    }
+
+    /// Escapes the input bytes one byte at a time, for inputs shorter than a
+    /// vector or CPUs without one.
+    ///
+    /// Unlike [`crate::Escapes::byte_byte_escape`], the bytes are looked up
+    /// through the value of the `Search`.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input bytes to be processed for escape sequences.
+    /// - `writer`: The function to write the escaped output.
+    ///
+    /// # Returns
+    /// A `Result` indicating the success or failure of the escape operation.
+    #[inline(always)]
+    pub(crate) fn byte_byte_escape<const FMT: bool, W: Writer<FMT>>(
+        &self,
+        haystack: &[u8],
+        mut writer: W,
+    ) -> Result<W::Error> {
+        unsafe {
+            let start = haystack.as_ptr();
+            let end = start.add(haystack.len());
+            let mut written = start;
+            let mut cur = start;
+            while cur < end {
+                if let Some((escaped, len)) = self.byte_byte_escape_at(cur, end) {
+                    if written < cur {
+                        write_span::<E, FMT, W>(written, cur, &mut writer)?;
+                    }
+                    write(escaped, &mut writer)?;
+                    cur = cur.add(len);
+                    written = cur;
+                } else {
+                    cur = cur.add(1);
+                }
+            }
+            if written < end {
+                write_span::<E, FMT, W>(written, end, &mut writer)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Computes the length of the escaped form of the input bytes, one byte
+    /// at a time.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input bytes to be measured.
+    ///
+    /// # Returns
+    /// The exact number of bytes the escaped output would take.
+    #[inline(always)]
+    pub(crate) fn byte_byte_escaped_len(&self, haystack: &[u8]) -> usize {
+        let start = haystack.as_ptr();
+        let end = unsafe { start.add(haystack.len()) };
+        let mut len = haystack.len();
+        let mut i = 0;
+        while i < haystack.len() {
+            match unsafe { self.byte_byte_escape_at(start.add(i), end) } {
+                Some((escaped, n)) => {
+                    len = len - n + escaped.len();
+                    i += n;
+                }
+                None => i += 1,
+            }
+        }
+        len
+    }
+
+    /// Finds the first byte of the input bytes that should be escaped, one
+    /// byte at a time.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input bytes to be scanned.
+    ///
+    /// # Returns
+    /// The offset of the first byte to escape, or `None` if there is none.
+    #[inline(always)]
+    pub(crate) fn byte_byte_first_escape(&self, haystack: &[u8]) -> Option<usize> {
+        let start = haystack.as_ptr();
+        let end = unsafe { start.add(haystack.len()) };
+        (0..haystack.len())
+            .find(|&i| unsafe { self.byte_byte_escape_at(start.add(i), end) }.is_some())
+    }
+
+    /// Returns the replacement of the escaped sequence starting at `at` and
+    /// the number of bytes it replaces, checking the byte with
+    /// [`Search::compare`] first.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that `at` is below `end`, both pointing into the same valid haystack.
+    #[inline(always)]
+    pub(crate) unsafe fn byte_byte_escape_at(
+        &self,
+        at: *const u8,
+        end: *const u8,
+    ) -> Option<(&str, usize)> {
+        unsafe {
+            if self.escapes.compare(*at) {
+                self.escapes.replacement_at(at, end)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns a pointer to the first byte of a mask that should be escaped,
+    /// re-checking candidates when the masking may yield false positives.
+    ///
+    /// # Parameters
+    /// - `mask`: The mask indicating which bytes may need to be escaped.
+    /// - `cur`: The current pointer in the data.
+    /// - `limit`: Only offsets lower than `limit` are taken into account.
+    /// - `end`: The end of the data.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that the memory is valid.
+    #[inline(always)]
+    unsafe fn first_in_mask(
+        &self,
+        mut mask: <<E as Search>::Vector as Vector>::Mask,
+        cur: *const u8,
+        limit: usize,
+        end: *const u8,
+    ) -> Option<*const u8> {
+        unsafe {
+            while mask.has_non_zero() {
+                let offset = mask.first_offset();
+                if limit <= offset {
+                    break;
+                }
+                let at = cur.add(offset);
+                if !E::FALSE_POSITIVE || self.escapes.replacement_at(at, end).is_some() {
+                    return Some(at);
+                }
+                mask = mask.clear_least_significant_bit();
+            }
+            None
+        }
+    }
+
+    /// Accumulates the matches of a mask into `count`.
+    ///
+    /// # Parameters
+    /// - `mask`: The mask indicating which bytes need to be escaped.
+    /// - `cur`: The current pointer in the data.
+    /// - `limit`: Only offsets lower than `limit` are taken into account.
+    /// - `end`: The end of the data.
+    /// - `count`: The running count of matched bytes and replacement lengths.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that the memory is valid.
+    #[inline(always)]
+    unsafe fn count_mask(
+        &self,
+        mut mask: <<E as Search>::Vector as Vector>::Mask,
+        cur: *const u8,
+        limit: usize,
+        end: *const u8,
+        count: &mut Count,
+    ) {
+        unsafe {
+            while mask.has_non_zero() {
+                let offset = mask.first_offset();
+                if limit <= offset {
+                    break;
+                }
+                let at = cur.add(offset);
+                // A candidate inside an escaped sequence is part of it
+                let escaped = if count.escaped <= at {
+                    self.escapes.replacement_at(at, end)
+                } else {
+                    None
+                };
+                if let Some((escaped, len)) = escaped {
+                    count.matched += len;
+                    count.replaced += escaped.len();
+                    count.escaped = at.add(len);
+                }
+                mask = mask.clear_least_significant_bit();
+            }
+        }
+    }
+
     /// Writes a single step of the escape process, handling any necessary escapes.
     ///
     /// # Parameters
     /// - `mask`: The mask indicating which bytes need to be escaped.
     /// - `cur`: The current pointer in the data.
     /// - `offset`: The offset from the current pointer.
+    /// - `end`: The end of the data.
     /// - `written`: A mutable reference to the pointer indicating the last written position.
     /// - `writer`: The function to write the escaped output.
     ///
@@ -195,21 +622,28 @@ where
     /// that the memory is valid.
     #[inline(always)]
     unsafe fn write_step<const FMT: bool, W: Writer<FMT>>(
-        mask: <<E as Escapes>::Vector as Vector>::Mask,
+        &self,
+        mask: <<E as Search>::Vector as Vector>::Mask,
         cur: *const u8,
         offset: usize,
+        end: *const u8,
         written: &mut *const u8,
         writer: &mut W,
-    ) -> core::result::Result<<<E as Escapes>::Vector as Vector>::Mask, W::Error> {
+    ) -> core::result::Result<<<E as Search>::Vector as Vector>::Mask, W::Error> {
         unsafe {
-            let c = E::position(*cur.add(offset));
-            if !E::FALSE_POSITIVE || c < E::ESCAPE_LEN {
-                let at = cur.add(offset);
+            let at = cur.add(offset);
+            // A candidate inside an escaped sequence is already written
+            let escaped = if *written <= at {
+                self.escapes.replacement_at(at, end)
+            } else {
+                None
+            };
+            if let Some((escaped, len)) = escaped {
                 if *written < at {
-                    write_slice(*written, at, writer)?;
+                    write_span::<E, FMT, W>(*written, at, writer)?;
                 }
-                write(E::escape(c), writer)?;
-                *written = at.add(1);
+                write(escaped, writer)?;
+                *written = at.add(len);
             }
 
             Ok(mask.clear_least_significant_bit())
@@ -222,6 +656,7 @@ where
     /// - `mask`: The mask indicating which bytes need to be escaped.
     /// - `cur`: The current pointer in the data.
     /// - `align`: The alignment offset.
+    /// - `end`: The end of the data.
     /// - `written`: A mutable reference to the pointer indicating the last written position.
     /// - `writer`: The function to write the escaped output.
     ///
@@ -234,9 +669,10 @@ where
     #[inline(always)]
     unsafe fn write_mask_unaligned<const FMT: bool, W: Writer<FMT>>(
         &mut self,
-        mut mask: <<E as Escapes>::Vector as Vector>::Mask,
+        mut mask: <<E as Search>::Vector as Vector>::Mask,
         cur: *const u8,
         align: usize,
+        end: *const u8,
         written: &mut *const u8,
         writer: &mut W,
     ) -> Result<W::Error> {
@@ -244,7 +680,7 @@ where
             if mask.has_non_zero() {
                 let mut offset = mask.first_offset();
                 while offset < align {
-                    mask = Self::write_step(mask, cur, offset, written, writer)?;
+                    mask = self.write_step(mask, cur, offset, end, written, writer)?;
                     if !mask.has_non_zero() {
                         break;
                     }
@@ -260,6 +696,7 @@ where
     /// # Parameters
     /// - `mask`: The mask indicating which bytes need to be escaped.
     /// - `cur`: The current pointer in the data.
+    /// - `end`: The end of the data.
     /// - `written`: A mutable reference to the pointer indicating the last written position.
     /// - `writer`: The function to write the escaped output.
     ///
@@ -272,9 +709,10 @@ where
     #[inline(always)]
     unsafe fn write_mask<const FMT: bool, W: Writer<FMT>>(
         &mut self,
-        vector: <E as Escapes>::Vector,
-        mut mask: <<E as Escapes>::Vector as Vector>::Mask,
+        vector: <E as Search>::Vector,
+        mut mask: <<E as Search>::Vector as Vector>::Mask,
         cur: *const u8,
+        end: *const u8,
         written: &mut *const u8,
         writer: &mut W,
     ) -> Result<W::Error> {
@@ -282,18 +720,19 @@ where
             if mask.has_non_zero() {
                 let mut offset = mask.first_offset();
                 loop {
-                    mask = Self::write_step(mask, cur, offset, written, writer)?;
+                    mask = self.write_step(mask, cur, offset, end, written, writer)?;
                     if !mask.has_non_zero() {
                         break;
                     }
                     offset = mask.first_offset();
                 }
             } else {
-                if !FMT {
+                // An escaped sequence may end past `cur`, its rest is already written
+                if !FMT && *written <= cur {
                     if *written < cur {
-                        write_slice(*written, cur, writer)?;
+                        write_span::<E, FMT, W>(*written, cur, writer)?;
                     }
-                    writer.write_vector(vector);
+                    writer.write_vector(vector)?;
                     *written = cur.add(E::Vector::BYTES);
                 }
             }
@@ -301,3 +740,105 @@ where
         }
     }
 }
+
+/// A generic structure for handling unescape sequences in a vectorized manner.
+///
+/// # Type Parameters
+/// - `U`: The unescape type implementing the `Unescapes` trait.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct GenericUnescape<U> {
+    unescapes: U,
+}
+
+impl<U> GenericUnescape<U>
+where
+    U: Unescapes,
+{
+    /// Creates a new `GenericUnescape` instance with the given unescape handler.
+    ///
+    /// # Parameters
+    /// - `unescapes`: The unescape handler to be used.
+    #[inline(always)]
+    pub(crate) fn new(unescapes: U) -> GenericUnescape<U> {
+        GenericUnescape { unescapes }
+    }
+
+    /// Unescapes the input string by decoding the sequences defined in the `Unescapes` trait.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input string to be unescaped.
+    /// - `writer`: The function to write the unescaped output.
+    ///
+    /// # Returns
+    /// A `Result` indicating the success or failure of the unescape operation.
+    #[inline(always)]
+    pub(crate) fn unescape<const FMT: bool, W: Writer<FMT>>(
+        &self,
+        haystack: &str,
+        mut writer: W,
+    ) -> core::result::Result<(), UnescapeFailure<W::Error>> {
+        let len = haystack.len();
+        let cur = haystack.as_ptr();
+        unsafe { self.unescape_raw(cur, cur.add(len), &mut writer) }
+    }
+
+    /// Unescapes the input data between the `start` and `end` pointers.
+    ///
+    /// Clean vectors are forwarded with [`Writer::write_vector`]. At the first
+    /// lead byte of a vector the sequence is decoded, and scanning resumes
+    /// right after it, since a sequence may span several vectors.
+    ///
+    /// # Parameters
+    /// - `start`: The starting pointer of the data to be unescaped.
+    /// - `end`: The ending pointer of the data to be unescaped.
+    /// - `writer`: The function to write the unescaped output.
+    ///
+    /// # Returns
+    /// A `Result` indicating the success or failure of the unescape operation.
+    ///
+    /// # Safety
+    /// This function is unsafe because it operates on raw pointers and assumes
+    /// that the memory between `start` and `end` is a valid UTF-8 string.
+    #[inline(always)]
+    pub(crate) unsafe fn unescape_raw<const FMT: bool, W: Writer<FMT>>(
+        &self,
+        start: *const u8,
+        end: *const u8,
+        writer: &mut W,
+    ) -> core::result::Result<(), UnescapeFailure<W::Error>> {
+        unsafe {
+            let len = end.distance(start);
+
+            debug_assert!(
+                len >= U::Vector::BYTES,
+                "haystack has length {}, but must be at least {}",
+                len,
+                U::Vector::BYTES
+            );
+
+            let last = end.sub(U::Vector::BYTES);
+            let mut written = start;
+            let mut cur = start;
+            while cur <= last {
+                let v = U::Vector::load_unaligned(cur);
+                let mask = self.unescapes.masking(v).movemask();
+                if mask.has_non_zero() {
+                    let at = cur.add(mask.first_offset());
+                    cur = unescape_at::<U, FMT, W>(start, at, end, &mut written, writer)?;
+                } else {
+                    if !FMT {
+                        if written < cur {
+                            write_slice(written, cur, writer)?;
+                        }
+                        writer.write_vector(v)?;
+                        written = cur.add(U::Vector::BYTES);
+                    }
+                    cur = cur.add(U::Vector::BYTES);
+                }
+            }
+
+            // Handle any remaining bytes that are less than a full vector's worth.
+            byte_byte_unescape_raw::<U, FMT, W>(start, cur, end, &mut written, writer)
+        }
+    }
+}
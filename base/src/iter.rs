@@ -0,0 +1,97 @@
+use core::{fmt, iter::FusedIterator, marker::PhantomData};
+
+use crate::Escapes;
+
+/// An iterator over the spans of an escaped string.
+///
+/// Yields borrowed slices of the input that are forwarded verbatim,
+/// alternating with the `&'static str` replacements of the escaped bytes
+/// and sequences.
+/// Empty spans are never yielded.
+///
+/// # Type Parameters
+/// - `E`: The escapes providing the replacement table.
+pub struct EscapeIter<'a, E: Escapes> {
+    haystack: &'a str,
+    pending: Option<&'static str>,
+    find: fn(&str) -> Option<usize>,
+    escapes: PhantomData<E>,
+}
+
+impl<'a, E: Escapes> EscapeIter<'a, E> {
+    /// Creates a new iterator over the escaped spans of `haystack`.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input string to be escaped.
+    /// - `find`: A function returning the offset of the first byte to escape.
+    #[inline]
+    pub fn new(haystack: &'a str, find: fn(&str) -> Option<usize>) -> Self {
+        Self {
+            haystack,
+            pending: None,
+            find,
+            escapes: PhantomData,
+        }
+    }
+}
+
+impl<'a, E: Escapes> Iterator for EscapeIter<'a, E> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(escaped) = self.pending.take() {
+            return Some(escaped);
+        }
+        if self.haystack.is_empty() {
+            return None;
+        }
+
+        let Some(at) = (self.find)(self.haystack) else {
+            return Some(core::mem::take(&mut self.haystack));
+        };
+        let (clean, rest) = self.haystack.split_at(at);
+        let range = rest.as_bytes().as_ptr_range();
+        // SAFETY: `rest` is a non empty string
+        let (escaped, len) = unsafe { E::escape_at(range.start, range.end) }
+            .expect("`find` returns the offset of an escaped sequence");
+        // Escaped sequences are ASCII bytes or valid UTF-8 starting at a char
+        // boundary, so `rest[len..]` is a char boundary
+        self.haystack = &rest[len..];
+        if clean.is_empty() {
+            Some(escaped)
+        } else {
+            self.pending = Some(escaped);
+            Some(clean)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let rest = self.haystack.len();
+        (pending + (rest != 0) as usize, Some(pending + 2 * rest))
+    }
+}
+
+impl<E: Escapes> FusedIterator for EscapeIter<'_, E> {}
+
+impl<E: Escapes> Clone for EscapeIter<'_, E> {
+    fn clone(&self) -> Self {
+        Self {
+            haystack: self.haystack,
+            pending: self.pending,
+            find: self.find,
+            escapes: PhantomData,
+        }
+    }
+}
+
+impl<E: Escapes> fmt::Debug for EscapeIter<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EscapeIter")
+            .field("haystack", &self.haystack)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
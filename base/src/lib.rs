@@ -2,10 +2,23 @@
 //!
 //! # Features
 //!
-//! - `std`: Enable standard library features
+//! - `std`: Enable standard library features and `escape_io` and `escape_vectored` functions
 //! - `alloc`: Enable alloc crate features
-//! - `string`: Enable `escape_string` function
-//! - `fmt`: Enable `escape_fmt` function
+//! - `string`: Enable `escape_string`, `escape_string_reserved`, `try_escape_string` and
+//!   `escape_cow` functions
+//! - `fmt`: Enable `escape_fmt` and `escape_fmt_into` functions
+//!
+//! The allocation-free `escape_slice`, `escape_iter`, `escaped_len`, `max_escaped_len`,
+//! `needs_escape` and `first_escape_position` functions are always available.
+//!
+//! The [`unescape_builder!`] macro generates `unescape_string`, `unescape_bytes` and
+//! `unescape_fmt` from an [`UnescapesBuilder`], gated by the same features.
+//!
+//! The [`escaper!`] macro generates a unit struct implementing [`Escaper`] with those
+//! functions, so generic code can be written over any escaper.
+//!
+//! Tables only known at runtime, such as loaded from configuration, are built into a
+//! [`DynamicEscaper`] instead, whose methods mirror those functions.
 //!
 //! # Examples
 //!
@@ -31,6 +44,8 @@
 //!
 //!     const FALSE_POSITIVE: bool = false;
 //!
+//!     const MAX_REPLACEMENT_LEN: usize = 3;
+//!
 //!     type Vector = V;
 //!
 //!     #[inline(always)]
@@ -78,10 +93,21 @@ extern crate std;
 #[cfg(any(test, feature = "alloc"))]
 extern crate alloc;
 
+/// Not part of the public API, used by the macros to name the items of
+/// `alloc` in `no_std` crates.
+#[doc(hidden)]
+#[cfg(feature = "alloc")]
+pub extern crate alloc as __alloc;
+
 /// A module for architecture-specific escape functions
 #[macro_use]
 pub mod arch;
 
+/// A module for escapers built at runtime
+mod dynamic;
+/// A module for generated escapers
+#[macro_use]
+mod escaper;
 /// A module for escapes
 mod escapes;
 
@@ -90,10 +116,21 @@ mod ext;
 
 /// A module for generic escape functions
 mod generic;
+/// A module for escaped span iterators
+mod iter;
+/// A module for unescapes
+mod unescapes;
 mod vector;
 #[macro_use]
 /// A module for writer functions
 pub mod writer;
 
+pub use dynamic::{DynamicEscaper, NonAsciiByte};
+pub use escaper::Escaper;
 pub use escapes::{Escapes, EscapesBuilder};
+pub use iter::EscapeIter;
+pub use unescapes::{
+    UnescapeError, UnescapeErrorKind, UnescapeFailure, Unescaped, Unescapes, UnescapesBuilder,
+};
 pub use vector::Vector;
+pub use writer::Overflow;
@@ -0,0 +1,237 @@
+use core::{fmt, result::Result as BResult, slice, str};
+
+use crate::{
+    Vector,
+    ext::Pointer,
+    writer::{Writer, write, write_slice},
+};
+
+/// A builder trait for creating instances of types that implement the `Unescapes` trait.
+///
+/// # Type Parameters
+/// - `V`: The vector type implementing the `Vector` trait.
+pub trait UnescapesBuilder {
+    /// The `Unescapes` type for a given vector type.
+    ///
+    /// # Type Parameters
+    /// - `V`: The vector type implementing the `Vector` trait.
+    type Unescapes<V: Vector>: Unescapes<Vector = V>;
+
+    /// Creates a new instance of the `Unescapes` type.
+    ///
+    /// # Returns
+    /// An instance of a type that implements the `Unescapes` trait.
+    fn new<V: Vector>() -> Self::Unescapes<V>;
+}
+
+/// A trait that abstracts masking and decoding functions for unescape sequences.
+///
+/// Every escape sequence starts with a lead byte, such as `&` or `\`. The
+/// lead bytes must be ASCII and [`Unescapes::masking`] must match them
+/// exactly, without false positives.
+pub trait Unescapes: Copy + fmt::Debug {
+    /// The vector type used for masking operations.
+    type Vector: Vector;
+
+    /// Applies a mask to the given vector `v` to identify lead bytes.
+    ///
+    /// # Parameters
+    /// - `v`: The vector to apply the mask to.
+    ///
+    /// # Returns
+    /// A vector with the mask applied.
+    fn masking(&self, v: Self::Vector) -> Self::Vector;
+
+    /// Compares a byte to determine if it is a lead byte.
+    ///
+    /// # Parameters
+    /// - `c`: The byte to compare.
+    ///
+    /// # Returns
+    /// `true` if the byte starts an escape sequence, `false` otherwise.
+    fn byte_byte_compare(c: u8) -> bool;
+
+    /// Decodes the escape sequence at the start of `haystack`.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input from a lead byte to the end of the string.
+    ///
+    /// # Returns
+    /// The number of bytes consumed, which must be at least one and end on a
+    /// char boundary, and the unescaped text. On failure, an error with an
+    /// offset relative to the start of `haystack`.
+    fn decode(haystack: &str) -> BResult<(usize, Unescaped), UnescapeError>;
+
+    /// Unescapes a string one byte at a time, writing the result using a writer.
+    ///
+    /// # Parameters
+    /// - `haystack`: The input string to be unescaped.
+    /// - `writer`: A mutable writer function to handle the unescaped output.
+    ///
+    /// # Returns
+    /// A `Result` indicating the success or failure of the unescape operation.
+    #[inline(always)]
+    fn byte_byte_unescape<const FMT: bool, W: Writer<FMT>>(
+        haystack: &str,
+        mut writer: W,
+    ) -> BResult<(), UnescapeFailure<W::Error>> {
+        let start = haystack.as_ptr();
+        let end = unsafe { start.add(haystack.len()) };
+        let mut written = start;
+        unsafe {
+            byte_byte_unescape_raw::<Self, FMT, W>(start, start, end, &mut written, &mut writer)
+        }
+    }
+}
+
+/// The text an escape sequence decodes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unescaped {
+    /// A static string, from a lookup table.
+    Str(&'static str),
+    /// A single character, e.g. from a numeric escape sequence.
+    Char(char),
+}
+
+/// The reason an escape sequence could not be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnescapeErrorKind {
+    /// The sequence is not a known escape sequence.
+    Unknown,
+    /// The input ends before the sequence is complete.
+    Incomplete,
+    /// The sequence encodes a number that is not a valid Unicode scalar value.
+    InvalidCodePoint,
+}
+
+impl fmt::Display for UnescapeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UnescapeErrorKind::Unknown => "unknown escape sequence",
+            UnescapeErrorKind::Incomplete => "incomplete escape sequence",
+            UnescapeErrorKind::InvalidCodePoint => "invalid code point",
+        })
+    }
+}
+
+/// A malformed escape sequence found while unescaping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnescapeError {
+    offset: usize,
+    kind: UnescapeErrorKind,
+}
+
+impl UnescapeError {
+    /// Creates a new error of `kind` at byte `offset`.
+    #[inline]
+    pub const fn new(offset: usize, kind: UnescapeErrorKind) -> Self {
+        Self { offset, kind }
+    }
+
+    /// Returns the byte offset of the malformed sequence in the input.
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the reason the sequence could not be decoded.
+    #[inline]
+    pub const fn kind(&self) -> UnescapeErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for UnescapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl core::error::Error for UnescapeError {}
+
+/// The failure of an unescape routine writing to a [`Writer`].
+///
+/// # Type Parameters
+/// - `E`: The error type of the writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnescapeFailure<E> {
+    /// The input contains a malformed escape sequence.
+    Unescape(UnescapeError),
+    /// The writer failed.
+    Writer(E),
+}
+
+impl<E> From<E> for UnescapeFailure<E> {
+    #[inline(always)]
+    fn from(err: E) -> Self {
+        UnescapeFailure::Writer(err)
+    }
+}
+
+/// Decodes the escape sequence at `at` and writes the pending clean span
+/// followed by the unescaped text.
+///
+/// # Returns
+/// A pointer to the first byte after the sequence.
+///
+/// # Safety
+/// `start <= *written <= at < end` must point into the same valid UTF-8
+/// string and `at` must point to a lead byte.
+#[inline(always)]
+pub(crate) unsafe fn unescape_at<U: Unescapes, const FMT: bool, W: Writer<FMT>>(
+    start: *const u8,
+    at: *const u8,
+    end: *const u8,
+    written: &mut *const u8,
+    writer: &mut W,
+) -> BResult<*const u8, UnescapeFailure<W::Error>> {
+    unsafe {
+        if *written < at {
+            write_slice(*written, at, writer)?;
+        }
+        let rest = str::from_utf8_unchecked(slice::from_raw_parts(at, end.distance(at)));
+        let (consumed, unescaped) = U::decode(rest).map_err(|err| {
+            UnescapeFailure::Unescape(UnescapeError::new(
+                at.distance(start) + err.offset,
+                err.kind,
+            ))
+        })?;
+        debug_assert!(0 < consumed && rest.is_char_boundary(consumed));
+        match unescaped {
+            Unescaped::Str(s) => write(s, writer)?,
+            Unescaped::Char(c) => write(c.encode_utf8(&mut [0; 4]), writer)?,
+        }
+        let next = at.add(consumed);
+        *written = next;
+        Ok(next)
+    }
+}
+
+/// Unescapes the input from `cur` to `end` one byte at a time.
+///
+/// # Safety
+/// `start <= *written <= cur <= end` must point into the same valid UTF-8
+/// string.
+#[inline(always)]
+pub(crate) unsafe fn byte_byte_unescape_raw<U: Unescapes, const FMT: bool, W: Writer<FMT>>(
+    start: *const u8,
+    mut cur: *const u8,
+    end: *const u8,
+    written: &mut *const u8,
+    writer: &mut W,
+) -> BResult<(), UnescapeFailure<W::Error>> {
+    unsafe {
+        while cur < end {
+            if U::byte_byte_compare(*cur) {
+                cur = unescape_at::<U, FMT, W>(start, cur, end, written, writer)?;
+            } else {
+                cur = cur.add(1);
+            }
+        }
+        if *written < end {
+            write_slice(*written, end, writer)?;
+        }
+        Ok(())
+    }
+}
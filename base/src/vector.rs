@@ -73,6 +73,22 @@ pub trait Vector: Copy + core::fmt::Debug {
     /// Compare two vectors for greater than.
     fn gt(self, vector2: Self) -> Self;
 
+    /// Bitwise AND of two vectors.
+    fn and(self, vector2: Self) -> Self;
+
+    /// Create a vector with the given 16 bytes repeated into each 128-bit
+    /// lane, to be used as the table of [`Vector::shuffle`].
+    fn from_table(table: &[u8; 16]) -> Self;
+
+    /// Look up each lane of `indices` in the table `self`, built by
+    /// [`Vector::from_table`]. Every lane of `indices` must be below 16.
+    ///
+    /// On x86_64, `__m128i` needs SSSE3 besides SSE2.
+    fn shuffle(self, indices: Self) -> Self;
+
+    /// Shift each 8-bit lane right by four bits, filling with zeros.
+    fn high_nibble(self) -> Self;
+
     /// Returns true if and only if `Self::movemask` would return a mask that
     /// contains at least one non-zero bit.
     #[inline(always)]
@@ -197,6 +213,9 @@ impl MoveMask for SensibleMoveMask {
 }
 
 /// Noop implementation for types that don't support vectorization.
+///
+/// The vectors of an `Escapes<()>` can be built, so that its value drives the
+/// scalar search, but never operated on.
 impl Vector for () {
     const BYTES: usize = 0;
 
@@ -205,9 +224,7 @@ impl Vector for () {
     type Mask = ();
 
     #[inline(always)]
-    fn splat(_byte: u8) -> Self {
-        unreachable!()
-    }
+    fn splat(_byte: u8) -> Self {}
 
     #[inline(always)]
     unsafe fn load_aligned(_data: *const u8) -> Self {
@@ -247,6 +264,24 @@ impl Vector for () {
     fn gt(self, _vector2: Self) -> Self {
         unreachable!()
     }
+
+    #[inline(always)]
+    fn and(self, _vector2: Self) -> Self {
+        unreachable!()
+    }
+
+    #[inline(always)]
+    fn from_table(_table: &[u8; 16]) -> Self {}
+
+    #[inline(always)]
+    fn shuffle(self, _indices: Self) -> Self {
+        unreachable!()
+    }
+
+    #[inline(always)]
+    fn high_nibble(self) -> Self {
+        unreachable!()
+    }
 }
 
 /// Noop implementation for types that don't support vectorization.
@@ -327,6 +362,27 @@ mod x86sse2 {
         fn gt(self, vector2: Self) -> Self {
             unsafe { _mm_cmpgt_epi8(self, vector2) }
         }
+
+        #[inline(always)]
+        fn and(self, vector2: Self) -> Self {
+            unsafe { _mm_and_si128(self, vector2) }
+        }
+
+        #[inline(always)]
+        fn from_table(table: &[u8; 16]) -> Self {
+            unsafe { _mm_loadu_si128(table.as_ptr() as *const __m128i) }
+        }
+
+        #[inline(always)]
+        fn shuffle(self, indices: Self) -> Self {
+            unsafe { _mm_shuffle_epi8(self, indices) }
+        }
+
+        #[inline(always)]
+        fn high_nibble(self) -> Self {
+            // There is no 8-bit shift, the bits crossing from the next lane are masked out
+            unsafe { _mm_and_si128(_mm_srli_epi16(self, 4), _mm_set1_epi8(0x0F)) }
+        }
     }
 }
 
@@ -382,6 +438,29 @@ mod x86avx2 {
         fn gt(self, vector2: Self) -> Self {
             unsafe { _mm256_cmpgt_epi8(self, vector2) }
         }
+
+        #[inline(always)]
+        fn and(self, vector2: Self) -> Self {
+            unsafe { _mm256_and_si256(self, vector2) }
+        }
+
+        #[inline(always)]
+        fn from_table(table: &[u8; 16]) -> Self {
+            unsafe {
+                _mm256_broadcastsi128_si256(_mm_loadu_si128(table.as_ptr() as *const __m128i))
+            }
+        }
+
+        #[inline(always)]
+        fn shuffle(self, indices: Self) -> Self {
+            unsafe { _mm256_shuffle_epi8(self, indices) }
+        }
+
+        #[inline(always)]
+        fn high_nibble(self) -> Self {
+            // There is no 8-bit shift, the bits crossing from the next lane are masked out
+            unsafe { _mm256_and_si256(_mm256_srli_epi16(self, 4), _mm256_set1_epi8(0x0F)) }
+        }
     }
 }
 
@@ -460,6 +539,31 @@ mod aarch64neon {
         fn gt(self, vector2: Self) -> Self {
             unsafe { vreinterpretq_s8_u8(vcgtq_s8(self, vector2)) }
         }
+
+        #[inline(always)]
+        fn and(self, vector2: Self) -> Self {
+            unsafe { vandq_s8(self, vector2) }
+        }
+
+        #[inline(always)]
+        fn from_table(table: &[u8; 16]) -> Self {
+            unsafe { vld1q_s8(table.as_ptr() as *const i8) }
+        }
+
+        #[inline(always)]
+        fn shuffle(self, indices: Self) -> Self {
+            unsafe {
+                vreinterpretq_s8_u8(vqtbl1q_u8(
+                    vreinterpretq_u8_s8(self),
+                    vreinterpretq_u8_s8(indices),
+                ))
+            }
+        }
+
+        #[inline(always)]
+        fn high_nibble(self) -> Self {
+            unsafe { vreinterpretq_s8_u8(vshrq_n_u8::<4>(vreinterpretq_u8_s8(self))) }
+        }
     }
 
     /// Neon doesn't have a `movemask` that works like the one in x86-64, so we
@@ -616,5 +720,25 @@ mod wasm_simd128 {
         fn gt(self, vector2: Self) -> Self {
             i8x16_gt(self, vector2)
         }
+
+        #[inline(always)]
+        fn and(self, vector2: Self) -> Self {
+            v128_and(self, vector2)
+        }
+
+        #[inline(always)]
+        fn from_table(table: &[u8; 16]) -> Self {
+            unsafe { v128_load(table.as_ptr().cast()) }
+        }
+
+        #[inline(always)]
+        fn shuffle(self, indices: Self) -> Self {
+            i8x16_swizzle(self, indices)
+        }
+
+        #[inline(always)]
+        fn high_nibble(self) -> Self {
+            u8x16_shr(self, 4)
+        }
     }
 }
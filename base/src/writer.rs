@@ -1,5 +1,5 @@
-use crate::Vector;
 use crate::ext::Pointer;
+use crate::{EscapesBuilder, Vector, escapes::Search, generic::Generic};
 use core::{fmt, result::Result as BResult, slice, str};
 use derive_new::new;
 
@@ -45,10 +45,36 @@ pub trait Writer<const FMT: bool> {
     ///
     /// Only meaningful for byte-oriented writers (`FMT = false`); formatter
     /// writers must never have this method called on them.
-    fn write_vector<V: Vector>(&mut self, vector: V);
+    fn write_vector<V: Vector>(&mut self, vector: V) -> Result<Self::Error>;
 
     /// Appends the contents of `src` to the writer.
     fn write_str(&mut self, src: &str) -> Result<Self::Error>;
+
+    /// Appends the bytes of `src` to the writer.
+    ///
+    /// Only called by tables escaping bytes above `0x7F`, whose spans may
+    /// start or end inside a multi-byte character; formatter writers, which
+    /// only accept strings, must never have this method called on them.
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error>;
+}
+
+impl<const FMT: bool, W: Writer<FMT> + ?Sized> Writer<FMT> for &mut W {
+    type Error = W::Error;
+
+    #[inline(always)]
+    fn write_vector<V: Vector>(&mut self, vector: V) -> Result<Self::Error> {
+        (**self).write_vector(vector)
+    }
+
+    #[inline(always)]
+    fn write_str(&mut self, src: &str) -> Result<Self::Error> {
+        (**self).write_str(src)
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error> {
+        (**self).write_bytes(src)
+    }
 }
 
 /// [`Writer`] implementation that appends bytes to a borrowed [`alloc::vec::Vec`].
@@ -64,35 +90,164 @@ impl Writer<false> for WriterVec<'_> {
     type Error = ();
 
     #[inline(always)]
-    fn write_vector<V: Vector>(&mut self, vector: V) {
+    fn write_vector<V: Vector>(&mut self, vector: V) -> Result<Self::Error> {
         unsafe {
             self.inner.reserve(V::BYTES);
             vector.store(self.inner.as_mut_ptr().add(self.inner.len()));
             self.inner.set_len(self.inner.len() + V::BYTES);
         }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write_str(&mut self, src: &str) -> Result<Self::Error> {
+        self.write_bytes(src.as_bytes())
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error> {
+        self.inner.extend_from_slice(src);
+        Ok(())
+    }
+}
+
+/// [`Writer`] implementation that appends bytes to a borrowed [`alloc::vec::Vec`]
+/// using fallible allocation.
+///
+/// Every reservation goes through [`alloc::vec::Vec::try_reserve`], and the
+/// allocation failure is reported as an error instead of aborting.
+#[cfg(feature = "alloc")]
+#[repr(transparent)]
+#[derive(new)]
+pub struct WriterTryVec<'a> {
+    inner: &'a mut alloc::vec::Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl Writer<false> for WriterTryVec<'_> {
+    type Error = alloc::collections::TryReserveError;
+
+    #[inline(always)]
+    fn write_vector<V: Vector>(&mut self, vector: V) -> Result<Self::Error> {
+        self.inner.try_reserve(V::BYTES)?;
+        unsafe {
+            vector.store(self.inner.as_mut_ptr().add(self.inner.len()));
+            self.inner.set_len(self.inner.len() + V::BYTES);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write_str(&mut self, src: &str) -> Result<Self::Error> {
+        self.write_bytes(src.as_bytes())
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error> {
+        self.inner.try_reserve(src.len())?;
+        self.inner.extend_from_slice(src);
+        Ok(())
+    }
+}
+
+/// [`Writer`] implementation that appends bytes to a borrowed
+/// [`alloc::vec::Vec`] whose capacity is reserved up front, such as for the
+/// worst-case escaped length.
+///
+/// Bytes are written straight into the spare capacity, and the length of the
+/// `Vec` is only updated by [`WriterUninit::finish`]. A write past the spare
+/// capacity, when the reservation was understated, grows the `Vec` as
+/// [`WriterVec`] does.
+#[cfg(feature = "alloc")]
+pub struct WriterUninit<'a> {
+    inner: &'a mut alloc::vec::Vec<u8>,
+    /// The length of `inner` along with the bytes written to its spare capacity
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> WriterUninit<'a> {
+    /// Creates a new `WriterUninit` appending to `inner`.
+    #[inline(always)]
+    pub fn new(inner: &'a mut alloc::vec::Vec<u8>) -> Self {
+        let len = inner.len();
+        Self { inner, len }
+    }
+
+    /// Sets the length of the `Vec` to include the bytes written, and returns
+    /// how many they are.
+    #[inline(always)]
+    pub fn finish(self) -> usize {
+        let written = self.len - self.inner.len();
+        // SAFETY: The bytes up to `self.len` have been initialized
+        unsafe { self.inner.set_len(self.len) };
+        written
+    }
+
+    /// Returns a pointer to the next byte to write, with room for at least
+    /// `additional` bytes.
+    #[inline(always)]
+    fn spare(&mut self, additional: usize) -> *mut u8 {
+        if self.inner.capacity() - self.len < additional {
+            self.grow(additional);
+        }
+        // SAFETY: `self.len` is at most the capacity of `self.inner`
+        unsafe { self.inner.as_mut_ptr().add(self.len) }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn grow(&mut self, additional: usize) {
+        // SAFETY: The bytes up to `self.len` have been initialized, and must
+        // be kept by the reallocation
+        unsafe { self.inner.set_len(self.len) };
+        self.inner.reserve(additional);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Writer<false> for WriterUninit<'_> {
+    type Error = ();
+
+    #[inline(always)]
+    fn write_vector<V: Vector>(&mut self, vector: V) -> Result<Self::Error> {
+        let dst = self.spare(V::BYTES);
+        // SAFETY: `dst` has room for `V::BYTES` bytes
+        unsafe { vector.store(dst) };
+        self.len += V::BYTES;
+        Ok(())
     }
 
     #[inline(always)]
     fn write_str(&mut self, src: &str) -> Result<Self::Error> {
-        self.inner.extend_from_slice(src.as_bytes());
+        self.write_bytes(src.as_bytes())
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error> {
+        let dst = self.spare(src.len());
+        // SAFETY: `dst` has room for `src.len()` bytes, outside of `src`
+        unsafe { core::ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len()) };
+        self.len += src.len();
         Ok(())
     }
 }
 
-/// [`Writer`] implementation that forwards bytes to a [`core::fmt::Formatter`].
+/// [`Writer`] implementation that forwards bytes to a [`core::fmt::Write`],
+/// such as a [`core::fmt::Formatter`] or a `String`.
 #[cfg(feature = "fmt")]
 #[repr(transparent)]
 #[derive(new)]
-pub struct WriterFMT<'a, 'b> {
-    inner: &'a mut core::fmt::Formatter<'b>,
+pub struct WriterFMT<'a, W: fmt::Write + ?Sized> {
+    inner: &'a mut W,
 }
 
 #[cfg(feature = "fmt")]
-impl Writer<true> for WriterFMT<'_, '_> {
+impl<W: fmt::Write + ?Sized> Writer<true> for WriterFMT<'_, W> {
     type Error = fmt::Error;
 
     #[inline(always)]
-    fn write_vector<V: Vector>(&mut self, _: V) {
+    fn write_vector<V: Vector>(&mut self, _: V) -> Result<Self::Error> {
         unreachable!()
     }
 
@@ -100,6 +255,310 @@ impl Writer<true> for WriterFMT<'_, '_> {
     fn write_str(&mut self, src: &str) -> Result<Self::Error> {
         self.inner.write_str(src)
     }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, _: &[u8]) -> Result<Self::Error> {
+        unreachable!()
+    }
+}
+
+/// [`Writer`] implementation that fills a caller-provided byte slice.
+///
+/// When a vector store or string slice does not fit in the remaining space,
+/// as many bytes as possible are copied and the writer reports an error.
+/// The output therefore may end in the middle of a replacement or of a
+/// multi-byte character; [`Overflow::new`] recovers the last complete unit.
+#[derive(new)]
+pub struct WriterSlice<'a> {
+    inner: &'a mut [u8],
+    #[new(value = "0")]
+    len: usize,
+}
+
+impl WriterSlice<'_> {
+    /// Returns the number of bytes written so far.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing has been written yet.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Copies the prefix of `src` that fits in the remaining space.
+    #[inline(always)]
+    fn fill(&mut self, src: &[u8]) -> Result<()> {
+        let n = src.len().min(self.inner.len() - self.len);
+        self.inner[self.len..self.len + n].copy_from_slice(&src[..n]);
+        self.len += n;
+        if n < src.len() { Err(()) } else { Ok(()) }
+    }
+}
+
+impl Writer<false> for WriterSlice<'_> {
+    type Error = ();
+
+    #[inline(always)]
+    fn write_vector<V: Vector>(&mut self, vector: V) -> Result<Self::Error> {
+        if self.inner.len() - self.len < V::BYTES {
+            let mut tmp = [0; 64];
+            debug_assert!(V::BYTES <= tmp.len());
+            unsafe { vector.store(tmp.as_mut_ptr()) };
+            return self.fill(&tmp[..V::BYTES]);
+        }
+        unsafe { vector.store(self.inner.as_mut_ptr().add(self.len)) };
+        self.len += V::BYTES;
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write_str(&mut self, src: &str) -> Result<Self::Error> {
+        self.fill(src.as_bytes())
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error> {
+        self.fill(src)
+    }
+}
+
+/// Error returned by `escape_slice` when the output slice is too small.
+///
+/// The first [`Overflow::written`] bytes of the output hold the escaped form of
+/// the first [`Overflow::consumed`] bytes of the input, so escaping can be
+/// resumed from `&haystack[consumed..]` once the output has been drained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    /// Number of input bytes whose escaped form was written to the output.
+    ///
+    /// Always lies on a `char` boundary of the input.
+    pub consumed: usize,
+    /// Number of valid bytes written to the output.
+    pub written: usize,
+}
+
+impl Overflow {
+    /// Builds the error for an escape of `haystack` that stopped after
+    /// writing `written` bytes.
+    ///
+    /// Not part of the public API, used by [`builder_slice`].
+    #[doc(hidden)]
+    pub fn new<B: EscapesBuilder>(haystack: &str, written: usize) -> Self {
+        Self::escaped(&Generic::new(B::new::<()>()), haystack, written)
+    }
+
+    /// Builds the error for an escape of `haystack` with the table of
+    /// `generic` that stopped after writing `written` bytes.
+    pub(crate) fn escaped<E: Search>(generic: &Generic<E>, haystack: &str, written: usize) -> Self {
+        let start = haystack.as_ptr();
+        // SAFETY: `end` is one past the last byte of `haystack`
+        let end = unsafe { start.add(haystack.len()) };
+        let mut consumed = 0;
+        let mut len = 0;
+        while consumed < haystack.len() {
+            // SAFETY: `consumed` is in bounds of `haystack`
+            let escaped = unsafe { generic.byte_byte_escape_at(start.add(consumed), end) };
+            let (n, m) = escaped.map_or((1, 1), |(escaped, m)| (escaped.len(), m));
+            if written < len + n {
+                break;
+            }
+            len += n;
+            consumed += m;
+        }
+        // The output may end in the middle of a multi-byte character that is
+        // not escaped, whose bytes are copied verbatim.
+        while !haystack.is_char_boundary(consumed) {
+            consumed -= 1;
+            len -= 1;
+        }
+
+        Overflow {
+            consumed,
+            written: len,
+        }
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "output buffer too small: {} input bytes escaped into {} bytes",
+            self.consumed, self.written
+        )
+    }
+}
+
+impl core::error::Error for Overflow {}
+
+/// Size of the internal staging buffer used by [`WriterIo`].
+#[cfg(feature = "std")]
+const IO_BUFFER_LEN: usize = 8 * 1024;
+
+/// [`Writer`] implementation that forwards bytes to a [`std::io::Write`].
+///
+/// Vector stores and short string slices are staged in an internal buffer so
+/// the SIMD fast path never issues a syscall-sized write per vector. Callers
+/// must call [`WriterIo::finish`] once escaping is done to push the staged
+/// bytes to the underlying writer.
+#[cfg(feature = "std")]
+pub struct WriterIo<'a, W: std::io::Write + ?Sized> {
+    inner: &'a mut W,
+    buf: [u8; IO_BUFFER_LEN],
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+impl<'a, W: std::io::Write + ?Sized> WriterIo<'a, W> {
+    /// Creates a new `WriterIo` staging its output for `inner`.
+    #[inline(always)]
+    pub fn new(inner: &'a mut W) -> Self {
+        Self {
+            inner,
+            buf: [0; IO_BUFFER_LEN],
+            len: 0,
+        }
+    }
+
+    /// Writes every staged byte to the underlying writer.
+    ///
+    /// The underlying writer itself is not flushed.
+    #[inline]
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.drain()
+    }
+
+    #[inline]
+    fn drain(&mut self) -> std::io::Result<()> {
+        let len = self.len;
+        self.len = 0;
+        self.inner.write_all(&self.buf[..len])
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write + ?Sized> Writer<false> for WriterIo<'_, W> {
+    type Error = std::io::Error;
+
+    #[inline(always)]
+    fn write_vector<V: Vector>(&mut self, vector: V) -> Result<Self::Error> {
+        if IO_BUFFER_LEN - self.len < V::BYTES {
+            self.drain()?;
+        }
+        unsafe { vector.store(self.buf.as_mut_ptr().add(self.len)) };
+        self.len += V::BYTES;
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write_str(&mut self, src: &str) -> Result<Self::Error> {
+        self.write_bytes(src.as_bytes())
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error> {
+        if IO_BUFFER_LEN - self.len < src.len() {
+            self.drain()?;
+            if IO_BUFFER_LEN <= src.len() {
+                return self.inner.write_all(src);
+            }
+        }
+        self.buf[self.len..self.len + src.len()].copy_from_slice(src);
+        self.len += src.len();
+        Ok(())
+    }
+}
+
+/// Number of [`std::io::IoSlice`]s batched by [`WriterVectored`] before they
+/// are flushed with a single [`std::io::Write::write_vectored`] call.
+#[cfg(feature = "std")]
+pub const IO_SLICES_LEN: usize = 64;
+
+/// [`Writer`] implementation that forwards spans to a [`std::io::Write`]
+/// through [`std::io::Write::write_vectored`].
+///
+/// Unchanged input spans and replacement strings are collected as
+/// [`std::io::IoSlice`]s without copying any byte, and flushed in batches of
+/// [`IO_SLICES_LEN`]. Like formatter writers it never accepts vector stores,
+/// so it must be driven with `FMT = true`. Callers must call
+/// [`WriterVectored::finish`] once escaping is done to write the pending
+/// slices.
+#[cfg(feature = "std")]
+pub struct WriterVectored<'a, 'b, W: std::io::Write + ?Sized> {
+    inner: &'b mut W,
+    slices: [std::io::IoSlice<'a>; IO_SLICES_LEN],
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+impl<'a, 'b, W: std::io::Write + ?Sized> WriterVectored<'a, 'b, W> {
+    /// Creates a new `WriterVectored` batching its output for `inner`.
+    ///
+    /// # Safety
+    /// Every slice written through [`Writer::write_str`] or
+    /// [`Writer::write_bytes`] is kept borrowed until the next flush, so it must be valid for `'a`. This holds
+    /// for the escape routines, which only write subslices of the haystack and
+    /// `'static` replacements, when the haystack outlives `'a`.
+    #[inline(always)]
+    pub unsafe fn new(inner: &'b mut W) -> Self {
+        Self {
+            inner,
+            slices: [std::io::IoSlice::new(&[]); IO_SLICES_LEN],
+            len: 0,
+        }
+    }
+
+    /// Writes every pending slice to the underlying writer.
+    ///
+    /// The underlying writer itself is not flushed.
+    #[inline]
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.drain()
+    }
+
+    fn drain(&mut self) -> std::io::Result<()> {
+        let mut slices = &mut self.slices[..self.len];
+        self.len = 0;
+        while !slices.is_empty() {
+            match self.inner.write_vectored(slices) {
+                Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+                Ok(n) => std::io::IoSlice::advance_slices(&mut slices, n),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write + ?Sized> Writer<true> for WriterVectored<'_, '_, W> {
+    type Error = std::io::Error;
+
+    #[inline(always)]
+    fn write_vector<V: Vector>(&mut self, _: V) -> Result<Self::Error> {
+        unreachable!()
+    }
+
+    #[inline(always)]
+    fn write_str(&mut self, src: &str) -> Result<Self::Error> {
+        self.write_bytes(src.as_bytes())
+    }
+
+    #[inline(always)]
+    fn write_bytes(&mut self, src: &[u8]) -> Result<Self::Error> {
+        if self.len == IO_SLICES_LEN {
+            self.drain()?;
+        }
+        // SAFETY: `src` outlives `'a` as required by `WriterVectored::new`
+        let src = unsafe { slice::from_raw_parts(src.as_ptr(), src.len()) };
+        self.slices[self.len] = std::io::IoSlice::new(src);
+        self.len += 1;
+        Ok(())
+    }
 }
 
 /// Writes a string slice using the writer function.
@@ -144,6 +603,39 @@ pub(crate) unsafe fn write_slice<const FMT: bool, W: Writer<FMT>>(
     }
 }
 
+/// Writes a span of the haystack escaped with `E`, left unchanged.
+///
+/// The span is written as a string slice when [`Escapes::UTF8`] is set, and
+/// as bytes otherwise: tables escaping bytes above `0x7F` cut spans inside
+/// multi-byte characters, and take input that may not be UTF-8 at all.
+///
+/// # Parameters
+/// - `start`: The starting pointer of the span.
+/// - `end`: The ending pointer of the span.
+/// - `writer`: The function to write the span.
+///
+/// # Returns
+/// A `Result` indicating the success or failure of the write operation.
+///
+/// # Safety
+/// This function is unsafe because it operates on raw pointers and assumes
+/// that the memory between `start` and `end` is valid, and valid UTF-8 when
+/// [`Escapes::UTF8`] is set.
+#[inline(always)]
+pub(crate) unsafe fn write_span<E: Search, const FMT: bool, W: Writer<FMT>>(
+    start: *const u8,
+    end: *const u8,
+    writer: &mut W,
+) -> Result<W::Error> {
+    unsafe {
+        if E::UTF8 {
+            write_slice(start, end, writer)
+        } else {
+            writer.write_bytes(slice::from_raw_parts(start, end.distance(start)))
+        }
+    }
+}
+
 /// A macro for creating a builder function that appends a string to a `String`.
 ///
 /// # Parameters
@@ -155,15 +647,75 @@ pub(crate) unsafe fn write_slice<const FMT: bool, W: Writer<FMT>>(
 #[macro_export]
 #[cfg(feature = "fmt")]
 macro_rules! builder_fmt {
-    ($name:ident, $fn:path, $fn_name:ident, $builder:ty) => {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
         fn $name<'a>(haystack: &str, buffer: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
             use $fn;
             let writer = $crate::writer::WriterFMT::new(buffer);
-            $fn_name::<$builder, true, _>(haystack, writer)
+            $fn_name::<$builder, true, _>(haystack.as_bytes(), writer)
+        }
+    };
+}
+
+/// A macro for creating a builder function that appends a string to a [`core::fmt::Write`].
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "fmt")]
+macro_rules! builder_fmt_write {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
+        fn $name(haystack: &str, buffer: &mut dyn core::fmt::Write) -> core::fmt::Result {
+            use $fn;
+            let writer = $crate::writer::WriterFMT::new(buffer);
+            $fn_name::<$builder, true, _>(haystack.as_bytes(), writer)
+        }
+    };
+}
+
+/// A macro for creating a function that escapes into any [`core::fmt::Write`].
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$internal`: The internal function to use for the body.
+/// - `$body`: The body of the function.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "fmt")]
+macro_rules! struct_fmt_write {
+    ($name:ident, $internal:ident, $body:expr, $builder:ty) => {
+        /// Escapes `haystack` and appends the result to `writer`.
+        ///
+        /// Works with any [`core::fmt::Write`] implementation, such as `String`,
+        /// fixed-capacity strings or custom log buffers, and therefore does not
+        /// require allocation. Errors reported by `writer` are returned as soon
+        /// as they occur, in which case only a prefix of the escaped output has
+        /// been written.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name<W: core::fmt::Write + ?Sized>(
+            haystack: &str,
+            mut writer: &mut W,
+        ) -> core::fmt::Result {
+            $body;
+            // `&mut W` is sized even when `W` is not, such as `dyn Write`
+            $internal(haystack, &mut writer)
         }
     };
 }
 
+#[cfg(not(feature = "fmt"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! struct_fmt_write {
+    ($($tt:tt)*) => {};
+}
+
 /// A macro for creating a function that return a `impl Display`.
 ///
 /// # Parameters
@@ -220,7 +772,7 @@ macro_rules! struct_display {
 #[macro_export]
 #[cfg(feature = "string")]
 macro_rules! builder_string {
-    ($name:ident, $fn:path, $fn_name:ident, $builder:ty) => {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
         /// Escapes `haystack` and appends the result to `buffer`.
         ///
         /// Bytes that are not part of the escape table defined for this crate
@@ -229,7 +781,7 @@ macro_rules! builder_string {
         ///
         /// See the crate-level documentation for the table of characters that
         /// get rewritten and their replacements.
-        pub fn $name(haystack: &str, buffer: &mut String) {
+        pub fn $name(haystack: &str, buffer: &mut $crate::__alloc::string::String) {
             use $fn;
             // SAFETY: The escape routine only writes valid UTF-8: the input
             // `haystack` is forwarded verbatim and every replacement emitted
@@ -238,7 +790,7 @@ macro_rules! builder_string {
             let vec = unsafe { buffer.as_mut_vec() };
             let writer = $crate::writer::WriterVec::new(vec);
 
-            let _ = $fn_name::<$builder, false, _>(haystack, writer);
+            let _ = $fn_name::<$builder, false, _>(haystack.as_bytes(), writer);
         }
     };
 }
@@ -259,35 +811,233 @@ macro_rules! struct_string {
     ($($tt:tt)*) => {};
 }
 
-/// A macro for creating a builder function that appends a Vector to a `Vector`.
+/// A macro for creating the worst-case escaped length constant and function.
 ///
 /// # Parameters
-/// - `$name`: The name of the builder function.
-/// - `$fn`: The function to use for the builder.
-/// - `$fn_name`: The name of the function to use for the builder.
 /// - `$builder`: The type of the builder.
 #[doc(hidden)]
 #[macro_export]
+macro_rules! builder_max_len {
+    ($builder:ty) => {
+        /// The length of the longest replacement in the escape table, or
+        /// `usize::MAX` if it is unknown.
+        pub const MAX_REPLACEMENT_LEN: usize =
+            <<$builder as $crate::EscapesBuilder>::Escapes<()> as $crate::Escapes>::MAX_REPLACEMENT_LEN;
+
+        /// Returns an upper bound of the escaped length of any input of `len`
+        /// bytes, assuming every byte is replaced by the longest replacement.
+        ///
+        /// Use [`escaped_len`] to compute the exact length of a given input.
+        #[inline]
+        pub const fn max_escaped_len(len: usize) -> usize {
+            if MAX_REPLACEMENT_LEN > 1 {
+                len.saturating_mul(MAX_REPLACEMENT_LEN)
+            } else {
+                len
+            }
+        }
+    };
+}
+
+/// A macro for creating a builder function that appends a string to a `String`
+/// reserving the worst-case escaped length once.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "string")]
+macro_rules! builder_string_reserved {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
+        /// Escapes `haystack` and appends the result to `buffer`, reserving
+        /// [`max_escaped_len`] bytes up front.
+        ///
+        /// The output is written straight into the spare capacity of `buffer`
+        /// without growing it on every write. This is faster than
+        /// `escape_string` when `buffer` is reused, at the cost of reserving
+        /// up to [`MAX_REPLACEMENT_LEN`] times the input length. The function
+        /// never clears `buffer`.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(haystack: &str, buffer: &mut $crate::__alloc::string::String) {
+            use $fn;
+            // SAFETY: The escape routine only writes valid UTF-8 (see
+            // `escape_string`), so the `String` invariant is upheld.
+            let vec = unsafe { buffer.as_mut_vec() };
+            if MAX_REPLACEMENT_LEN == usize::MAX {
+                // The worst case is unknown, the buffer grows as it is written
+                let _ = $fn_name::<$builder, false, _>(
+                    haystack.as_bytes(),
+                    $crate::writer::WriterVec::new(vec),
+                );
+                return;
+            }
+            vec.reserve(max_escaped_len(haystack.len()));
+            let mut writer = $crate::writer::WriterUninit::new(vec);
+            let _ = $fn_name::<$builder, false, _>(haystack.as_bytes(), &mut writer);
+            writer.finish();
+        }
+    };
+}
+
+/// A macro for creating a builder function that appends a string to a `String`
+/// using fallible allocation.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "string")]
+macro_rules! builder_try_string {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
+        /// Escapes `haystack` and appends the result to `buffer`, returning an
+        /// error instead of aborting if memory cannot be allocated.
+        ///
+        /// On failure `buffer` is truncated back to its original contents,
+        /// although its capacity may have grown. The function never clears
+        /// `buffer`; callers that want a fresh result should pass an empty
+        /// `String`.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(
+            haystack: &str,
+            buffer: &mut $crate::__alloc::string::String,
+        ) -> Result<(), $crate::__alloc::collections::TryReserveError> {
+            use $fn;
+            // SAFETY: The escape routine only writes valid UTF-8, and on
+            // failure the buffer is truncated back to its original valid
+            // contents. Therefore the `String` invariant is upheld.
+            let vec = unsafe { buffer.as_mut_vec() };
+            let len = vec.len();
+            let writer = $crate::writer::WriterTryVec::new(vec);
+
+            $fn_name::<$builder, false, _>(haystack.as_bytes(), writer)
+                .inspect_err(|_| vec.truncate(len))
+        }
+    };
+}
+
+/// A macro for creating a builder function that appends a Vector to a `Vector`.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
 #[cfg(feature = "bytes")]
 macro_rules! builder_bytes {
-    ($name:ident, $fn:path, $fn_name:ident, $builder:ty) => {
-        /// Escapes `haystack` and appends the resulting UTF-8 bytes to `buffer`.
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty, $haystack:ty) => {
+        /// Escapes `haystack` and appends the result to `buffer`.
         ///
-        /// `haystack` is required to be a valid `&str` so the escaped output is
-        /// itself guaranteed to be valid UTF-8. The function never clears
+        /// The output is valid UTF-8 when `haystack` is a `&str`. Tables
+        /// escaping bytes above `0x7F` split multi-byte characters, so their
+        /// escapers take any bytes instead. The function never clears
         /// `buffer`; callers that want a fresh result should pass an empty
         /// `Vec`.
         ///
         /// See the crate-level documentation for the table of characters that
         /// get rewritten and their replacements.
-        pub fn $name(haystack: &str, buffer: &mut Vec<u8>) {
+        pub fn $name(haystack: &$haystack, buffer: &mut $crate::__alloc::vec::Vec<u8>) {
             use $fn;
+            let haystack = core::convert::AsRef::<[u8]>::as_ref(haystack);
             let writer = $crate::writer::WriterVec::new(buffer);
             let _ = $fn_name::<$builder, false, _>(haystack, writer);
         }
     };
 }
 
+/// A macro for creating a builder function that appends a Vector to a `Vector`
+/// reserving the worst-case escaped length once.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "bytes")]
+macro_rules! builder_bytes_reserved {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty, $haystack:ty) => {
+        /// Escapes `haystack` and appends the result to `buffer`, reserving [`max_escaped_len`] bytes up front.
+        ///
+        /// The output is written straight into the spare capacity of `buffer`
+        /// without growing it on every write. This is faster than
+        /// `escape_bytes` when `buffer` is reused, at the cost of reserving up
+        /// to [`MAX_REPLACEMENT_LEN`] times the input length. The function
+        /// never clears `buffer`.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(haystack: &$haystack, buffer: &mut $crate::__alloc::vec::Vec<u8>) {
+            use $fn;
+            let haystack = core::convert::AsRef::<[u8]>::as_ref(haystack);
+            if MAX_REPLACEMENT_LEN == usize::MAX {
+                // The worst case is unknown, the buffer grows as it is written
+                let _ = $fn_name::<$builder, false, _>(
+                    haystack,
+                    $crate::writer::WriterVec::new(buffer),
+                );
+                return;
+            }
+            buffer.reserve(max_escaped_len(haystack.len()));
+            let mut writer = $crate::writer::WriterUninit::new(buffer);
+            let _ = $fn_name::<$builder, false, _>(haystack, &mut writer);
+            writer.finish();
+        }
+    };
+}
+
+/// A macro for creating a builder function that appends a Vector to a `Vector`
+/// using fallible allocation.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "bytes")]
+macro_rules! builder_try_bytes {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty, $haystack:ty) => {
+        /// Escapes `haystack` and appends the result to `buffer`, returning an
+        /// error instead of aborting if memory cannot be allocated.
+        ///
+        /// On failure `buffer` is truncated back to its original contents,
+        /// although its capacity may have grown. The function never clears
+        /// `buffer`; callers that want a fresh result should pass an empty
+        /// `Vec`.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(
+            haystack: &$haystack,
+            buffer: &mut $crate::__alloc::vec::Vec<u8>,
+        ) -> Result<(), $crate::__alloc::collections::TryReserveError> {
+            use $fn;
+            let haystack = core::convert::AsRef::<[u8]>::as_ref(haystack);
+            let len = buffer.len();
+            let writer = $crate::writer::WriterTryVec::new(buffer);
+
+            $fn_name::<$builder, false, _>(haystack, writer).inspect_err(|_| buffer.truncate(len))
+        }
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 #[cfg(feature = "bytes")]
@@ -303,3 +1053,488 @@ macro_rules! struct_bytes {
 macro_rules! struct_bytes {
     ($($tt:tt)*) => {};
 }
+
+/// A macro for creating a builder function that writes to a `&mut [u8]`.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! builder_slice {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
+        /// Escapes `haystack` into `buffer` and returns the number of bytes
+        /// written.
+        ///
+        /// No allocation is performed. If `buffer` is too small an
+        /// [`Overflow`]($crate::writer::Overflow) is returned describing how
+        /// much of `haystack` was escaped, so the caller can drain `buffer`
+        /// and resume from there. Bytes of `buffer` past
+        /// [`Overflow::written`]($crate::writer::Overflow::written) are
+        /// unspecified. Progress is guaranteed as long as `buffer` can hold
+        /// the longest replacement and any single character.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(
+            haystack: &str,
+            buffer: &mut [u8],
+        ) -> core::result::Result<usize, $crate::writer::Overflow> {
+            use $fn;
+            let mut writer = $crate::writer::WriterSlice::new(buffer);
+            match $fn_name::<$builder, false, _>(haystack.as_bytes(), &mut writer) {
+                Ok(()) => Ok(writer.len()),
+                Err(()) => Err($crate::writer::Overflow::new::<$builder>(
+                    haystack,
+                    writer.len(),
+                )),
+            }
+        }
+    };
+}
+
+/// A macro for creating a builder function that computes the escaped length.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! builder_len {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty, $haystack:ty) => {
+        /// Returns the exact length in bytes of the escaped form of `haystack`.
+        ///
+        /// No output is produced: the input is only scanned for bytes that
+        /// need to be escaped and the lengths of their replacements are summed.
+        /// Useful to pre-size buffers or to compute a `Content-Length`.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(haystack: &$haystack) -> usize {
+            use $fn;
+            let haystack = core::convert::AsRef::<[u8]>::as_ref(haystack);
+            $fn_name::<$builder>(haystack)
+        }
+    };
+}
+
+/// A macro for creating a builder function that finds the first byte to escape.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! builder_first_escape {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty, $haystack:ty) => {
+        /// Returns the byte offset of the first character of `haystack` that
+        /// needs to be escaped, or `None` if `haystack` can be used verbatim.
+        ///
+        /// The scan stops at the first match.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(haystack: &$haystack) -> Option<usize> {
+            use $fn;
+            let haystack = core::convert::AsRef::<[u8]>::as_ref(haystack);
+            $fn_name::<$builder>(haystack)
+        }
+    };
+}
+
+/// A macro for creating a function that checks whether escaping is needed.
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$first`: The function that finds the first byte to escape.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! builder_needs_escape {
+    ($name:ident, $first:ident, $haystack:ty) => {
+        /// Returns `true` if at least one character of `haystack` needs to be
+        /// escaped.
+        ///
+        /// The scan stops at the first match, so this is cheaper than escaping
+        /// when most inputs are expected to be clean.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        #[inline]
+        pub fn $name(haystack: &$haystack) -> bool {
+            $first(haystack).is_some()
+        }
+    };
+}
+
+/// A macro for creating a function that iterates over the escaped spans.
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$first`: The function that finds the first byte to escape.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! builder_iter {
+    ($name:ident, $first:ident, $builder:ty) => {
+        /// Returns an iterator over the spans of the escaped `haystack`.
+        ///
+        /// The iterator yields borrowed slices of `haystack` that need no
+        /// escaping, alternating with the `&'static str` replacements, so the
+        /// escaped output can be assembled without copying. Concatenating all
+        /// spans gives the same result as `escape_string`.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        #[inline]
+        pub fn $name(
+            haystack: &str,
+        ) -> impl core::iter::FusedIterator<Item = &str> + Clone + core::fmt::Debug {
+            $crate::EscapeIter::<<$builder as $crate::EscapesBuilder>::Escapes<()>>::new(
+                haystack, $first,
+            )
+        }
+    };
+}
+
+/// A macro for creating a function that escapes into a copy-on-write string.
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$string`: The function that escapes into a `String`.
+/// - `$first`: The function that finds the first byte to escape.
+/// - `$len`: The function that computes the escaped length.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! builder_cow {
+    ($name:ident, $string:ident, $first:ident, $len:ident) => {
+        /// Escapes `haystack`, borrowing it when nothing needs to be escaped.
+        ///
+        /// Returns `Cow::Borrowed` without allocating when `haystack`
+        /// contains no character to escape. Otherwise the escaped output is
+        /// written to a `String` allocated once with the exact escaped length.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name(haystack: &str) -> $crate::__alloc::borrow::Cow<'_, str> {
+            match $first(haystack) {
+                None => $crate::__alloc::borrow::Cow::Borrowed(haystack),
+                Some(at) => {
+                    let (clean, rest) = haystack.split_at(at);
+                    let mut buffer =
+                        $crate::__alloc::string::String::with_capacity(at + $len(rest));
+                    buffer.push_str(clean);
+                    $string(rest, &mut buffer);
+                    $crate::__alloc::borrow::Cow::Owned(buffer)
+                }
+            }
+        }
+    };
+}
+
+/// A macro for creating a builder function that writes to a [`std::io::Write`].
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! builder_io {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty, $haystack:ty) => {
+        fn $name(haystack: &$haystack, buffer: &mut dyn std::io::Write) -> std::io::Result<()> {
+            use $fn;
+            let haystack = core::convert::AsRef::<[u8]>::as_ref(haystack);
+            let mut writer = $crate::writer::WriterIo::new(buffer);
+            $fn_name::<$builder, false, _>(haystack, &mut writer)?;
+            writer.finish()
+        }
+    };
+}
+
+/// A macro for creating a builder function that writes vectored to a [`std::io::Write`].
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! builder_vectored {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty, $haystack:ty) => {
+        fn $name(haystack: &$haystack, buffer: &mut dyn std::io::Write) -> std::io::Result<()> {
+            use $fn;
+            let haystack = core::convert::AsRef::<[u8]>::as_ref(haystack);
+            // SAFETY: The escape routine only writes subslices of `haystack`
+            // and `'static` replacements, and every slice is written out
+            // before `haystack` goes out of scope.
+            let mut writer = unsafe { $crate::writer::WriterVectored::new(buffer) };
+            $fn_name::<$builder, true, _>(haystack, &mut writer)?;
+            writer.finish()
+        }
+    };
+}
+
+/// A macro for creating a function that escapes vectored into any [`std::io::Write`].
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$internal`: The internal function to use for the body.
+/// - `$body`: The body of the function.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! struct_vectored {
+    ($name:ident, $internal:ident, $body:expr, $builder:ty, $haystack:ty) => {
+        /// Escapes `haystack` and writes the result to `writer` using
+        /// vectored writes.
+        ///
+        /// Unchanged spans of `haystack` and the replacements are passed to
+        /// [`std::io::Write::write_vectored`] in batches without being copied,
+        /// which pays off for large inputs written to sinks with native
+        /// scatter/gather support. `writer` itself is not flushed.
+        ///
+        /// Any I/O error reported by `writer` is returned as soon as it occurs,
+        /// in which case only a prefix of the escaped output has been written.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name<W: std::io::Write + ?Sized>(
+            haystack: &$haystack,
+            mut writer: &mut W,
+        ) -> std::io::Result<()> {
+            $body;
+            // `&mut W` is sized even when `W` is not, such as `dyn Write`
+            $internal(haystack, &mut writer)
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! struct_vectored {
+    ($($tt:tt)*) => {};
+}
+
+/// A macro for creating a function that escapes into any [`std::io::Write`].
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$internal`: The internal function to use for the body.
+/// - `$body`: The body of the function.
+/// - `$builder`: The type of the builder.
+/// - `$haystack`: The type of the input, `str` or `[u8]`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! struct_io {
+    ($name:ident, $internal:ident, $body:expr, $builder:ty, $haystack:ty) => {
+        /// Escapes `haystack` and writes the result to `writer`.
+        ///
+        /// Output is staged in a fixed-size internal buffer so that the SIMD
+        /// fast path can store whole vectors at once; the staged bytes are
+        /// written out before returning. `writer` itself is not flushed.
+        ///
+        /// Any I/O error reported by `writer` is returned as soon as it occurs,
+        /// in which case only a prefix of the escaped output has been written.
+        ///
+        /// See the crate-level documentation for the table of characters that
+        /// get rewritten and their replacements.
+        pub fn $name<W: std::io::Write + ?Sized>(
+            haystack: &$haystack,
+            mut writer: &mut W,
+        ) -> std::io::Result<()> {
+            $body;
+            // `&mut W` is sized even when `W` is not, such as `dyn Write`
+            $internal(haystack, &mut writer)
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! struct_io {
+    ($($tt:tt)*) => {};
+}
+
+/// A macro for creating a builder function that unescapes into a `Vec<u8>`.
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "alloc")]
+macro_rules! builder_unescape_vec {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
+        fn $name(
+            haystack: &str,
+            buffer: &mut $crate::__alloc::vec::Vec<u8>,
+        ) -> core::result::Result<(), $crate::UnescapeError> {
+            use $fn;
+            let writer = $crate::writer::WriterVec::new(buffer);
+            $fn_name::<$builder, false, _>(haystack, writer).map_err(|err| match err {
+                $crate::UnescapeFailure::Unescape(err) => err,
+                $crate::UnescapeFailure::Writer(()) => unreachable!(),
+            })
+        }
+    };
+}
+
+/// A macro for creating a builder function that unescapes into a [`core::fmt::Formatter`].
+///
+/// # Parameters
+/// - `$name`: The name of the builder function.
+/// - `$fn`: The function to use for the builder.
+/// - `$fn_name`: The name of the function to use for the builder.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "fmt")]
+macro_rules! builder_unescape_fmt {
+    ($name:ident, $fn:path, $fn_name:ident, $builder:ty $(, $haystack:ty)?) => {
+        fn $name(haystack: &str, buffer: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            use $fn;
+            let writer = $crate::writer::WriterFMT::new(buffer);
+            $fn_name::<$builder, true, _>(haystack, writer).map_err(|_| core::fmt::Error)
+        }
+    };
+}
+
+/// A macro for creating a function that unescapes into a `String`.
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$internal`: The internal function to use for the body.
+/// - `$body`: The body of the function.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "string")]
+macro_rules! struct_unescape_string {
+    ($name:ident, $internal:ident, $body:expr, $builder:ty) => {
+        /// Unescapes `haystack` and appends the result to `buffer`.
+        ///
+        /// Text outside escape sequences is forwarded verbatim. On a malformed
+        /// sequence an error carrying its byte offset in `haystack` is
+        /// returned and `buffer` is truncated back to its original contents.
+        /// The function never clears `buffer`.
+        pub fn $name(
+            haystack: &str,
+            buffer: &mut $crate::__alloc::string::String,
+        ) -> core::result::Result<(), $crate::UnescapeError> {
+            $body;
+            // SAFETY: The unescape routine only writes valid UTF-8: spans of
+            // `haystack` are cut at ASCII lead bytes and every decoded
+            // sequence is a `char` or a `&str`. On failure the buffer is
+            // truncated back to its original valid contents.
+            let vec = unsafe { buffer.as_mut_vec() };
+            let len = vec.len();
+            $internal(haystack, vec).inspect_err(|_| vec.truncate(len))
+        }
+    };
+}
+
+#[cfg(not(feature = "string"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! struct_unescape_string {
+    ($($tt:tt)*) => {};
+}
+
+/// A macro for creating a function that unescapes into a `Vec<u8>`.
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$internal`: The internal function to use for the body.
+/// - `$body`: The body of the function.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "bytes")]
+macro_rules! struct_unescape_bytes {
+    ($name:ident, $internal:ident, $body:expr, $builder:ty) => {
+        /// Unescapes `haystack` and appends the resulting UTF-8 bytes to
+        /// `buffer`.
+        ///
+        /// Text outside escape sequences is forwarded verbatim. On a malformed
+        /// sequence an error carrying its byte offset in `haystack` is
+        /// returned and `buffer` is truncated back to its original contents.
+        /// The function never clears `buffer`.
+        pub fn $name(
+            haystack: &str,
+            buffer: &mut $crate::__alloc::vec::Vec<u8>,
+        ) -> core::result::Result<(), $crate::UnescapeError> {
+            $body;
+            let len = buffer.len();
+            $internal(haystack, buffer).inspect_err(|_| buffer.truncate(len))
+        }
+    };
+}
+
+#[cfg(not(feature = "bytes"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! struct_unescape_bytes {
+    ($($tt:tt)*) => {};
+}
+
+/// A macro for creating a function that return a `impl Display` unescaping its input.
+///
+/// # Parameters
+/// - `$name`: The name of the function.
+/// - `$internal`: The internal function to use for the struct.
+/// - `$body`: The body of the struct.
+/// - `$builder`: The type of the builder.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "fmt")]
+macro_rules! struct_unescape_display {
+    ($name:ident, $internal:ident, $body:expr, $builder:ty) => {
+        /// Returns a value implementing [`core::fmt::Display`] that unescapes
+        /// `haystack` lazily into the formatter it is rendered to.
+        ///
+        /// Formatting fails with [`core::fmt::Error`] on a malformed escape
+        /// sequence, after the text preceding it has been written. Use
+        /// `unescape_string` to get the error and its byte offset.
+        pub fn $name<'a>(haystack: &'a str) -> impl core::fmt::Display + 'a {
+            struct __UDisplay<'a>(&'a str);
+
+            impl<'a> core::fmt::Display for __UDisplay<'a> {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    $body;
+                    $internal(self.0, f)
+                }
+            }
+
+            __UDisplay(haystack)
+        }
+    };
+}
+
+#[cfg(not(feature = "fmt"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! struct_unescape_display {
+    ($($tt:tt)*) => {};
+}
@@ -0,0 +1,105 @@
+#![cfg(all(feature = "string", feature = "fmt", feature = "bytes"))]
+use v_escape_base::{DynamicEscaper, NonAsciiByte};
+
+const HTML: &[(u8, &str)] = &[
+    (b'"', "&quot;"),
+    (b'&', "&amp;"),
+    (b'\'', "&#x27;"),
+    (b'/', "&#x2f;"),
+    (b'<', "&lt;"),
+    (b'>', "&gt;"),
+];
+
+fn reference(escaper: &DynamicEscaper, haystack: &str) -> String {
+    haystack
+        .chars()
+        .map(|c| {
+            u8::try_from(c)
+                .ok()
+                .and_then(|byte| escaper.replacement(byte))
+                .map_or_else(|| c.to_string(), str::to_string)
+        })
+        .collect()
+}
+
+#[test]
+fn test_escape() {
+    let escaper = DynamicEscaper::new(HTML).unwrap();
+    let haystack = "<a href=\"/\">Tom & 'Jerry'</a>";
+    let expected = "&lt;a href=&quot;&#x2f;&quot;&gt;Tom &amp; &#x27;Jerry&#x27;&lt;&#x2f;a&gt;";
+
+    let mut escaped = String::new();
+    escaper.escape_string(haystack, &mut escaped);
+    assert_eq!(escaped, expected);
+    assert_eq!(escaper.escape_fmt(haystack).to_string(), expected);
+    let mut bytes = Vec::new();
+    escaper.escape_bytes(haystack, &mut bytes);
+    assert_eq!(bytes, expected.as_bytes());
+    assert_eq!(escaper.escaped_len(haystack), expected.len());
+    assert_eq!(escaper.max_replacement_len(), 6);
+    assert_eq!(escaper.max_escaped_len(2), 12);
+}
+
+#[test]
+fn test_boundaries() {
+    // Every ASCII byte, alone or along with a multi-byte character
+    let escaper = DynamicEscaper::new(HTML).unwrap();
+    let tail = "b".repeat(300);
+    for size in 0..70 {
+        let clean = "é".repeat(size / 2) + &"b".repeat(size % 2);
+        for byte in 0..0x80u8 {
+            let c = char::from(byte);
+            for tail in ["", &tail] {
+                let haystack = format!("{clean}{c}{clean}{c}{tail}");
+                let expected = reference(&escaper, &haystack);
+
+                let mut escaped = String::new();
+                escaper.escape_string(&haystack, &mut escaped);
+                assert_eq!(escaped, expected, "{size} {byte:#04x}");
+                assert_eq!(escaper.escaped_len(&haystack), expected.len());
+                let first = escaper.replacement(byte).map(|_| clean.len());
+                assert_eq!(escaper.first_escape_position(&haystack), first);
+                assert_eq!(escaper.needs_escape(&haystack), first.is_some());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_table() {
+    // Sharing nibbles with the escaped bytes: 0x0A and 0x2B, against 0x2A and 0x7E
+    let escaper = DynamicEscaper::new(&[(b'*', "\\*"), (b'~', ""), (b'*', "\\x2a")]).unwrap();
+    let haystack = ["\n+*~", &"\n+".repeat(40), "~*"].concat();
+    let expected = ["\n+\\x2a", &"\n+".repeat(40), "\\x2a"].concat();
+    let mut escaped = String::new();
+    escaper.escape_string(&haystack, &mut escaped);
+    assert_eq!(escaped, expected);
+    assert_eq!(escaper.escaped_len(&haystack), expected.len());
+    assert_eq!(escaper.replacement(b'*'), Some("\\x2a"));
+    assert_eq!(escaper.replacement(0xE9), None);
+
+    let empty = DynamicEscaper::new(&[]).unwrap();
+    assert!(!empty.needs_escape(&"\u{7F}é".repeat(40)));
+    assert_eq!(empty.max_escaped_len(10), 10);
+
+    assert_eq!(
+        DynamicEscaper::new(&[(b'<', "&lt;"), (0xE9, "&eacute;")]).unwrap_err(),
+        NonAsciiByte(0xE9)
+    );
+}
+
+#[test]
+fn test_slice() {
+    let escaper = DynamicEscaper::new(HTML).unwrap();
+    let mut buf = [0u8; 16];
+    assert_eq!(escaper.escape_slice("a<b", &mut buf), Ok(6));
+    assert_eq!(&buf[..6], b"a&lt;b");
+
+    let mut buf = [0u8; 4];
+    let err = escaper.escape_slice("a<b", &mut buf).unwrap_err();
+    assert_eq!((err.consumed, err.written), (1, 1));
+
+    let mut buf = [0u8; 2];
+    let err = escaper.escape_slice("aé<", &mut buf).unwrap_err();
+    assert_eq!((err.consumed, err.written), (1, 1));
+}
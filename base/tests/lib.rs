@@ -23,6 +23,8 @@ mod no_false_positive {
 
         const FALSE_POSITIVE: bool = false;
 
+        const MAX_REPLACEMENT_LEN: usize = 3;
+
         type Vector = V;
 
         #[inline(always)]
@@ -269,7 +271,7 @@ mod no_false_positive {
     fn test_byte_byte_escape() {
         let mut buffer = String::new();
         let writer = WriterVec::new(unsafe { buffer.as_mut_vec() });
-        let result = Equal::<()>::byte_byte_escape("a", writer);
+        let result = Equal::<()>::byte_byte_escape(b"a", writer);
         assert!(result.is_ok());
         assert_eq!(buffer, "foo");
     }
@@ -731,6 +733,391 @@ mod no_false_positive {
             assert_eq!(buffer, expected.as_bytes(), "Failed for input: {:?}", input);
         }
     }
+
+    #[test]
+    fn test_escaped_len() {
+        assert_eq!(escaped_len(""), 0);
+        assert_eq!(escaped_len("hello world"), 11);
+        assert_eq!(escaped_len("a🚀a"), "foo🚀foo".len());
+        for size in [1, 15, 16, 31, 32, 64, 127, 128, 1000, 10000] {
+            let haystack = ["b", &"a".repeat(size), "b"].join("");
+            assert_eq!(escaped_len(&haystack), 3 * size + 2);
+            let haystack = "ab".repeat(size);
+            assert_eq!(escaped_len(&haystack), 4 * size);
+        }
+    }
+
+    #[test]
+    fn test_first_escape_position() {
+        assert_eq!(first_escape_position(""), None);
+        assert!(!needs_escape(""));
+        assert_eq!(first_escape_position("hello world"), None);
+        assert_eq!(first_escape_position("🚀a"), Some(4));
+        for size in [1, 15, 16, 31, 32, 63, 64, 127, 128, 1000, 10000] {
+            let haystack = "b".repeat(size);
+            assert_eq!(first_escape_position(&haystack), None);
+            assert!(!needs_escape(&haystack));
+            for at in [0, size / 2, size - 1] {
+                let mut haystack = haystack.clone().into_bytes();
+                haystack[at] = b'a';
+                let haystack = String::from_utf8(haystack).unwrap();
+                assert_eq!(first_escape_position(&haystack), Some(at));
+                assert!(needs_escape(&haystack));
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_escaped_len() {
+        assert_eq!(MAX_REPLACEMENT_LEN, 3);
+        assert_eq!(max_escaped_len(0), 0);
+        assert_eq!(max_escaped_len(10), 30);
+        assert_eq!(max_escaped_len(usize::MAX), usize::MAX);
+        let haystack = "a".repeat(100);
+        assert_eq!(escaped_len(&haystack), max_escaped_len(haystack.len()));
+    }
+
+    #[test]
+    fn test_escape_reserved() {
+        for size in [0, 1, 15, 16, 31, 32, 64, 1000, 10000] {
+            let haystack = ["b", &"a".repeat(size), "b", &"ba".repeat(size)].join("");
+            let expected = ["b", &"foo".repeat(size), "b", &"bfoo".repeat(size)].join("");
+
+            let mut buffer = String::from("x");
+            escape_string_reserved(&haystack, &mut buffer);
+            assert_eq!(buffer, ["x", &expected].join(""));
+            assert!(buffer.capacity() > max_escaped_len(haystack.len()));
+
+            let mut buffer = b"x".to_vec();
+            escape_bytes_reserved(&haystack, &mut buffer);
+            assert_eq!(buffer, ["x", &expected].join("").as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_escape_cow() {
+        use std::borrow::Cow;
+
+        assert!(matches!(escape_cow(""), Cow::Borrowed("")));
+        for size in [1, 15, 16, 31, 32, 64, 1000] {
+            let haystack = "b".repeat(size);
+            assert!(matches!(escape_cow(&haystack), Cow::Borrowed(s) if s == haystack));
+
+            let haystack = ["b".repeat(size), "a".repeat(size)].join("");
+            let escaped = escape_cow(&haystack);
+            assert!(matches!(escaped, Cow::Owned(_)));
+            assert_eq!(escaped, ["b".repeat(size), "foo".repeat(size)].join(""));
+        }
+    }
+
+    #[test]
+    fn test_escape_cow_capacity() {
+        let haystack = ["🚀", &"a".repeat(100), "b"].join("");
+        let escaped = escape_cow(&haystack).into_owned();
+        assert_eq!(escaped.len(), escaped_len(&haystack));
+        assert_eq!(escaped.capacity(), escaped.len());
+    }
+
+    #[test]
+    fn test_escape_iter() {
+        assert_eq!(escape_iter("").next(), None);
+        assert_eq!(escape_iter("hello").collect::<Vec<_>>(), ["hello"]);
+        assert_eq!(escape_iter("a").collect::<Vec<_>>(), ["foo"]);
+        assert_eq!(
+            escape_iter("xaa🚀ay").collect::<Vec<_>>(),
+            ["x", "foo", "foo", "🚀", "foo", "y"]
+        );
+
+        for size in [1, 15, 16, 31, 32, 64, 1000] {
+            let haystack = ["b".repeat(size), "a".to_string(), "b".repeat(size)].join("");
+            let spans = escape_iter(&haystack).collect::<Vec<_>>();
+            assert_eq!(
+                spans,
+                ["b".repeat(size), "foo".to_string(), "b".repeat(size)]
+            );
+            // Clean spans borrow the input
+            assert_eq!(spans[0].as_ptr(), haystack.as_ptr());
+            assert_eq!(spans[2].as_ptr(), haystack[size + 1..].as_ptr());
+
+            let haystack = "ab".repeat(size);
+            assert_eq!(
+                escape_iter(&haystack).collect::<String>(),
+                "foob".repeat(size)
+            );
+        }
+    }
+
+    #[test]
+    fn test_escape_slice() {
+        for size in [0, 1, 15, 16, 64, 1000] {
+            let mut buffer = vec![0; 3 * size + 2];
+            let haystack = ["b", &"a".repeat(size), "b"].join("");
+            let written = escape_slice(&haystack, &mut buffer).unwrap();
+            assert_eq!(written, buffer.len());
+            assert_eq!(buffer, ["b", &"foo".repeat(size), "b"].join("").as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_escape_slice_overflow() {
+        let mut buffer = [0; 7];
+        let err = escape_slice("xa a", &mut buffer).unwrap_err();
+        assert_eq!(
+            err,
+            v_escape_base::Overflow {
+                consumed: 3,
+                written: 5
+            }
+        );
+        assert_eq!(&buffer[..err.written], b"xfoo ");
+
+        let err = escape_slice("a", &mut []).unwrap_err();
+        assert_eq!(err.consumed, 0);
+        assert_eq!(err.written, 0);
+    }
+
+    #[test]
+    fn test_escape_slice_resume() {
+        let haystack = ["a🚀", &"hello world ".repeat(100), "a🎉a"]
+            .join("")
+            .repeat(20);
+        let expected = ["foo🚀", &"hello world ".repeat(100), "foo🎉foo"]
+            .join("")
+            .repeat(20);
+
+        let mut buffer = [0; 100];
+        let mut rest = haystack.as_str();
+        let mut result = Vec::new();
+        loop {
+            match escape_slice(rest, &mut buffer) {
+                Ok(written) => {
+                    result.extend_from_slice(&buffer[..written]);
+                    break;
+                }
+                Err(err) => {
+                    assert!(err.consumed > 0);
+                    result.extend_from_slice(&buffer[..err.written]);
+                    rest = &rest[err.consumed..];
+                }
+            }
+        }
+        assert_eq!(String::from_utf8(result).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_escape_fmt_into() {
+        for size in [0, 1, 15, 16, 64, 1000] {
+            let mut buffer = String::from("b");
+            let haystack = ["a".repeat(size), "b".to_string()].join("");
+            escape_fmt_into(&haystack, &mut buffer).unwrap();
+            assert_eq!(buffer, ["b", &"foo".repeat(size), "b"].join(""));
+        }
+    }
+
+    #[test]
+    fn test_escape_fmt_into_fixed_capacity() {
+        struct Fixed {
+            buf: [u8; 16],
+            len: usize,
+        }
+
+        impl std::fmt::Write for Fixed {
+            fn write_str(&mut self, s: &str) -> std::fmt::Result {
+                let end = self.len + s.len();
+                if self.buf.len() < end {
+                    return Err(std::fmt::Error);
+                }
+                self.buf[self.len..end].copy_from_slice(s.as_bytes());
+                self.len = end;
+                Ok(())
+            }
+        }
+
+        let mut fixed = Fixed {
+            buf: [0; 16],
+            len: 0,
+        };
+        escape_fmt_into("xa a", &mut fixed).unwrap();
+        assert_eq!(&fixed.buf[..fixed.len], b"xfoo foo");
+
+        let mut fixed = Fixed {
+            buf: [0; 16],
+            len: 0,
+        };
+        assert!(escape_fmt_into(&"a".repeat(64), &mut fixed).is_err());
+        assert_eq!(&fixed.buf[..fixed.len], b"foofoofoofoofoo");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_escape_io() {
+        for size in [0, 1, 15, 16, 64, 1000, 10000] {
+            let mut buffer = Vec::new();
+            let haystack = ["b", &"a".repeat(size), "b"].join("");
+            escape_io(&haystack, &mut buffer).unwrap();
+            assert_eq!(buffer, ["b", &"foo".repeat(size), "b"].join("").as_bytes());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_escape_io_no_escapes() {
+        // Large clean input goes through the vector store path of the writer
+        let mut buffer = Vec::new();
+        let haystack = "hello world ".repeat(4096);
+        escape_io(&haystack, &mut buffer).unwrap();
+        assert_eq!(buffer, haystack.as_bytes());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_escape_io_error() {
+        struct Limited(usize);
+
+        impl std::io::Write for Limited {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.0 < buf.len() {
+                    return Err(std::io::Error::other("limit reached"));
+                }
+                self.0 -= buf.len();
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let haystack = "a".repeat(10000);
+        let err = escape_io(&haystack, &mut Limited(100)).unwrap_err();
+        assert_eq!(err.to_string(), "limit reached");
+
+        let mut writer = Limited(30000);
+        escape_io(&haystack, &mut writer).unwrap();
+        assert_eq!(writer.0, 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_escape_vectored() {
+        for size in [0, 1, 15, 16, 64, 1000, 10000] {
+            let mut buffer = Vec::new();
+            let haystack = ["b", &"a".repeat(size), "b"].join("");
+            escape_vectored(&haystack, &mut buffer).unwrap();
+            assert_eq!(buffer, ["b", &"foo".repeat(size), "b"].join("").as_bytes());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_escape_vectored_batches() {
+        use v_escape_base::writer::IO_SLICES_LEN;
+
+        struct Vectored {
+            buf: Vec<u8>,
+            calls: usize,
+            limit: usize,
+        }
+
+        impl std::io::Write for Vectored {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.write_vectored(&[std::io::IoSlice::new(buf)])
+            }
+
+            fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+                self.calls += 1;
+                let mut n = 0;
+                for buf in bufs {
+                    let len = buf.len().min(self.limit - n);
+                    self.buf.extend_from_slice(&buf[..len]);
+                    n += len;
+                    if n == self.limit {
+                        break;
+                    }
+                }
+                Ok(n)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let new = |limit| Vectored {
+            buf: Vec::new(),
+            calls: 0,
+            limit,
+        };
+
+        let haystack = "xa".repeat(1000);
+        let mut writer = new(usize::MAX);
+        escape_vectored(&haystack, &mut writer).unwrap();
+        assert_eq!(writer.buf, "xfoo".repeat(1000).as_bytes());
+        assert_eq!(writer.calls, 2000usize.div_ceil(IO_SLICES_LEN));
+
+        // Short writes resume in the middle of a slice
+        let mut writer = new(5);
+        escape_vectored(&haystack, &mut writer).unwrap();
+        assert_eq!(writer.buf, "xfoo".repeat(1000).as_bytes());
+
+        let haystack = "hello world ".repeat(1000);
+        let mut writer = new(5);
+        escape_vectored(&haystack, &mut writer).unwrap();
+        assert_eq!(writer.buf, haystack.as_bytes());
+        assert_eq!(writer.calls, haystack.len() / 5);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_escape_vectored_borrows_input() {
+        struct Spans(Vec<(*const u8, usize)>);
+
+        impl std::io::Write for Spans {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.write_vectored(&[std::io::IoSlice::new(buf)])
+            }
+
+            fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+                self.0
+                    .extend(bufs.iter().map(|buf| (buf.as_ptr(), buf.len())));
+                Ok(bufs.iter().map(|buf| buf.len()).sum())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let haystack = ["b".repeat(100), "a".to_string(), "b".repeat(100)].join("");
+        let mut writer = Spans(Vec::new());
+        escape_vectored(&haystack, &mut writer).unwrap();
+        assert_eq!(writer.0.len(), 3);
+        assert_eq!(writer.0[0], (haystack.as_ptr(), 100));
+        assert_eq!(writer.0[1].1, 3);
+        assert_eq!(writer.0[2], (haystack[101..].as_ptr(), 100));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_escape_vectored_error() {
+        struct Failing;
+
+        impl std::io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("failed"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let err = escape_vectored("xa", &mut Failing).unwrap_err();
+        assert_eq!(err.to_string(), "failed");
+
+        let err = escape_vectored(&"a".repeat(10000), &mut Failing).unwrap_err();
+        assert_eq!(err.to_string(), "failed");
+    }
 }
 
 mod false_positive {
@@ -774,6 +1161,7 @@ mod false_positive {
     impl<V: Vector> Escapes for Escape<V> {
         const ESCAPE_LEN: usize = 6usize;
         const FALSE_POSITIVE: bool = true;
+        const MAX_REPLACEMENT_LEN: usize = 6usize;
         type Vector = V;
         fn masking(&self, vector2: V) -> V {
             vector2
@@ -880,4 +1268,431 @@ mod false_positive {
         escape_bytes(input, &mut buffer_bytes);
         assert_eq!(buffer_bytes, expected.as_bytes());
     }
+
+    #[test]
+    fn test_escaped_len_false_positive() {
+        let input = r#"<script>alert("Hello & 'World'")</script>"#;
+        let expected =
+            r#"&lt;script&gt;alert(&quot;Hello &amp; &#x27;World&#x27;&quot;)&lt;&#x2f;script&gt;"#;
+        assert_eq!(escaped_len(input), expected.len());
+        assert_eq!(escaped_len(&input.repeat(100)), expected.len() * 100);
+
+        // Bytes in the masking ranges that are not escaped
+        let haystack = "#$%=?@[]^".repeat(100);
+        assert_eq!(escaped_len(&haystack), haystack.len());
+    }
+
+    #[test]
+    fn test_escape_reserved_false_positive() {
+        assert_eq!(MAX_REPLACEMENT_LEN, 6);
+
+        let input = r#"<script>alert("Hello & 'World'")</script>"#.repeat(100);
+        let expected =
+            r#"&lt;script&gt;alert(&quot;Hello &amp; &#x27;World&#x27;&quot;)&lt;&#x2f;script&gt;"#
+                .repeat(100);
+
+        let mut buffer = String::new();
+        escape_string_reserved(&input, &mut buffer);
+        assert_eq!(buffer, expected);
+
+        let mut buffer = Vec::new();
+        escape_bytes_reserved(&input, &mut buffer);
+        assert_eq!(buffer, expected.as_bytes());
+    }
+
+    #[test]
+    fn test_escape_cow_false_positive() {
+        use std::borrow::Cow;
+
+        // Bytes in the masking ranges that are not escaped
+        let haystack = "#$%=?@[]^".repeat(100);
+        assert!(matches!(escape_cow(&haystack), Cow::Borrowed(_)));
+
+        let haystack = [&haystack, "<"].join("");
+        assert_eq!(
+            escape_cow(&haystack),
+            ["#$%=?@[]^".repeat(100), "&lt;".to_string()].join("")
+        );
+    }
+
+    #[test]
+    fn test_escape_iter_false_positive() {
+        let input = r#"<script>alert("Hello & 'World'")</script>"#.repeat(10);
+        let expected =
+            r#"&lt;script&gt;alert(&quot;Hello &amp; &#x27;World&#x27;&quot;)&lt;&#x2f;script&gt;"#
+                .repeat(10);
+        assert_eq!(escape_iter(&input).collect::<String>(), expected);
+
+        // Bytes in the masking ranges that are not escaped
+        let haystack = "#$%=?@[]^".repeat(100);
+        assert_eq!(escape_iter(&haystack).collect::<Vec<_>>(), [&haystack]);
+    }
+
+    #[test]
+    fn test_first_escape_position_false_positive() {
+        // Bytes in the masking ranges that are not escaped
+        let haystack = "#$%=?@[]^".repeat(100);
+        assert_eq!(first_escape_position(&haystack), None);
+        assert!(!needs_escape(&haystack));
+
+        let haystack = [&haystack, "&"].join("");
+        assert_eq!(first_escape_position(&haystack), Some(900));
+        assert!(needs_escape(&haystack));
+
+        let haystack = ["=?@", "<", &"^".repeat(100)].join("");
+        assert_eq!(first_escape_position(&haystack), Some(3));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_complete_html_escaping_vectored() {
+        let input = r#"<script>alert("Hello & 'World'")</script>"#.repeat(100);
+        let expected =
+            r#"&lt;script&gt;alert(&quot;Hello &amp; &#x27;World&#x27;&quot;)&lt;&#x2f;script&gt;"#
+                .repeat(100);
+
+        let mut buffer = Vec::new();
+        escape_vectored(&input, &mut buffer).unwrap();
+        assert_eq!(buffer, expected.as_bytes());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_complete_html_escaping_io() {
+        let input = r#"<script>alert("Hello & 'World'")</script>"#.repeat(100);
+        let expected =
+            r#"&lt;script&gt;alert(&quot;Hello &amp; &#x27;World&#x27;&quot;)&lt;&#x2f;script&gt;"#
+                .repeat(100);
+
+        let mut buffer = Vec::new();
+        escape_io(&input, &mut buffer).unwrap();
+        assert_eq!(buffer, expected.as_bytes());
+    }
+}
+
+// Implemented as before `MAX_REPLACEMENT_LEN` existed
+mod unknown_max_len {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct Equal<V: Vector> {
+        a: V,
+    }
+
+    struct Builder;
+    impl EscapesBuilder for Builder {
+        type Escapes<V: Vector> = Equal<V>;
+
+        fn new<V: Vector>() -> Self::Escapes<V> {
+            Equal { a: V::splat(b'a') }
+        }
+    }
+
+    impl<V: Vector> Escapes for Equal<V> {
+        const ESCAPE_LEN: usize = 1;
+
+        const FALSE_POSITIVE: bool = false;
+
+        type Vector = V;
+
+        #[inline(always)]
+        fn masking(&self, vector2: V) -> V {
+            self.a.cmpeq(vector2)
+        }
+
+        #[inline(always)]
+        fn escape(_: usize) -> &'static str {
+            "foo"
+        }
+
+        #[inline(always)]
+        fn position(_: u8) -> usize {
+            0
+        }
+
+        #[inline(always)]
+        fn byte_byte_compare(c: u8) -> bool {
+            c == b'a'
+        }
+    }
+
+    escape_builder!(Builder);
+
+    #[test]
+    fn test_max_escaped_len() {
+        assert_eq!(MAX_REPLACEMENT_LEN, usize::MAX);
+        assert_eq!(max_escaped_len(0), 0);
+        assert_eq!(max_escaped_len(1), usize::MAX);
+        assert_eq!(max_escaped_len(10), usize::MAX);
+    }
+
+    #[test]
+    fn test_escape_reserved() {
+        for size in [0, 1, 15, 16, 31, 32, 64, 1000, 10000] {
+            let haystack = ["b", &"a".repeat(size), "b", &"ba".repeat(size)].join("");
+            let expected = ["b", &"foo".repeat(size), "b", &"bfoo".repeat(size)].join("");
+
+            let mut buffer = String::from("x");
+            escape_string_reserved(&haystack, &mut buffer);
+            assert_eq!(buffer, ["x", &expected].join(""));
+
+            let mut buffer = b"x".to_vec();
+            escape_bytes_reserved(&haystack, &mut buffer);
+            assert_eq!(buffer, ["x", &expected].join("").as_bytes());
+        }
+    }
+}
+
+// A safe implementation understating `MAX_REPLACEMENT_LEN`
+mod understated_max_len {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct Equal<V: Vector> {
+        a: V,
+    }
+
+    struct Builder;
+    impl EscapesBuilder for Builder {
+        type Escapes<V: Vector> = Equal<V>;
+
+        fn new<V: Vector>() -> Self::Escapes<V> {
+            Equal { a: V::splat(b'a') }
+        }
+    }
+
+    impl<V: Vector> Escapes for Equal<V> {
+        const ESCAPE_LEN: usize = 1;
+
+        const FALSE_POSITIVE: bool = false;
+
+        const MAX_REPLACEMENT_LEN: usize = 1;
+
+        type Vector = V;
+
+        #[inline(always)]
+        fn masking(&self, vector2: V) -> V {
+            self.a.cmpeq(vector2)
+        }
+
+        #[inline(always)]
+        fn escape(_: usize) -> &'static str {
+            "foofoofoo"
+        }
+
+        #[inline(always)]
+        fn position(_: u8) -> usize {
+            0
+        }
+
+        #[inline(always)]
+        fn byte_byte_compare(c: u8) -> bool {
+            c == b'a'
+        }
+    }
+
+    escape_builder!(Builder);
+
+    #[test]
+    fn test_escape_reserved() {
+        // The reserved capacity is exceeded, so the buffer grows as it is written
+        for size in [0, 1, 15, 16, 31, 32, 64, 1000, 10000] {
+            let haystack = ["b", &"a".repeat(size), "b", &"ba".repeat(size)].join("");
+            let expected = [
+                "b",
+                &"foofoofoo".repeat(size),
+                "b",
+                &"bfoofoofoo".repeat(size),
+            ]
+            .join("");
+
+            let mut buffer = String::from("x");
+            escape_string_reserved(&haystack, &mut buffer);
+            assert_eq!(buffer, ["x", &expected].join(""));
+
+            let mut buffer = b"x".to_vec();
+            escape_bytes_reserved(&haystack, &mut buffer);
+            assert_eq!(buffer, ["x", &expected].join("").as_bytes());
+        }
+    }
+}
+
+mod unescape {
+    use v_escape_base::{
+        UnescapeError, UnescapeErrorKind, Unescaped, Unescapes, UnescapesBuilder, Vector,
+        unescape_builder,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    struct Backslash<V: Vector> {
+        a: V,
+    }
+
+    struct Builder;
+    impl UnescapesBuilder for Builder {
+        type Unescapes<V: Vector> = Backslash<V>;
+
+        fn new<V: Vector>() -> Self::Unescapes<V> {
+            Backslash { a: V::splat(b'\\') }
+        }
+    }
+
+    impl<V: Vector> Unescapes for Backslash<V> {
+        type Vector = V;
+
+        #[inline(always)]
+        fn masking(&self, vector2: V) -> V {
+            self.a.cmpeq(vector2)
+        }
+
+        #[inline(always)]
+        fn byte_byte_compare(c: u8) -> bool {
+            c == b'\\'
+        }
+
+        // `\n`, `\\` and `\uXXXX`
+        fn decode(haystack: &str) -> Result<(usize, Unescaped), UnescapeError> {
+            match haystack.as_bytes().get(1) {
+                Some(b'n') => Ok((2, Unescaped::Str("\n"))),
+                Some(b'\\') => Ok((2, Unescaped::Str("\\"))),
+                Some(b'u') => {
+                    let hex = haystack
+                        .get(2..6)
+                        .ok_or(UnescapeError::new(0, UnescapeErrorKind::Incomplete))?;
+                    let n = u32::from_str_radix(hex, 16)
+                        .map_err(|_| UnescapeError::new(2, UnescapeErrorKind::Unknown))?;
+                    let c = char::from_u32(n)
+                        .ok_or(UnescapeError::new(0, UnescapeErrorKind::InvalidCodePoint))?;
+                    Ok((6, Unescaped::Char(c)))
+                }
+                Some(_) => Err(UnescapeError::new(0, UnescapeErrorKind::Unknown)),
+                None => Err(UnescapeError::new(0, UnescapeErrorKind::Incomplete)),
+            }
+        }
+    }
+
+    unescape_builder!(Builder);
+
+    fn unescape(haystack: &str) -> Result<String, UnescapeError> {
+        let mut buffer = String::new();
+        unescape_string(haystack, &mut buffer).map(|()| buffer)
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape("").unwrap(), "");
+        assert_eq!(unescape("foo").unwrap(), "foo");
+        assert_eq!(unescape("\\n").unwrap(), "\n");
+        assert_eq!(unescape("a\\\\b\\nc").unwrap(), "a\\b\nc");
+        assert_eq!(unescape("\\u00e9🚀\\u00E9").unwrap(), "é🚀é");
+    }
+
+    #[test]
+    fn test_unescape_sizes() {
+        for size in [1, 15, 16, 31, 32, 33, 63, 64, 65, 1000] {
+            let clean = "b".repeat(size);
+            assert_eq!(unescape(&clean).unwrap(), clean);
+
+            let haystack = [&clean, "\\n", &clean].join("");
+            assert_eq!(
+                unescape(&haystack).unwrap(),
+                [&clean, "\n", &clean].join("")
+            );
+
+            let haystack = "\\n".repeat(size);
+            assert_eq!(unescape(&haystack).unwrap(), "\n".repeat(size));
+
+            let haystack = "b\\u0041".repeat(size);
+            assert_eq!(unescape(&haystack).unwrap(), "bA".repeat(size));
+        }
+    }
+
+    #[test]
+    fn test_unescape_across_vectors() {
+        // Sequences starting at every offset, straddling vector boundaries
+        for prefix in 0..70 {
+            let clean = "b".repeat(prefix);
+            let haystack = [&clean, "\\u0041", &clean, "\\\\"].join("");
+            let expected = [&clean, "A", &clean, "\\"].join("");
+            assert_eq!(unescape(&haystack).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_unescape_errors() {
+        for prefix in [0, 1, 15, 16, 31, 32, 33, 64, 100] {
+            let clean = "b".repeat(prefix);
+
+            let err = unescape(&[&clean, "\\q", &clean].join("")).unwrap_err();
+            assert_eq!(err, UnescapeError::new(prefix, UnescapeErrorKind::Unknown));
+
+            let err = unescape(&[&clean, "\\"].join("")).unwrap_err();
+            assert_eq!(
+                err,
+                UnescapeError::new(prefix, UnescapeErrorKind::Incomplete)
+            );
+
+            let err = unescape(&[&clean, "\\u00"].join("")).unwrap_err();
+            assert_eq!(err.kind(), UnescapeErrorKind::Incomplete);
+
+            let err = unescape(&[&clean, "\\n\\uxyzw", &clean].join("")).unwrap_err();
+            assert_eq!(err.offset(), prefix + 4);
+            assert_eq!(err.kind(), UnescapeErrorKind::Unknown);
+
+            let err = unescape(&[&clean, "\\ud800", &clean].join("")).unwrap_err();
+            assert_eq!(
+                err,
+                UnescapeError::new(prefix, UnescapeErrorKind::InvalidCodePoint)
+            );
+        }
+        assert_eq!(
+            UnescapeError::new(3, UnescapeErrorKind::Unknown).to_string(),
+            "unknown escape sequence at byte 3"
+        );
+    }
+
+    #[test]
+    fn test_unescape_restores_buffer() {
+        let haystack = ["b".repeat(100), "\\q".to_string()].join("");
+
+        let mut buffer = String::from("prefix");
+        assert!(unescape_string(&haystack, &mut buffer).is_err());
+        assert_eq!(buffer, "prefix");
+
+        let mut buffer = b"prefix".to_vec();
+        assert!(unescape_bytes(&haystack, &mut buffer).is_err());
+        assert_eq!(buffer, b"prefix");
+    }
+
+    #[test]
+    fn test_unescape_bytes() {
+        for size in [0, 1, 15, 16, 64, 1000] {
+            let mut buffer = b"x".to_vec();
+            let haystack = ["b".repeat(size), "\\n".repeat(size)].join("");
+            unescape_bytes(&haystack, &mut buffer).unwrap();
+            assert_eq!(
+                buffer,
+                ["x".to_string(), "b".repeat(size), "\n".repeat(size)]
+                    .join("")
+                    .as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_unescape_fmt() {
+        use std::fmt::Write;
+
+        for size in [0, 1, 15, 16, 64, 1000] {
+            let haystack = ["b".repeat(size), "\\u0041".repeat(size)].join("");
+            assert_eq!(
+                unescape_fmt(&haystack).to_string(),
+                ["b".repeat(size), "A".repeat(size)].join("")
+            );
+        }
+
+        let mut buffer = String::new();
+        assert!(write!(buffer, "{}", unescape_fmt("ab\\q")).is_err());
+        assert_eq!(buffer, "ab");
+    }
 }
@@ -0,0 +1,147 @@
+#![cfg(all(feature = "string", feature = "bytes"))]
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use v_escape_base::{Escapes, EscapesBuilder, Vector, escape_builder};
+
+/// Passes allocations through to the system allocator, failing every
+/// allocation larger than the current thread's limit.
+struct Limited;
+
+thread_local! {
+    static LIMIT: Cell<usize> = const { Cell::new(usize::MAX) };
+}
+
+unsafe impl GlobalAlloc for Limited {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if LIMIT.get() < layout.size() {
+            return std::ptr::null_mut();
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if LIMIT.get() < new_size {
+            return std::ptr::null_mut();
+        }
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Limited = Limited;
+
+fn with_limit<R>(limit: usize, f: impl FnOnce() -> R) -> R {
+    LIMIT.set(limit);
+    let result = f();
+    LIMIT.set(usize::MAX);
+    result
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Equal<V: Vector> {
+    a: V,
+}
+
+struct Builder;
+impl EscapesBuilder for Builder {
+    type Escapes<V: Vector> = Equal<V>;
+
+    fn new<V: Vector>() -> Self::Escapes<V> {
+        Equal { a: V::splat(b'a') }
+    }
+}
+
+impl<V: Vector> Escapes for Equal<V> {
+    const ESCAPE_LEN: usize = 1;
+
+    const FALSE_POSITIVE: bool = false;
+
+    const MAX_REPLACEMENT_LEN: usize = 3;
+
+    type Vector = V;
+
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        self.a.cmpeq(vector2)
+    }
+
+    #[inline(always)]
+    fn escape(_: usize) -> &'static str {
+        "foo"
+    }
+
+    #[inline(always)]
+    fn position(_: u8) -> usize {
+        0
+    }
+
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        c == b'a'
+    }
+}
+
+escape_builder!(Builder);
+
+#[test]
+fn test_try_escape_string() {
+    for size in [0, 1, 15, 16, 64, 1000, 10000] {
+        let mut buffer = String::from("b");
+        let haystack = ["a".repeat(size), "b".repeat(size)].join("");
+        try_escape_string(&haystack, &mut buffer).unwrap();
+        assert_eq!(
+            buffer,
+            ["b", &"foo".repeat(size), &"b".repeat(size)].join("")
+        );
+    }
+}
+
+#[test]
+fn test_try_escape_bytes() {
+    for size in [0, 1, 15, 16, 64, 1000, 10000] {
+        let mut buffer = b"b".to_vec();
+        let haystack = ["a".repeat(size), "b".repeat(size)].join("");
+        try_escape_bytes(&haystack, &mut buffer).unwrap();
+        assert_eq!(
+            buffer,
+            ["b", &"foo".repeat(size), &"b".repeat(size)]
+                .join("")
+                .as_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_try_escape_string_error() {
+    for haystack in ["a".repeat(10000), "b".repeat(10000), "ab".repeat(5000)] {
+        let mut buffer = String::with_capacity(16);
+        buffer.push_str("prefix");
+        assert!(with_limit(1024, || try_escape_string(&haystack, &mut buffer)).is_err());
+        assert_eq!(buffer, "prefix");
+    }
+}
+
+#[test]
+fn test_try_escape_bytes_error() {
+    for haystack in ["a".repeat(10000), "b".repeat(10000), "ab".repeat(5000)] {
+        let mut buffer = Vec::with_capacity(16);
+        buffer.extend_from_slice(b"prefix");
+        assert!(with_limit(1024, || try_escape_bytes(&haystack, &mut buffer)).is_err());
+        assert_eq!(buffer, b"prefix");
+    }
+}
+
+#[test]
+fn test_try_escape_within_limit() {
+    let haystack = "ab".repeat(100);
+    let mut buffer = String::new();
+    with_limit(1024, || try_escape_string(&haystack, &mut buffer)).unwrap();
+    assert_eq!(buffer, "foob".repeat(100));
+}
@@ -6,6 +6,11 @@ mod v_html;
 
 fn functions(c: &mut Criterion) {
     common::register_cases!(c, "v_htmlescape/Escaping", v_html::escaping);
+    common::register_cases!(
+        c,
+        "v_htmlescape/Escaping reserved",
+        v_html::escaping_reserved
+    );
 }
 
 criterion_group!(benches, functions);
@@ -6,6 +6,11 @@ mod v_json;
 
 fn functions(c: &mut Criterion) {
     common::register_cases!(c, "v_jsonescape/Escaping", v_json::escaping);
+    common::register_cases!(
+        c,
+        "v_jsonescape/Escaping reserved",
+        v_json::escaping_reserved
+    );
 }
 
 criterion_group!(benches, functions);
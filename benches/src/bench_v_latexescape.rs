@@ -6,6 +6,11 @@ mod v_latex;
 
 fn functions(c: &mut Criterion) {
     common::register_cases!(c, "v_latexescape/Escaping", v_latex::escaping);
+    common::register_cases!(
+        c,
+        "v_latexescape/Escaping reserved",
+        v_latex::escaping_reserved
+    );
 }
 
 criterion_group!(benches, functions);
@@ -1,5 +1,5 @@
 use criterion::Bencher;
-use v_latexescape::escape_string;
+use v_latexescape::{escape_string, escape_string_reserved};
 
 pub fn escaping(corpus: &str) -> impl FnMut(&mut Bencher) {
     move |b: &mut Bencher| {
@@ -11,3 +11,14 @@ pub fn escaping(corpus: &str) -> impl FnMut(&mut Bencher) {
         });
     }
 }
+
+pub fn escaping_reserved(corpus: &str) -> impl FnMut(&mut Bencher) {
+    move |b: &mut Bencher| {
+        let mut buf = String::with_capacity(corpus.len());
+
+        b.iter(|| {
+            buf.clear();
+            escape_string_reserved(corpus, &mut buf);
+        });
+    }
+}
@@ -1,66 +1,142 @@
+use std::cmp::Reverse;
+
 use proc_macro2::{Span, TokenStream};
 use quote::{TokenStreamExt, quote};
 use syn::{
-    Ident, Lit, Token, parenthesized,
+    Ident, Lit, Path, Token, Visibility, parenthesized,
     parse::{Parse, ParseBuffer, ParseStream},
     punctuated::Punctuated,
     token::{Not, Paren},
 };
 
 use crate::{
-    pairs::{Pair, Pairs},
+    pairs::{Layout, Pair, Pairs, Plan, Sequence},
     switch::{self, Masking, Switch},
+    trie::Trie,
 };
 
-/// Parse template and return pairs
-pub fn parse_template(tokens: TokenStream) -> syn::Result<Vec<Pair>> {
-    let mut builder = syn::parse2::<Builder>(tokens)?;
-    let mut pairs = builder.build();
+/// Parse template and return the pairs of bytes and the sequences of bytes
+pub fn parse_template(tokens: TokenStream) -> syn::Result<(Vec<Pair>, Vec<Sequence>)> {
+    let builder = syn::parse2::<Builder>(tokens)?;
+    let (mut pairs, mut sequences) = builder.build()?;
 
     // need order for calculate ranges
     pairs.sort_by_key(|p| p.ch);
+    sequences.sort_by(|a, b| a.bytes.cmp(&b.bytes));
     // check repeated
-    for i in 0..pairs.len() - 1 {
-        let p1 = &pairs[i];
-        let p2 = &pairs[i + 1];
-        if p1.ch == p2.ch {
-            return Err(syn::Error::new(Span::call_site(), "Repeated character"));
-        }
+    let repeated = pairs.windows(2).any(|w| w[0].ch == w[1].ch)
+        || sequences.windows(2).any(|w| w[0].bytes == w[1].bytes);
+    if repeated {
+        return Err(syn::Error::new(Span::call_site(), "Repeated character"));
+    }
+    // the bytes above 0x7F would be found inside the characters
+    let non_ascii = sequences.iter().any(|s| !s.bytes.is_ascii());
+    if non_ascii && pairs.iter().any(|p| !p.ch.is_ascii()) {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "Bytes above 0x7F can not be escaped along with characters outside ASCII",
+        ));
+    }
+    // the table of positions keeps one value for the bytes not escaped
+    if pairs.len() > usize::from(u8::MAX) {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "At most 255 bytes can be escaped",
+        ));
     }
 
-    Ok(pairs)
+    Ok((pairs, sequences))
 }
 
-struct Ch(i8);
-
-impl Ch {
-    fn new(n: u8) -> Result<Self, ()> {
-        match n < (i8::MAX as u8) {
-            true => Ok(Ch(n as i8)),
-            false => Err(()),
-        }
-    }
-}
+/// An escaped byte, any from `0x00` to `0xFF`.
+///
+/// Char literals are limited to ASCII, since any other character is encoded
+/// in more than one byte and can not be part of a range.
+struct Ch(u8);
 
 impl Parse for Ch {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let lit: &Lit = &input.parse()?;
-        let map_err = |_: ()| syn::Error::new(lit.span(), "Only accept ASCII characters");
+        let map_err = |msg: &str| syn::Error::new(lit.span(), msg);
         Ok(match lit {
-            Lit::Byte(v) => Ch::new(v.value()).map_err(map_err)?,
-            Lit::Char(v) => Ch::new(v.value() as u8).map_err(map_err)?,
+            Lit::Byte(v) => Ch(v.value()),
+            Lit::Char(v) if v.value().is_ascii() => Ch(v.value() as u8),
+            Lit::Char(_) => {
+                return Err(map_err(
+                    "Ranges only accept ASCII characters, use a byte literal above 0x7F",
+                ));
+            }
             Lit::Int(v) => v
-                .base10_parse::<i8>()
-                .map_err(|_| ())
-                .and_then(|n| Ch::new(n as u8))
-                .map_err(map_err)?,
-            _ => return Err(map_err(())),
+                .base10_parse::<u8>()
+                .map(Ch)
+                .map_err(|_| map_err("Only accept bytes from 0x00 to 0xFF"))?,
+            _ => return Err(map_err("Only accept bytes and ASCII characters")),
         })
     }
 }
 
+/// The characters of a mapping: a single one, an inclusive range or a
+/// sequence of bytes, from a string, a byte string or a character outside
+/// ASCII.
+enum Chars {
+    One(Ch),
+    Range(Ch, Ch),
+    Sequence(Vec<u8>),
+}
+
+impl Chars {
+    fn sequence(span: Span, bytes: Vec<u8>) -> syn::Result<Self> {
+        match bytes[..] {
+            [] => Err(syn::Error::new(span, "Empty sequences can not be escaped")),
+            [b] => Ok(Chars::One(Ch(b))),
+            _ => Ok(Chars::Sequence(bytes)),
+        }
+    }
+}
+
+impl Parse for Chars {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        match input.fork().parse() {
+            Ok(Lit::Char(c)) if !c.value().is_ascii() && !input.peek2(Token![..=]) => {
+                let _: Lit = input.parse()?;
+                return Ok(Chars::Sequence(c.value().to_string().into_bytes()));
+            }
+            Ok(Lit::Str(s)) => {
+                let _: Lit = input.parse()?;
+                return Chars::sequence(s.span(), s.value().into_bytes());
+            }
+            Ok(Lit::ByteStr(s)) => {
+                let _: Lit = input.parse()?;
+                // A sequence ends at a char boundary, so strings are still escaped into strings
+                if core::str::from_utf8(&s.value()).is_err() {
+                    return Err(syn::Error::new(
+                        s.span(),
+                        "Byte strings should be valid UTF-8",
+                    ));
+                }
+                return Chars::sequence(s.span(), s.value());
+            }
+            _ => (),
+        }
+        let start: Ch = input.parse()?;
+        if !input.peek(Token![..=]) {
+            return Ok(Chars::One(start));
+        }
+        let _: Token![..=] = input.parse()?;
+        let span = input.span();
+        let end: Ch = input.parse()?;
+        if end.0 < start.0 {
+            return Err(syn::Error::new(
+                span,
+                "Range end should not be below its start",
+            ));
+        }
+        Ok(Chars::Range(start, end))
+    }
+}
+
 struct PairBuilder {
-    ch: Ch,
+    chars: Chars,
     _s: Token![->],
     quote: syn::LitStr,
 }
@@ -68,13 +144,113 @@ struct PairBuilder {
 impl Parse for PairBuilder {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         Ok(PairBuilder {
-            ch: input.parse()?,
+            chars: input.parse()?,
             _s: input.parse()?,
             quote: input.parse()?,
         })
     }
 }
 
+/// A piece of the replacement of a range.
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Lit(String),
+    /// The character, as `{}` or `{:04x}`, in the given radix, uppercase
+    /// and zero padded to the given width.
+    Arg {
+        radix: char,
+        zero: bool,
+        width: usize,
+    },
+}
+
+/// The replacement of a range, a format string taking the character as argument.
+///
+/// Supports `{}` and `{:x}`, `{:X}`, `{:o}` and `{:b}`, optionally padded to a
+/// width as in `{:4x}` or `{:04x}`. Braces are escaped as `{{` and `}}`.
+#[derive(Debug, PartialEq)]
+struct Format(Vec<Piece>);
+
+impl Format {
+    fn parse(quote: &syn::LitStr) -> syn::Result<Self> {
+        let err = |msg: &str| syn::Error::new(quote.span(), msg);
+        let value = quote.value();
+        let mut pieces = Vec::new();
+        let mut lit = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    lit.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    lit.push('}');
+                }
+                '}' => return Err(err("Unmatched `}` in format, escape it as `}}`")),
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| err("Unmatched `{` in format, escape it as `{{`"))?;
+                    let spec = &rest[..end];
+                    chars = rest[end + 1..].chars();
+
+                    let spec = match spec.strip_prefix(':') {
+                        Some(spec) => spec,
+                        None if spec.is_empty() => spec,
+                        None => return Err(err("Only `{}` and `{:...}` formats are supported")),
+                    };
+                    let (spec, radix) = match spec.chars().last() {
+                        Some(c @ ('x' | 'X' | 'o' | 'b')) => (&spec[..spec.len() - 1], c),
+                        _ => (spec, 'd'),
+                    };
+                    let zero = spec.starts_with('0') && 1 < spec.len();
+                    let width = match spec {
+                        "" => 0,
+                        _ => spec
+                            .parse()
+                            .map_err(|_| err("Format should be like `{}`, `{:x}` or `{:04x}`"))?,
+                    };
+                    if !lit.is_empty() {
+                        pieces.push(Piece::Lit(core::mem::take(&mut lit)));
+                    }
+                    pieces.push(Piece::Arg { radix, zero, width });
+                }
+                c => lit.push(c),
+            }
+        }
+        if !lit.is_empty() {
+            pieces.push(Piece::Lit(lit));
+        }
+
+        Ok(Format(pieces))
+    }
+
+    fn render(&self, ch: u8) -> String {
+        self.0
+            .iter()
+            .map(|piece| match *piece {
+                Piece::Lit(ref lit) => lit.clone(),
+                Piece::Arg { radix, zero, width } => {
+                    let n = match radix {
+                        'x' => format!("{ch:x}"),
+                        'X' => format!("{ch:X}"),
+                        'o' => format!("{ch:o}"),
+                        'b' => format!("{ch:b}"),
+                        _ => format!("{ch}"),
+                    };
+                    match zero {
+                        true => format!("{n:0>width$}"),
+                        false => format!("{n:>width$}"),
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
 /// A parser for macro invocation syntax that extracts character-to-escape-sequence mappings.
 ///
 /// This struct parses the macro invocation format like `escape!(path, (b'"' -> "&quot;", b'<' -> "&lt;"))`
@@ -102,14 +278,49 @@ impl Parse for Builder {
 
 impl Builder {
     /// Consume and return arguments data
-    fn build(self) -> Vec<Pair> {
+    ///
+    /// Ranges are expanded to a pair per character, and single characters
+    /// take precedence over them.
+    fn build(self) -> syn::Result<(Vec<Pair>, Vec<Sequence>)> {
         let Builder { pairs, .. } = self;
 
-        pairs
-            .into_pairs()
-            .map(|x| x.into_value())
-            .map(|x| Pair::new(x.ch.0, x.quote.value()))
-            .collect()
+        let mut singles = Vec::new();
+        let mut ranged: Vec<Pair> = Vec::new();
+        let mut sequences = Vec::new();
+        for x in pairs.into_iter() {
+            match x.chars {
+                Chars::One(ch) => singles.push(Pair::new(ch.0, x.quote.value())),
+                Chars::Sequence(bytes) => sequences.push(Sequence::new(bytes, x.quote.value())),
+                Chars::Range(start, end) => {
+                    let format = Format::parse(&x.quote)?;
+                    for ch in start.0..=end.0 {
+                        if ranged.iter().any(|p| p.ch == ch) {
+                            return Err(syn::Error::new(x.quote.span(), "Overlapping ranges"));
+                        }
+                        ranged.push(Pair::new(ch, format.render(ch)));
+                    }
+                }
+            }
+        }
+        ranged.retain(|r| singles.iter().all(|p| p.ch != r.ch));
+        singles.extend(ranged);
+
+        Ok((singles, sequences))
+    }
+}
+
+/// The unit struct implementing `Escaper`, as `pub Html`.
+pub(crate) struct Unit {
+    vis: Visibility,
+    ident: Ident,
+}
+
+impl Parse for Unit {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            vis: input.parse()?,
+            ident: input.parse()?,
+        })
     }
 }
 
@@ -126,15 +337,40 @@ pub(crate) type Tables = (Ident, Ident, Ident);
 /// for efficient character escaping at runtime.
 pub(crate) struct Generator<'a> {
     pairs: &'a [Pair],
-    crate_name: Ident,
+    sequences: &'a [Sequence],
+    crate_name: Path,
+    escaper: Option<Unit>,
 }
 
 impl Generator<'_> {
-    pub fn new<'a>(pairs: &'a [Pair], crate_name: &str) -> Generator<'a> {
+    pub fn new<'a>(
+        pairs: &'a [Pair],
+        sequences: &'a [Sequence],
+        crate_name: Path,
+    ) -> Generator<'a> {
         Generator {
             pairs,
-            crate_name: Ident::new(crate_name, Span::call_site()),
+            sequences,
+            crate_name,
+            escaper: None,
+        }
+    }
+
+    /// Also generates the unit struct `escaper` implementing `Escaper`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `syn::Error` if bytes above `0x7F` are escaped, as their
+    /// escapers do not write valid UTF-8.
+    pub fn with_escaper(mut self, escaper: Unit) -> syn::Result<Self> {
+        if !self.pairs.iter().all(|p| p.ch.is_ascii()) {
+            return Err(syn::Error::new(
+                escaper.ident.span(),
+                "An escaper can not be generated when bytes above 0x7F are escaped",
+            ));
         }
+        self.escaper = Some(escaper);
+        Ok(self)
     }
 
     /// Builds a TokenStream containing the generated code for character escaping.
@@ -154,32 +390,91 @@ impl Generator<'_> {
         buf
     }
 
+    /// Builds a TokenStream containing the generated code for unescaping.
+    ///
+    /// The replacements are decoded with a match following a trie built from
+    /// the table, dispatching on their first byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `syn::Error` if the replacements can not be decoded
+    /// unambiguously.
+    pub fn build_unescape(&self) -> syn::Result<TokenStream> {
+        let trie = Trie::new(self.pairs, self.sequences)?;
+        let leads: Vec<_> = trie
+            .leads()
+            .map(|b| syn::LitByte::new(b, Span::call_site()))
+            .collect();
+        let fields: Vec<_> = (0..leads.len())
+            .map(|i| Ident::new(&format!("lead_{i}"), Span::call_site()))
+            .collect();
+        let (first, rest) = fields.split_first().expect("at least one pair");
+        let decode = trie.decode();
+        let crate_name = &self.crate_name;
+
+        Ok(quote! {
+        use #crate_name::{
+            unescape_builder, UnescapeError, UnescapeErrorKind, Unescaped, Unescapes,
+            UnescapesBuilder,
+        };
+
+        #[derive(Debug, Clone, Copy)]
+        struct Unescape<V: Vector> {
+            #(#fields: V,)*
+        }
+
+        #[allow(dead_code)]
+        struct UnescapeBuilder;
+        impl UnescapesBuilder for UnescapeBuilder {
+            type Unescapes<V: Vector> = Unescape<V>;
+
+            fn new<V: Vector>() -> Self::Unescapes<V> {
+                Unescape {
+                    #(#fields: V::splat(#leads),)*
+                }
+            }
+        }
+
+        impl<V: Vector> Unescapes for Unescape<V> {
+            type Vector = V;
+
+            #[inline(always)]
+            fn masking(&self, vector2: V) -> V {
+                vector2.cmpeq(self.#first)#(.or(vector2.cmpeq(self.#rest)))*
+            }
+
+            #[inline(always)]
+            fn byte_byte_compare(c: u8) -> bool {
+                matches!(c, #(#leads)|*)
+            }
+
+            fn decode(haystack: &str) -> Result<(usize, Unescaped), UnescapeError> {
+                let haystack = haystack.as_bytes();
+                #decode
+            }
+        }
+
+        unescape_builder!(UnescapeBuilder);
+        })
+    }
+
     fn write_static_table(&self, buf: &mut TokenStream) -> Tables {
         let len = self.pairs.len();
         let v_char = Ident::new("V_ESCAPE_CHARS", proc_macro2::Span::call_site());
         let v_quotes = Ident::new("V_ESCAPE_QUOTES", proc_macro2::Span::call_site());
         let v_len = Ident::new("V_ESCAPE_LEN", proc_macro2::Span::call_site());
 
-        if len == 1 {
-            let ch = self.pairs[0].ch;
-            let quote = &self.pairs[0].quote;
-            buf.extend(quote! {
-                const #v_char: u8 = #ch;
-                static #v_quotes: &str = #quote;
-            })
-        } else {
-            let mut chs = Vec::with_capacity(256);
-            let sch = self.pairs[0].ch;
-            for i in 0..=255_u8 {
-                let n = self.pairs.binary_search_by(|s| s.ch.cmp(&i)).unwrap_or(len);
-                chs.push(n as u8)
-            }
-            let quotes: Vec<&str> = self.pairs.iter().map(|s| s.quote.as_str()).collect();
-            buf.extend(quote! {
-                static #v_char: [u8; 256] = [#(#chs),*];
-                static #v_quotes: [&str; #len] = [#(#quotes),*];
-            })
+        // A table even for a single byte, `Escapes` indexes it
+        let mut chs = Vec::with_capacity(256);
+        for i in 0..=255_u8 {
+            let n = self.pairs.binary_search_by(|s| s.ch.cmp(&i)).unwrap_or(len);
+            chs.push(n as u8)
         }
+        let quotes: Vec<&str> = self.pairs.iter().map(|s| s.quote.as_str()).collect();
+        buf.extend(quote! {
+            static #v_char: [u8; 256] = [#(#chs),*];
+            static #v_quotes: [&str; #len] = [#(#quotes),*];
+        });
         buf.extend(quote! {
             const #v_len: usize = #len;
         });
@@ -189,60 +484,451 @@ impl Generator<'_> {
 
     fn write_impl(&self, buf: &mut TokenStream) {
         let escape_len = self.pairs.len();
-        let switch: Switch = Pairs(self.pairs).into();
-        let Masking {
-            struct_body,
-            build,
-            mask_body,
-            false_positive,
-        } = switch.into();
-        let crate_name = &self.crate_name;
-        let q = quote! {
-        use #crate_name::{escape_builder, Escapes, EscapesBuilder, Vector};
+        let max_replacement_len = self
+            .pairs
+            .iter()
+            .map(|p| p.quote.len())
+            .chain(self.sequences.iter().map(|p| p.quote.len()))
+            .max()
+            .unwrap_or(0);
+        let leads = self.leads();
+        let leads: Vec<_> = leads.into_iter().map(hex).collect();
+        let compare = match (self.pairs.is_empty(), leads.is_empty()) {
+            (_, true) => quote!((V_ESCAPE_CHARS[c as usize] as usize) < V_ESCAPE_LEN),
+            (true, false) => quote!(matches!(c, #(#leads)|*)),
+            (false, false) => quote! {
+                (V_ESCAPE_CHARS[c as usize] as usize) < V_ESCAPE_LEN || matches!(c, #(#leads)|*)
+            },
+        };
+        let escape_at = self.escape_sequences();
+        // Escaping a byte above 0x7F splits a multi-byte character, only bytes can be written
+        let utf8 = self.pairs.iter().all(|p| p.ch.is_ascii());
+        let mappings: Vec<_> = self
+            .pairs
+            .iter()
+            .map(|p| {
+                let ch = hex(p.ch);
+                let quote = &p.quote;
+                quote!((#ch, #quote))
+            })
+            .collect();
+        let sequences: Vec<_> = self
+            .sequences
+            .iter()
+            .map(|s| {
+                let bytes = syn::LitByteStr::new(&s.bytes, Span::call_site());
+                let quote = &s.quote;
+                quote!((#bytes, #quote))
+            })
+            .collect();
+        let escapes = |escape: &str, builder: &str, layout: Layout, attrs: TokenStream| {
+            let escape = Ident::new(escape, Span::call_site());
+            let builder = Ident::new(builder, Span::call_site());
+            // A first byte of a sequence only starts a candidate
+            let false_positive = layout.false_positives != 0 || !self.sequences.is_empty();
+            let Masking {
+                struct_body,
+                build,
+                mask_body,
+                shuffle,
+            } = layout.switch.into();
+            quote! {
+            #[derive(Debug, Clone, Copy)]
+            #attrs
+            struct #escape<V: Vector> #struct_body
+
+            #[allow(dead_code)]
+            struct #builder;
+            impl EscapesBuilder for #builder {
+                type Escapes<V: Vector> = #escape<V>;
+
+                fn new<V: Vector>() -> Self::Escapes<V> {
+                    #build
+                }
+            }
 
-        #[derive(Debug, Clone, Copy)]
-        struct Escape<V: Vector> #struct_body
+            impl<V: Vector> Escapes for #escape<V> {
+                const ESCAPE_LEN: usize = #escape_len;
 
-        #[allow(dead_code)]
-        struct Builder;
-        impl EscapesBuilder for Builder {
-            type Escapes<V: Vector> = Escape<V>;
+                const FALSE_POSITIVE: bool = #false_positive;
 
-            fn new<V: Vector>() -> Self::Escapes<V> {
-                #build
+                const MAX_REPLACEMENT_LEN: usize = #max_replacement_len;
+
+                const SHUFFLE: bool = #shuffle;
+
+                const UTF8: bool = #utf8;
+
+                const MAPPINGS: &'static [(u8, &'static str)] = &[#(#mappings),*];
+
+                const SEQUENCES: &'static [(&'static [u8], &'static str)] = &[#(#sequences),*];
+
+                type Vector = V;
+
+                #[inline(always)]
+                fn masking(&self, vector2: V) -> V {
+                    #mask_body
+                }
+
+                #[inline(always)]
+                fn escape(i: usize) -> &'static str {
+                    V_ESCAPE_QUOTES[i]
+                }
+
+                #[inline(always)]
+                fn position(i: u8) -> usize {
+                    V_ESCAPE_CHARS[i as usize] as usize
+                }
+
+                #escape_at
+
+                #[inline(always)]
+                fn byte_byte_compare(c: u8) -> bool {
+                    #compare
+                }
+            }
             }
+        };
+        let Plan { shuffle, sse2 } = self.plan();
+        // SSE2 has no shuffle, it gets the cheapest ranges instead of the nibble tables
+        let ranges = (sse2 != shuffle).then_some(sse2);
+        let mut builders = quote!(Builder);
+        if ranges.is_some() {
+            builders.extend(quote!(, RangeBuilder));
         }
+        let builder = if utf8 {
+            builders
+        } else {
+            quote!(@bytes #builders)
+        };
+        let crate_name = &self.crate_name;
+        let escaper = self
+            .escaper
+            .as_ref()
+            .map(|Unit { vis, ident }| quote!(#crate_name::escaper!(#vis #ident, Builder);));
+        let escape = escapes("Escape", "Builder", shuffle, TokenStream::new());
+        // Only the x86_64 backends have an SSE2 fallback
+        let range_escape = ranges.map(|layout| {
+            escapes(
+                "RangeEscape",
+                "RangeBuilder",
+                layout,
+                quote!(#[allow(dead_code)]),
+            )
+        });
+        let q = quote! {
+        use #crate_name::{escape_builder, Escapes, EscapesBuilder, Vector};
 
-        impl<V: Vector> Escapes for Escape<V> {
-            const ESCAPE_LEN: usize = #escape_len;
+        #escape
 
-            const FALSE_POSITIVE: bool = #false_positive;
+        #range_escape
 
-            type Vector = V;
+        escape_builder!(#builder);
 
-            #[inline(always)]
-            fn masking(&self, vector2: V) -> V {
-                #mask_body
-            }
+        #escaper
+        };
+        buf.extend(q);
+    }
 
-            #[inline(always)]
-            fn escape(i: usize) -> &'static str {
-                V_ESCAPE_QUOTES[i]
-            }
+    /// Returns the cheapest maskings of the escaped bytes, with and without
+    /// shuffle.
+    ///
+    /// The first bytes of the sequences are masked along with them.
+    pub fn plan(&self) -> Plan {
+        let mut masked: Vec<Pair> = self.pairs.iter().map(|p| Pair::new(p.ch, "")).collect();
+        masked.extend(self.leads().into_iter().map(|b| Pair::new(b, "")));
+        masked.sort_by_key(|p| p.ch);
+        Pairs(&masked).into()
+    }
 
-            #[inline(always)]
-            fn position(i: u8) -> usize {
-                V_ESCAPE_CHARS[i as usize] as usize
+    /// Returns the first bytes of the sequences that are not escaped bytes,
+    /// in ascending order.
+    fn leads(&self) -> Vec<u8> {
+        let mut leads: Vec<u8> = self
+            .sequences
+            .iter()
+            .map(Sequence::lead)
+            .filter(|&b| self.pairs.iter().all(|p| p.ch != b))
+            .collect();
+        leads.dedup();
+        leads
+    }
+
+    /// Builds the `Escapes::escape_at` override matching the sequences.
+    ///
+    /// Every sequence gets a slice pattern over the rest of the haystack,
+    /// longest first, and the escaped bytes are matched last. Sequences
+    /// crossing the end of the haystack are not escaped.
+    fn escape_sequences(&self) -> TokenStream {
+        if self.sequences.is_empty() {
+            return TokenStream::new();
+        }
+
+        let mut sequences: Vec<_> = self.sequences.iter().collect();
+        // A sequence is tried before the shorter ones it may start with
+        sequences.sort_by_key(|s| Reverse(s.bytes.len()));
+        let arms = sequences.into_iter().map(|s| {
+            let bytes = s.bytes.iter().map(|&b| hex(b));
+            let quote = &s.quote;
+            let len = s.bytes.len();
+            quote!([#(#bytes,)* ..] => Some((#quote, #len)),)
+        });
+        let bytes = if self.pairs.is_empty() {
+            TokenStream::new()
+        } else {
+            quote! {
+                [c, ..] => {
+                    let c = V_ESCAPE_CHARS[*c as usize] as usize;
+                    if c < V_ESCAPE_LEN {
+                        Some((V_ESCAPE_QUOTES[c], 1))
+                    } else {
+                        None
+                    }
+                }
             }
+        };
 
+        quote! {
             #[inline(always)]
-            fn byte_byte_compare(c: u8) -> bool {
-                (V_ESCAPE_CHARS[c as usize] as usize) < V_ESCAPE_LEN
+            unsafe fn escape_at(at: *const u8, end: *const u8) -> Option<(&'static str, usize)> {
+                // SAFETY: `at` is below `end`, both in the same haystack
+                let haystack = unsafe { core::slice::from_raw_parts(at, end.offset_from(at) as usize) };
+                match haystack {
+                    #(#arms)*
+                    #bytes
+                    _ => None,
+                }
             }
         }
+    }
+}
+
+/// Returns a hexadecimal literal of the byte.
+fn hex(b: u8) -> syn::LitInt {
+    syn::LitInt::new(&format!("0x{b:02X}"), Span::call_site())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(tokens: TokenStream) -> Vec<(u8, String)> {
+        parse_template(tokens)
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|p| (p.ch, p.quote))
+            .collect()
+    }
+
+    fn parse_sequences(tokens: TokenStream) -> Vec<(String, String)> {
+        parse_template(tokens)
+            .unwrap()
+            .1
+            .into_iter()
+            .map(|p| (String::from_utf8(p.bytes).unwrap(), p.quote))
+            .collect()
+    }
+
+    fn sequences(sequences: &[(&str, &str)]) -> Vec<(String, String)> {
+        sequences
+            .iter()
+            .map(|(s, q)| (s.to_string(), q.to_string()))
+            .collect()
+    }
+
+    fn err(tokens: TokenStream) -> String {
+        parse_template(tokens).unwrap_err().to_string()
+    }
+
+    fn pairs(pairs: &[(u8, &str)]) -> Vec<(u8, String)> {
+        pairs.iter().map(|(c, q)| (*c, q.to_string())).collect()
+    }
 
-        escape_builder!(Builder);
+    #[test]
+    fn test_range() {
+        assert_eq!(
+            parse(quote!(new!(0x00..=0x02 -> "\\u{:04x}", b'a'..=b'b' -> "{};");)),
+            pairs(&[
+                (0, "\\u0000"),
+                (1, "\\u0001"),
+                (2, "\\u0002"),
+                (97, "97;"),
+                (98, "98;")
+            ])
+        );
+        assert_eq!(
+            parse(quote!(new!('&' -> "&amp;", 0x1F..=0x1F -> "&#{};");)),
+            pairs(&[(31, "&#31;"), (38, "&amp;")])
+        );
+    }
+
+    #[test]
+    fn test_range_overrides() {
+        assert_eq!(
+            parse(quote!(new!(0x09 -> "\\t", 0x08..=0x0B -> "\\x{:02X}", 0x0A -> "\\n");)),
+            pairs(&[(8, "\\x08"), (9, "\\t"), (10, "\\n"), (11, "\\x0B")])
+        );
+    }
+
+    #[test]
+    fn test_range_format() {
+        let render = |format: &str, ch| {
+            let quote = syn::LitStr::new(format, Span::call_site());
+            Format::parse(&quote).unwrap().render(ch)
         };
-        buf.extend(q);
+        assert_eq!(render("{}", 10), "10");
+        assert_eq!(render("{:x}|{:X}", 31), "1f|1F");
+        assert_eq!(render("{:o} {:b}", 10), "12 1010");
+        assert_eq!(render("{:4}|{:04}|{:08b}", 5), "   5|0005|00000101");
+        assert_eq!(render("{{{}}}", 1), "{1}");
+        assert_eq!(render("\\textbackslash{{}}", 1), "\\textbackslash{}");
+        assert_eq!(render("?", 1), "?");
+    }
+
+    #[test]
+    fn test_range_errors() {
+        assert_eq!(
+            err(quote!(new!(0x00..=0x02 -> "a", 0x02..=0x03 -> "b");)),
+            "Overlapping ranges"
+        );
+        assert_eq!(
+            err(quote!(new!(0x02..=0x01 -> "a");)),
+            "Range end should not be below its start"
+        );
+        assert_eq!(
+            err(quote!(new!(0x00..=0x01 -> "{");)),
+            "Unmatched `{` in format, escape it as `{{`"
+        );
+        assert_eq!(
+            err(quote!(new!(0x00..=0x01 -> "}");)),
+            "Unmatched `}` in format, escape it as `}}`"
+        );
+        assert_eq!(
+            err(quote!(new!(0x00..=0x01 -> "{0}");)),
+            "Only `{}` and `{:...}` formats are supported"
+        );
+        assert_eq!(
+            err(quote!(new!(0x00..=0x01 -> "{:?}");)),
+            "Format should be like `{}`, `{:x}` or `{:04x}`"
+        );
+        assert_eq!(
+            err(quote!(new!(0x01 -> "a", 0x01 -> "b");)),
+            "Repeated character"
+        );
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(
+            parse(quote!(new!(0x7F -> "^?", b'\xFE' -> "\\xfe", 255 -> "\\xff");)),
+            pairs(&[(0x7F, "^?"), (0xFE, "\\xfe"), (0xFF, "\\xff")])
+        );
+        assert_eq!(
+            parse(quote!(new!(0x7E..=0x81 -> "{:02x}");)),
+            pairs(&[(0x7E, "7e"), (0x7F, "7f"), (0x80, "80"), (0x81, "81")])
+        );
+    }
+
+    #[test]
+    fn test_bytes_errors() {
+        assert_eq!(
+            err(quote!(new!('a'..='é' -> "e");)),
+            "Ranges only accept ASCII characters, use a byte literal above 0x7F"
+        );
+        assert_eq!(
+            err(quote!(new!(256 -> "a");)),
+            "Only accept bytes from 0x00 to 0xFF"
+        );
+        assert_eq!(
+            err(quote!(new!(0x00..=0xFF -> "{}");)),
+            "At most 255 bytes can be escaped"
+        );
+    }
+
+    #[test]
+    fn test_chars() {
+        let tokens = quote!(new!('\u{2029}' -> "\\u2029", '<' -> "&lt;", '\u{A0}' -> "&nbsp;", '\u{2028}' -> "\\u2028"););
+        assert_eq!(parse(tokens.clone()), pairs(&[(b'<', "&lt;")]));
+        assert_eq!(
+            parse_sequences(tokens),
+            sequences(&[
+                ("\u{A0}", "&nbsp;"),
+                ("\u{2028}", "\\u2028"),
+                ("\u{2029}", "\\u2029")
+            ])
+        );
+        assert_eq!(
+            parse_sequences(quote!(new!('🚀' -> "rocket");)),
+            sequences(&[("🚀", "rocket")])
+        );
+    }
+
+    #[test]
+    fn test_chars_errors() {
+        assert_eq!(
+            err(quote!(new!('é' -> "a", '<' -> "b", 'é' -> "c");)),
+            "Repeated character"
+        );
+        assert_eq!(
+            err(quote!(new!('é' -> "a", 0xA9 -> "b");)),
+            "Bytes above 0x7F can not be escaped along with characters outside ASCII"
+        );
+    }
+
+    #[test]
+    fn test_sequences() {
+        let tokens = quote!(new!("]]>" -> "]]]]><![CDATA[>", '<' -> "&lt;", b"</script" -> "<\\/script", "-" -> "&#45;"););
+        assert_eq!(
+            parse(tokens.clone()),
+            pairs(&[(b'-', "&#45;"), (b'<', "&lt;")])
+        );
+        assert_eq!(
+            parse_sequences(tokens),
+            sequences(&[("</script", "<\\/script"), ("]]>", "]]]]><![CDATA[>")])
+        );
+    }
+
+    #[test]
+    fn test_sequences_errors() {
+        assert_eq!(
+            err(quote!(new!("" -> "a");)),
+            "Empty sequences can not be escaped"
+        );
+        assert_eq!(
+            err(quote!(new!(b"\xFF\xFE" -> "a");)),
+            "Byte strings should be valid UTF-8"
+        );
+        assert_eq!(
+            err(quote!(new!("--" -> "a", b"--" -> "b");)),
+            "Repeated character"
+        );
+        assert_eq!(
+            err(quote!(new!("é!" -> "a", 0xFF -> "b");)),
+            "Bytes above 0x7F can not be escaped along with characters outside ASCII"
+        );
+    }
+
+    #[test]
+    fn test_unescape_ambiguous() {
+        let err = |tokens| {
+            crate::generate_unescape(tokens, "v_escape_base")
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            err(quote!(new!('&' -> "&amp;", '<' -> "&amp;lt;");)),
+            "Ambiguous replacements: \"&amp;\" is a prefix of \"&amp;lt;\""
+        );
+        assert_eq!(
+            err(quote!(new!('&' -> "&a", '\u{A0}' -> "&a");)),
+            "Ambiguous replacements: \"&a\" is a prefix of \"&a\""
+        );
+        assert!(
+            crate::generate_unescape(
+                quote!(new!('&' -> "&amp;", '<' -> "&lt;");),
+                "v_escape_base"
+            )
+            .is_ok()
+        );
     }
 }
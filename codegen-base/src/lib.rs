@@ -13,6 +13,13 @@ use proc_macro2::TokenStream;
 mod generator;
 mod pairs;
 mod switch;
+mod trie;
+
+pub use pairs::{Layout, Plan};
+pub use switch::Switch;
+
+/// An escaped source, a byte or a sequence of bytes, and its replacement
+pub type Mapping = (Vec<u8>, String);
 
 /// Generate escape functions from a token stream template
 ///
@@ -23,28 +30,88 @@ mod switch;
 /// # Arguments
 ///
 /// * `tokens` - A token stream containing the character mappings in the format `new!(char -> "escape", ...)`
-/// * `crate_name` - The name of the crate where the generated code will be used
+/// * `crate_name` - The path of the crate where the generated code will be used, such as
+///   `v_escape_base` or `::facade::v_escape`
+/// * `escaper` - The unit struct implementing `Escaper` with the generated functions, along
+///   with its visibility, such as `pub Html`, if any
 ///
 /// # Returns
 ///
 /// Returns a tuple containing:
 /// * The generated code as a `TokenStream`
-/// * A `Vec<(u8, String)>` of `(char_byte, replacement)` pairs, sorted by `char_byte`
-///   ascending. Callers can derive convenience strings (such as the concatenation of
-///   all source bytes or replacements) from this list as needed.
+/// * A `Vec<Mapping>` of `(source, replacement)` pairs, sorted by `source`
+///   ascending. The source is the escaped byte, or an escaped sequence such as `]]>`
+///   or the UTF-8 encoding of a character outside ASCII. Callers can derive convenience strings (such as the
+///   concatenation of all sources or replacements) from this list as needed.
 ///
 /// # Errors
 ///
-/// Returns a `syn::Error` if the token stream cannot be parsed or if the character
-/// mappings are invalid.
+/// Returns a `syn::Error` if the token stream, the crate path or the escaper cannot be
+/// parsed, if the character mappings are invalid, or if an escaper is asked for along
+/// with bytes above `0x7F`.
 pub fn generate(
     tokens: TokenStream,
     crate_name: &str,
-) -> syn::Result<(TokenStream, Vec<(u8, String)>)> {
-    let pairs = parse_template(tokens)?;
-    let generator = Generator::new(&pairs, crate_name);
+    escaper: Option<&str>,
+) -> syn::Result<(TokenStream, Vec<Mapping>)> {
+    let (pairs, sequences) = parse_template(tokens)?;
+    let mut generator = Generator::new(&pairs, &sequences, syn::parse_str(crate_name)?);
+    if let Some(escaper) = escaper {
+        generator = generator.with_escaper(syn::parse_str(escaper)?)?;
+    }
     let generated = generator.build();
-    let mappings: Vec<(u8, String)> = pairs.into_iter().map(|p| (p.ch, p.quote)).collect();
+    let mut mappings: Vec<Mapping> = pairs
+        .into_iter()
+        .map(|p| (vec![p.ch], p.quote))
+        .chain(sequences.into_iter().map(|p| (p.bytes, p.quote)))
+        .collect();
+    mappings.sort();
 
     Ok((generated, mappings))
 }
+
+/// Generate unescape functions from a token stream template
+///
+/// This function takes the same template as [`generate`] and generates an
+/// unescaper recognising exactly the replacements of the table, the inverse
+/// of the generated escape functions. The generated code expects the `Vector`
+/// trait to be in scope, as it is in the code returned by [`generate`].
+///
+/// # Arguments
+///
+/// * `tokens` - A token stream containing the character mappings in the format `new!(char -> "escape", ...)`
+/// * `crate_name` - The path of the crate where the generated code will be used, such as
+///   `v_escape_base` or `::facade::v_escape`
+///
+/// # Errors
+///
+/// Returns a `syn::Error` if the token stream or the crate path cannot be parsed, if
+/// the character mappings are invalid, or if the replacements can not be decoded unambiguously:
+/// when a replacement is a prefix of another one, or when it does not start with
+/// an escaped ASCII character.
+pub fn generate_unescape(tokens: TokenStream, crate_name: &str) -> syn::Result<TokenStream> {
+    let (pairs, sequences) = parse_template(tokens)?;
+    Generator::new(&pairs, &sequences, syn::parse_str(crate_name)?).build_unescape()
+}
+
+/// Plan the masking of the escaped bytes of a token stream template
+///
+/// The escaped bytes, along with the first bytes of the escaped sequences, are
+/// found in the vectors with a few ranges and equalities or with nibble
+/// tables. Every layout is scored by its vector operations plus the bytes it
+/// masks without escaping them, and [`generate`] uses the cheapest one,
+/// returned here for inspection. The nibble tables need a byte shuffle, so
+/// SSE2 without SSSE3 gets the cheapest ranges instead, planned separately.
+///
+/// # Arguments
+///
+/// * `tokens` - A token stream containing the character mappings in the format `new!(char -> "escape", ...)`
+///
+/// # Errors
+///
+/// Returns a `syn::Error` if the token stream cannot be parsed or if the character
+/// mappings are invalid.
+pub fn plan(tokens: TokenStream) -> syn::Result<Plan> {
+    let (pairs, sequences) = parse_template(tokens)?;
+    Ok(Generator::new(&pairs, &sequences, syn::parse_quote!(v_escape_base)).plan())
+}
@@ -1,4 +1,4 @@
-use std::{cmp::Reverse, ops::Index};
+use std::{fmt, ops::Index};
 
 use crate::switch::Switch;
 
@@ -9,109 +9,144 @@ pub(crate) struct Pair {
 }
 
 impl Pair {
-    pub fn new<I: Into<String>>(ch: i8, quote: I) -> Self {
+    pub fn new<I: Into<String>>(ch: u8, quote: I) -> Self {
         Pair {
-            ch: ch as u8,
+            ch,
             quote: quote.into(),
         }
     }
 }
 
-pub(crate) struct Pairs<'a>(pub &'a [Pair]);
-impl<'a> From<Pairs<'a>> for Switch {
-    fn from(val: Pairs<'a>) -> Self {
-        use Switch::*;
-        assert_ne!(val.len(), 0);
+/// A sequence of bytes and its replacement, such as `]]>` or a character
+/// outside ASCII.
+///
+/// Its first byte is masked along with the escaped bytes, and the rest of the
+/// sequence is checked before replacing it.
+#[derive(Debug)]
+pub(crate) struct Sequence {
+    pub bytes: Vec<u8>,
+    pub quote: String,
+}
 
-        if val.len() == 1 {
-            return A { a: val.ch(0) };
+impl Sequence {
+    pub fn new<B: Into<Vec<u8>>, I: Into<String>>(bytes: B, quote: I) -> Self {
+        Sequence {
+            bytes: bytes.into(),
+            quote: quote.into(),
         }
-        let e = val.len() - 1;
+    }
 
-        let mut d = vec![];
-        for i in 0..e {
-            let diff = val.ch(i + 1) - val.ch(i);
-            if 1 < diff {
-                d.push((i, diff));
-            }
+    /// Returns the first byte of the sequence.
+    pub fn lead(&self) -> u8 {
+        self.bytes[0]
+    }
+}
+
+/// The cost of a vector comparison or of a bitwise OR.
+const OP_COST: usize = 1;
+
+/// The cost of masking a range, a translation and a comparison.
+const RANGE_COST: usize = 2;
+
+/// The cost of [`Switch::Nibble`]: two shuffles, the nibbles of the vector, the
+/// intersection of the tables and the two comparisons turning it into a mask.
+const NIBBLE_COST: usize = 8;
+
+/// The cost of each byte masked without being escaped.
+///
+/// With 16 lanes, a byte value shows up about once every 16 vectors of
+/// uniformly distributed bytes, and re-checking its candidate costs about 48
+/// operations: a mispredicted branch, the lookup of its replacement and the
+/// split of the clean bytes around it.
+const FALSE_POSITIVE_COST: usize = 3;
+
+/// A masking of a set of escaped bytes and its cost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// The layout of the masking.
+    pub switch: Switch,
+    /// The number of vector operations of the masking.
+    pub ops: usize,
+    /// The number of byte values masked without being escaped, re-checked
+    /// byte by byte.
+    pub false_positives: usize,
+    /// The estimated cost of the masking, in vector operations, the lower the
+    /// better.
+    pub score: usize,
+}
+
+impl Layout {
+    fn new(switch: Switch, ops: usize, false_positives: usize) -> Self {
+        Layout {
+            switch,
+            ops,
+            false_positives,
+            score: ops + false_positives * FALSE_POSITIVE_COST,
         }
-        d.sort_unstable_by_key(|a| Reverse(a.1));
+    }
+}
 
-        match d.len() {
-            0 => Ar {
-                la: val.ch(0),
-                ra: val.ch(e),
-            },
-            1 => {
-                if e == 1 {
-                    AB {
-                        a: val.ch(0),
-                        b: val.ch(e),
-                    }
-                } else {
-                    let i = d[0].0;
-                    if i == 0 {
-                        ArB {
-                            la: val.ch(i + 1),
-                            ra: val.ch(e),
-                            b: val.ch(0),
-                        }
-                    } else if i + 1 != e {
-                        ArBr {
-                            la: val.ch(0),
-                            ra: val.ch(i),
-                            lb: val.ch(i + 1),
-                            rb: val.ch(e),
-                        }
-                    } else {
-                        ArB {
-                            la: val.ch(0),
-                            ra: val.ch(i),
-                            b: val.ch(i + 1),
-                        }
-                    }
-                }
-            }
-            _ => {
-                if e <= 2 {
-                    assert_eq!(e, 2);
-                    return ABC {
-                        a: val.ch(0),
-                        b: val.ch(1),
-                        c: val.ch(2),
-                    };
-                }
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}: {} vector operations, {} false positives, score {}",
+            self.switch, self.ops, self.false_positives, self.score
+        )
+    }
+}
 
-                let (d, _) = d.split_at_mut(2);
-                d.sort_unstable_by_key(|d| d.0);
-
-                let f = d[0].0;
-                let l = d[1].0;
-
-                if f + 1 == l {
-                    if f == 0 {
-                        val.push_1_ranges_2_equals_at_first(f, l, e)
-                    } else if l + 1 == e {
-                        val.push_1_ranges_2_equals_at_last(f, l, e)
-                    } else {
-                        val.push_2_ranges_1_equals(f, l, e)
-                    }
-                } else if f == 0 {
-                    if l + 1 == e {
-                        val.push_1_ranges_2_equals_at_first_last(f, l, e)
-                    } else {
-                        val.push_2_ranges_1_equals_at_first(f, l, e)
-                    }
-                } else if l + 1 == e {
-                    val.push_2_ranges_1_equals_at_last(f, l, e)
-                } else {
-                    val.push_3_ranges(f, l, e)
-                }
-            }
+/// The maskings chosen for a set of escaped bytes, one for each kind of
+/// backend.
+///
+/// The nibble tables need a byte shuffle, missing from SSE2, so CPUs without
+/// SSSE3 mask the bytes with ranges and equalities instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    /// The masking of the backends with a byte shuffle: AVX2, SSSE3, NEON and
+    /// wasm simd128.
+    pub shuffle: Layout,
+    /// The masking of SSE2 without SSSE3, the same as `shuffle` unless it uses
+    /// the nibble tables.
+    pub sse2: Layout,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.shuffle == self.sse2 {
+            write!(f, "AVX2, SSSE3, SSE2, NEON and simd128: {}", self.shuffle)
+        } else {
+            writeln!(f, "AVX2, SSSE3, NEON and simd128: {}", self.shuffle)?;
+            write!(f, "SSE2: {}", self.sse2)
         }
     }
 }
 
+pub(crate) struct Pairs<'a>(pub &'a [Pair]);
+impl<'a> From<Pairs<'a>> for Switch {
+    fn from(val: Pairs<'a>) -> Self {
+        Plan::from(val).shuffle.switch
+    }
+}
+
+impl<'a> From<Pairs<'a>> for Plan {
+    /// Picks the cheapest of [`Pairs::ranges`] and the nibble tables for the
+    /// backends with a shuffle, and the ranges for SSE2. Ties go to the fewest
+    /// false positives, then to the ranges.
+    fn from(val: Pairs<'a>) -> Self {
+        let sse2 = val.ranges();
+        let shuffle = val
+            .nibbles()
+            .map(|(low, high)| Layout::new(Switch::Nibble { low, high }, NIBBLE_COST, 0))
+            .filter(|nibble| {
+                (nibble.score, nibble.false_positives) < (sse2.score, sse2.false_positives)
+            })
+            .unwrap_or_else(|| sse2.clone());
+
+        Plan { shuffle, sse2 }
+    }
+}
+
 impl<'a> Index<usize> for Pairs<'a> {
     type Output = Pair;
 
@@ -127,82 +162,109 @@ impl<'a> Pairs<'a> {
     }
 
     #[inline]
-    fn ch(&self, i: usize) -> i8 {
-        self[i].ch as i8
-    }
-    #[inline]
-    fn push_1_ranges_2_equals_at_first(&self, f: usize, l: usize, e: usize) -> Switch {
-        Switch::ArBC {
-            la: self.ch(l + 1),
-            ra: self.ch(e),
-            b: self.ch(0),
-            c: self.ch(f + 1),
-        }
+    fn ch(&self, i: usize) -> u8 {
+        self[i].ch
     }
 
-    #[inline]
-    fn push_1_ranges_2_equals_at_last(&self, f: usize, l: usize, e: usize) -> Switch {
-        Switch::ArBC {
-            la: self.ch(0),
-            ra: self.ch(f),
-            b: self.ch(l),
-            c: self.ch(e),
+    /// Scores every layout of up to three ranges and equalities covering the
+    /// bytes in order and returns the cheapest one, with the fewest false
+    /// positives on ties.
+    fn ranges(&self) -> Layout {
+        assert_ne!(self.len(), 0);
+
+        let n = self.len();
+        let mut layouts = vec![];
+        for i in 1..=n {
+            for j in i..=n {
+                // The groups `..i`, `i..j` and `j..`, the empty ones skipped
+                let groups = [(0, i), (i, j), (j, n)];
+                layouts.push(self.layout(groups.iter().filter(|(l, r)| l < r)));
+            }
         }
-    }
 
-    #[inline]
-    fn push_1_ranges_2_equals_at_first_last(&self, f: usize, l: usize, e: usize) -> Switch {
-        Switch::ArBC {
-            la: self.ch(f + 1),
-            ra: self.ch(l),
-            b: self.ch(0),
-            c: self.ch(e),
-        }
+        layouts
+            .into_iter()
+            .min_by_key(|layout| (layout.score, layout.false_positives))
+            .expect("one layout at least")
     }
 
-    #[inline]
-    fn push_2_ranges_1_equals(&self, f: usize, l: usize, e: usize) -> Switch {
-        Switch::ArBrC {
-            la: self.ch(0),
-            ra: self.ch(f),
-            lb: self.ch(l + 1),
-            rb: self.ch(e),
-            c: self.ch(f + 1),
-        }
-    }
+    /// Returns the layout masking each group of consecutive bytes, given by its
+    /// bounds, with an equality or with a range over-matching its gaps.
+    fn layout<'b>(&self, groups: impl Iterator<Item = &'b (usize, usize)>) -> Layout {
+        use Switch::*;
 
-    #[inline]
-    fn push_2_ranges_1_equals_at_first(&self, f: usize, l: usize, e: usize) -> Switch {
-        Switch::ArBrC {
-            la: self.ch(f + 1),
-            ra: self.ch(l),
-            lb: self.ch(l + 1),
-            rb: self.ch(e),
-            c: self.ch(0),
+        let mut ranges = vec![];
+        let mut equals = vec![];
+        let mut false_positives = 0;
+        let mut ops = 0;
+        for &(l, r) in groups {
+            let (la, ra) = (self.ch(l), self.ch(r - 1));
+            if la == ra {
+                equals.push(la);
+                ops += OP_COST;
+            } else {
+                ranges.push((la, ra));
+                false_positives += usize::from(ra - la) + 1 - (r - l);
+                ops += RANGE_COST;
+            }
         }
+        // The masks of the groups are joined with a bitwise OR
+        ops += (ranges.len() + equals.len() - 1) * OP_COST;
+
+        let switch = match (&ranges[..], &equals[..]) {
+            ([], &[a]) => A { a },
+            (&[(la, ra)], []) => Ar { la, ra },
+            ([], &[a, b]) => AB { a, b },
+            (&[(la, ra)], &[b]) => ArB { la, ra, b },
+            (&[(la, ra), (lb, rb)], []) => ArBr { la, ra, lb, rb },
+            ([], &[a, b, c]) => ABC { a, b, c },
+            (&[(la, ra)], &[b, c]) => ArBC { la, ra, b, c },
+            (&[(la, ra), (lb, rb)], &[c]) => ArBrC { la, ra, lb, rb, c },
+            (&[(la, ra), (lb, rb), (lc, rc)], []) => ArBrCr {
+                la,
+                ra,
+                lb,
+                rb,
+                lc,
+                rc,
+            },
+            _ => unreachable!("up to three groups"),
+        };
+        Layout::new(switch, ops, false_positives)
     }
 
-    #[inline]
-    fn push_2_ranges_1_equals_at_last(&self, f: usize, l: usize, e: usize) -> Switch {
-        Switch::ArBrC {
-            la: self.ch(0),
-            ra: self.ch(f),
-            lb: self.ch(f + 1),
-            rb: self.ch(l),
-            c: self.ch(e),
+    /// Returns the tables of [`Switch::Nibble`], or `None` if the high nibbles
+    /// have more than 8 different sets of low nibbles.
+    ///
+    /// Each set gets a bit, set in the entry of its high nibbles in `high` and
+    /// in the entries of its low nibbles in `low`, so a byte is escaped when
+    /// the entries of its nibbles share a bit.
+    fn nibbles(&self) -> Option<([u8; 16], [u8; 16])> {
+        let mut columns = [0u16; 16];
+        for p in self.0 {
+            columns[(p.ch >> 4) as usize] |= 1 << (p.ch & 0x0F);
+        }
+        let mut sets: Vec<u16> = columns.iter().copied().filter(|&c| c != 0).collect();
+        sets.sort_unstable();
+        sets.dedup();
+        if sets.len() > 8 {
+            return None;
         }
-    }
 
-    #[inline]
-    fn push_3_ranges(&self, f: usize, l: usize, e: usize) -> Switch {
-        Switch::ArBrCr {
-            la: self.ch(0),
-            ra: self.ch(f),
-            lb: self.ch(f + 1),
-            rb: self.ch(l),
-            lc: self.ch(l + 1),
-            rc: self.ch(e),
+        let mut low = [0; 16];
+        let mut high = [0; 16];
+        for (hi, column) in columns.into_iter().enumerate() {
+            let Some(bit) = sets.iter().position(|&set| set == column) else {
+                continue;
+            };
+            high[hi] = 1 << bit;
+            for (lo, low) in low.iter_mut().enumerate() {
+                if column & (1 << lo) != 0 {
+                    *low |= 1 << bit;
+                }
+            }
         }
+        Some((low, high))
     }
 }
 
@@ -216,6 +278,15 @@ mod test {
         ($($l:literal),*) => { &[$(Pair::new($l, E)),*] };
     }
 
+    /// Asserts that `switch` shuffles the nibble tables, matching exactly the
+    /// bytes of `pairs`.
+    fn assert_nibble(switch: &Switch, pairs: &[Pair]) {
+        assert!(matches!(switch, Nibble { .. }), "{switch:?}");
+        for c in 0..=u8::MAX {
+            assert_eq!(switch.contains(c), pairs.iter().any(|p| p.ch == c), "{c}");
+        }
+    }
+
     #[test]
     fn test_1_escape() {
         let pairs = pair!(0);
@@ -284,17 +355,92 @@ mod test {
         let pairs = pair!(0, 1, 3, 5, 7, 9, 50, 52, 55, 60, 61, 62, 63, 64, 126, 127);
         let switch: Switch = Pairs(pairs).into();
 
+        assert_nibble(&switch, pairs);
+    }
+
+    #[test]
+    fn test_nibble() {
+        // Over-matched by the ranges: the HTML bytes and scattered ones above 0x7F
+        let sets: [&[u8]; 3] = [
+            b"\"&'/<>",
+            &[0, 1, 3, 5, 7, 9, 50, 52, 55, 60, 61, 62, 63, 64, 126, 127],
+            &[0x0A, 0x5C, 0x80, 0x9F, 0xC0, 0xFF],
+        ];
+        for set in sets {
+            let pairs: Vec<_> = set.iter().map(|&c| Pair::new(c, E)).collect();
+            let switch: Switch = Pairs(&pairs).into();
+
+            assert_nibble(&switch, &pairs);
+            let masking: switch::Masking = switch.into();
+            assert!(masking.shuffle);
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let html: Vec<_> = b"\"&'/<>".iter().map(|&c| Pair::new(c, E)).collect();
+        let plan = Plan::from(Pairs(&html));
+        let Plan { shuffle, sse2 } = &plan;
+        assert!(matches!(shuffle.switch, Nibble { .. }), "{plan}");
         assert_eq!(
-            switch,
-            ArBrCr {
+            (shuffle.ops, shuffle.false_positives, shuffle.score),
+            (8, 0, 8)
+        );
+        // SSE2 can not shuffle, the ranges over-match `#`, `$`, `%` and `=`
+        assert!(!matches!(sse2.switch, Nibble { .. }), "{plan}");
+        assert!(html.iter().all(|p| sse2.switch.contains(p.ch)));
+        assert_eq!((sse2.ops, sse2.false_positives, sse2.score), (7, 4, 19));
+        assert!(plan.to_string().contains("\nSSE2: "), "{plan}");
+
+        let json: Vec<_> = (0..0x20)
+            .chain([b'"', b'\\'])
+            .map(|c| Pair::new(c, E))
+            .collect();
+        let plan = Plan::from(Pairs(&json));
+        assert_eq!(
+            plan.shuffle.switch,
+            ArBC {
                 la: 0,
-                ra: 9,
-                lb: 50,
-                rb: 64,
-                lc: 126,
-                rc: 127
+                ra: 31,
+                b: 34,
+                c: 92
             }
-        )
+        );
+        assert_eq!(
+            (
+                plan.shuffle.ops,
+                plan.shuffle.false_positives,
+                plan.shuffle.score
+            ),
+            (6, 0, 6)
+        );
+        assert_eq!(plan.sse2, plan.shuffle);
+        assert!(
+            plan.to_string()
+                .starts_with("AVX2, SSSE3, SSE2, NEON and simd128: ArBC"),
+            "{plan}"
+        );
+
+        // Neither three groups nor the nibble tables fit, the gaps are over-matched
+        let scattered: Vec<_> = (0..0x90)
+            .filter(|c| c >> 4 != (c & 0x0F) + 1)
+            .map(|c| Pair::new(c, E))
+            .collect();
+        let plan = Plan::from(Pairs(&scattered)).shuffle;
+        assert!(!matches!(plan.switch, Nibble { .. }), "{plan}");
+        assert!(scattered.iter().all(|p| plan.switch.contains(p.ch)));
+        let masked = (0..=u8::MAX).filter(|&c| plan.switch.contains(c)).count();
+        assert_eq!(plan.false_positives, masked - scattered.len());
+        assert_eq!(plan.score, plan.ops + 3 * plan.false_positives);
+    }
+
+    #[test]
+    fn test_nibble_overflow() {
+        // Nine high nibbles with different low nibbles do not fit in the 8 bits
+        let pairs = pair!(0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88);
+        let switch: Switch = Pairs(pairs).into();
+
+        assert!(!matches!(switch, Nibble { .. }), "{switch:?}");
     }
 
     #[test]
@@ -380,19 +526,6 @@ mod test {
 
     #[test]
     fn test_2_range_1_escape_d() {
-        let pairs = pair!(14, 15, 16, 50, 51, 52, 98);
-        let switch: Switch = Pairs(pairs).into();
-
-        assert_eq!(
-            switch,
-            ArBrC {
-                la: 14,
-                ra: 16,
-                lb: 50,
-                rb: 52,
-                c: 98
-            }
-        );
         let pairs = pair!(
             14, 15, 16, 17, 18, 19, 50, 51, 52, 53, 54, 55, 56, 57, 58, 98
         );
@@ -415,30 +548,12 @@ mod test {
         let pairs = pair!(14, 16, 50, 51, 52, 98);
         let switch: Switch = Pairs(pairs).into();
 
-        assert_eq!(
-            switch,
-            ArBrC {
-                la: 14,
-                ra: 16,
-                lb: 50,
-                rb: 52,
-                c: 98
-            }
-        );
+        assert_nibble(&switch, pairs);
 
         let pairs = pair!(14, 16, 17, 18, 19, 50, 52, 53, 56, 57, 58, 98);
         let switch: Switch = Pairs(pairs).into();
 
-        assert_eq!(
-            switch,
-            ArBrC {
-                la: 14,
-                ra: 19,
-                lb: 50,
-                rb: 58,
-                c: 98
-            }
-        );
+        assert_nibble(&switch, pairs);
     }
 
     #[test]
@@ -462,16 +577,7 @@ mod test {
         );
         let switch: Switch = Pairs(pairs).into();
 
-        assert_eq!(
-            switch,
-            ArBrC {
-                la: 52,
-                ra: 62,
-                lb: 101,
-                rb: 120,
-                c: 80
-            }
-        );
+        assert_nibble(&switch, pairs);
     }
 
     #[test]
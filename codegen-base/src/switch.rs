@@ -1,57 +1,82 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, PartialEq)]
+/// The layout of the masking of the escaped bytes.
+///
+/// The variants are named after their parts: `A`, `B` and `C` are equalities
+/// with a byte, and `Ar`, `Br` and `Cr` ranges from `l*` to `r*` inclusive.
+#[allow(clippy::upper_case_acronyms, missing_docs)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Switch {
-    A {
-        a: i8,
-    },
-    Ar {
-        la: i8,
-        ra: i8,
-    },
-    AB {
-        a: i8,
-        b: i8,
-    },
-    ArB {
-        la: i8,
-        ra: i8,
-        b: i8,
-    },
-    ArBr {
-        la: i8,
-        ra: i8,
-        lb: i8,
-        rb: i8,
-    },
-    ABC {
-        a: i8,
-        b: i8,
-        c: i8,
-    },
-    ArBC {
-        la: i8,
-        ra: i8,
-        b: i8,
-        c: i8,
-    },
+    /// One byte
+    A { a: u8 },
+    /// One range
+    Ar { la: u8, ra: u8 },
+    /// Two bytes
+    AB { a: u8, b: u8 },
+    /// One range and one byte
+    ArB { la: u8, ra: u8, b: u8 },
+    /// Two ranges
+    ArBr { la: u8, ra: u8, lb: u8, rb: u8 },
+    /// Three bytes
+    ABC { a: u8, b: u8, c: u8 },
+    /// One range and two bytes
+    ArBC { la: u8, ra: u8, b: u8, c: u8 },
+    /// Two ranges and one byte
     ArBrC {
-        la: i8,
-        ra: i8,
-        lb: i8,
-        rb: i8,
-        c: i8,
+        la: u8,
+        ra: u8,
+        lb: u8,
+        rb: u8,
+        c: u8,
     },
+    /// Three ranges
     ArBrCr {
-        la: i8,
-        ra: i8,
-        lb: i8,
-        rb: i8,
-        lc: i8,
-        rc: i8,
+        la: u8,
+        ra: u8,
+        lb: u8,
+        rb: u8,
+        lc: u8,
+        rc: u8,
     },
+    /// Any set of bytes whose high nibbles have up to 8 different sets of low
+    /// nibbles, looked up by shuffling two tables.
+    ///
+    /// `low` has, for each low nibble, the bits of the high nibbles it is
+    /// escaped with, and `high` the bit of each high nibble.
+    Nibble { low: [u8; 16], high: [u8; 16] },
+}
+
+impl Switch {
+    /// Returns `true` if the masking of this switch matches `c`.
+    pub fn contains(&self, c: u8) -> bool {
+        use Switch::*;
+        match *self {
+            A { a } => c == a,
+            Ar { la, ra } => (la..=ra).contains(&c),
+            AB { a, b } => c == a || c == b,
+            ArB { la, ra, b } => (la..=ra).contains(&c) || c == b,
+            ArBr { la, ra, lb, rb } => (la..=ra).contains(&c) || (lb..=rb).contains(&c),
+            ABC { a, b, c: cc } => c == a || c == b || c == cc,
+            ArBC { la, ra, b, c: cc } => (la..=ra).contains(&c) || c == b || c == cc,
+            ArBrC {
+                la,
+                ra,
+                lb,
+                rb,
+                c: cc,
+            } => (la..=ra).contains(&c) || (lb..=rb).contains(&c) || c == cc,
+            ArBrCr {
+                la,
+                ra,
+                lb,
+                rb,
+                lc,
+                rc,
+            } => (la..=ra).contains(&c) || (lb..=rb).contains(&c) || (lc..=rc).contains(&c),
+            Nibble { low, high } => low[(c & 0x0F) as usize] & high[(c >> 4) as usize] != 0,
+        }
+    }
 }
 
 enum Bodies {
@@ -74,33 +99,40 @@ pub(crate) struct Masking {
     pub struct_body: TokenStream,
     pub build: TokenStream,
     pub mask_body: TokenStream,
-    pub false_positive: bool,
+    pub shuffle: bool,
 }
 
 impl Masking {
-    fn new(
-        struct_body: TokenStream,
-        build: TokenStream,
-        mask_body: TokenStream,
-        false_positive: bool,
-    ) -> Self {
+    fn new(struct_body: TokenStream, build: TokenStream, mask_body: TokenStream) -> Self {
         Self {
             struct_body,
             build,
             mask_body,
-            false_positive,
+            shuffle: false,
         }
     }
 }
 
+/// Returns the translation and the bound masking the range `la..=ra` with a
+/// single signed comparison.
+///
+/// The translation moves `ra` to `i8::MAX`, so with wrapping arithmetic only
+/// the bytes of the range land above the bound, whatever side of `0x80` they
+/// are on. Ranges can cover up to 255 bytes.
+fn range(la: u8, ra: u8) -> (u8, u8) {
+    debug_assert!(la <= ra && ra - la < u8::MAX);
+    let translation = (i8::MAX as u8).wrapping_sub(ra);
+    let below = (i8::MAX as u8).wrapping_sub(ra - la + 1);
+    (translation, below)
+}
+
 impl From<Switch> for Masking {
     fn from(switch: Switch) -> Self {
         use Switch::*;
 
         match switch {
             ArBC { la, ra, b, c } => {
-                let translation_a: i8 = i8::MAX - ra;
-                let below_a: i8 = i8::MAX - (ra - la) - 1;
+                let (translation_a, below_a) = range(la, ra);
                 Masking::new(
                     quote! {{
                         translation_a: V,
@@ -110,10 +142,10 @@ impl From<Switch> for Masking {
                     }},
                     quote! {
                         Self::Escapes {
-                            translation_a: V::splat(#translation_a as u8),
-                            below_a: V::splat(#below_a as u8),
-                            b: V::splat(#b as u8),
-                            c: V::splat(#c as u8),
+                            translation_a: V::splat(#translation_a),
+                            below_a: V::splat(#below_a),
+                            b: V::splat(#b),
+                            c: V::splat(#c),
                         }
                     },
                     quote! {
@@ -121,7 +153,6 @@ impl From<Switch> for Masking {
                             .or(vector2.cmpeq(self.b))
                             .or(vector2.cmpeq(self.c))
                     },
-                    false,
                 )
             }
             ABC { a, b, c } => Masking::new(
@@ -132,9 +163,9 @@ impl From<Switch> for Masking {
                 }},
                 quote! {
                     Self::Escapes {
-                        a: V::splat(#a as u8),
-                        b: V::splat(#b as u8),
-                        c: V::splat(#c as u8),
+                        a: V::splat(#a),
+                        b: V::splat(#b),
+                        c: V::splat(#c),
                     }
                 },
                 quote! {
@@ -142,7 +173,6 @@ impl From<Switch> for Masking {
                             .or(vector2.cmpeq(self.b))
                             .or(vector2.cmpeq(self.c))
                 },
-                false,
             ),
             AB { a, b } => Masking::new(
                 quote! {{
@@ -151,15 +181,14 @@ impl From<Switch> for Masking {
                 }},
                 quote! {
                     Self::Escapes {
-                        a: V::splat(#a as u8),
-                        b: V::splat(#b as u8),
+                        a: V::splat(#a),
+                        b: V::splat(#b),
                     }
                 },
                 quote! {
                         vector2.cmpeq(self.a)
                             .or(vector2.cmpeq(self.b))
                 },
-                false,
             ),
             A { a } => Masking::new(
                 quote! {{
@@ -167,13 +196,12 @@ impl From<Switch> for Masking {
                 }},
                 quote! {
                     Self::Escapes {
-                        a: V::splat(#a as u8),
+                        a: V::splat(#a),
                     }
                 },
                 quote! {
                     vector2.cmpeq(self.a)
                 },
-                false,
             ),
             ArBrCr {
                 la,
@@ -183,12 +211,9 @@ impl From<Switch> for Masking {
                 lc,
                 rc,
             } => {
-                let translation_a: i8 = i8::MAX - ra;
-                let below_a: i8 = i8::MAX - (ra - la) - 1;
-                let translation_b: i8 = i8::MAX - rb;
-                let below_b: i8 = i8::MAX - (rb - lb) - 1;
-                let translation_c: i8 = i8::MAX - rc;
-                let below_c: i8 = i8::MAX - (rc - lc) - 1;
+                let (translation_a, below_a) = range(la, ra);
+                let (translation_b, below_b) = range(lb, rb);
+                let (translation_c, below_c) = range(lc, rc);
                 Masking::new(
                     quote! {{
                         translation_a: V,
@@ -200,12 +225,12 @@ impl From<Switch> for Masking {
                     }},
                     quote! {
                         Self::Escapes {
-                            translation_a: V::splat(#translation_a as u8),
-                            below_a: V::splat(#below_a as u8),
-                            translation_b: V::splat(#translation_b as u8),
-                            below_b: V::splat(#below_b as u8),
-                            translation_c: V::splat(#translation_c as u8),
-                            below_c: V::splat(#below_c as u8),
+                            translation_a: V::splat(#translation_a),
+                            below_a: V::splat(#below_a),
+                            translation_b: V::splat(#translation_b),
+                            below_b: V::splat(#below_b),
+                            translation_c: V::splat(#translation_c),
+                            below_c: V::splat(#below_c),
                         }
                     },
                     quote! {
@@ -213,14 +238,11 @@ impl From<Switch> for Masking {
                             .or(vector2.add(self.translation_b).gt(self.below_b))
                             .or(vector2.add(self.translation_c).gt(self.below_c))
                     },
-                    true,
                 )
             }
             ArBrC { la, ra, lb, rb, c } => {
-                let translation_a: i8 = i8::MAX - ra;
-                let below_a: i8 = i8::MAX - (ra - la) - 1;
-                let translation_b: i8 = i8::MAX - rb;
-                let below_b: i8 = i8::MAX - (rb - lb) - 1;
+                let (translation_a, below_a) = range(la, ra);
+                let (translation_b, below_b) = range(lb, rb);
                 Masking::new(
                     quote! {{
                         translation_a: V,
@@ -231,11 +253,11 @@ impl From<Switch> for Masking {
                     }},
                     quote! {
                         Self::Escapes {
-                            translation_a: V::splat(#translation_a as u8),
-                            below_a: V::splat(#below_a as u8),
-                            translation_b: V::splat(#translation_b as u8),
-                            below_b: V::splat(#below_b as u8),
-                            c: V::splat(#c as u8),
+                            translation_a: V::splat(#translation_a),
+                            below_a: V::splat(#below_a),
+                            translation_b: V::splat(#translation_b),
+                            below_b: V::splat(#below_b),
+                            c: V::splat(#c),
                         }
                     },
                     quote! {
@@ -243,14 +265,11 @@ impl From<Switch> for Masking {
                             .or(vector2.add(self.translation_b).gt(self.below_b))
                             .or(vector2.cmpeq(self.c))
                     },
-                    true,
                 )
             }
             ArBr { la, ra, lb, rb } => {
-                let translation_a: i8 = i8::MAX - ra;
-                let below_a: i8 = i8::MAX - (ra - la) - 1;
-                let translation_b: i8 = i8::MAX - rb;
-                let below_b: i8 = i8::MAX - (rb - lb) - 1;
+                let (translation_a, below_a) = range(la, ra);
+                let (translation_b, below_b) = range(lb, rb);
                 Masking::new(
                     quote! {{
                         translation_a: V,
@@ -260,22 +279,20 @@ impl From<Switch> for Masking {
                     }},
                     quote! {
                         Self::Escapes {
-                            translation_a: V::splat(#translation_a as u8),
-                            below_a: V::splat(#below_a as u8),
-                            translation_b: V::splat(#translation_b as u8),
-                            below_b: V::splat(#below_b as u8),
+                            translation_a: V::splat(#translation_a),
+                            below_a: V::splat(#below_a),
+                            translation_b: V::splat(#translation_b),
+                            below_b: V::splat(#below_b),
                         }
                     },
                     quote! {
                         vector2.add(self.translation_a).gt(self.below_a)
                             .or(vector2.add(self.translation_b).gt(self.below_b))
                     },
-                    false,
                 )
             }
             ArB { la, ra, b } => {
-                let translation_a: i8 = i8::MAX - ra;
-                let below_a: i8 = i8::MAX - (ra - la) - 1;
+                let (translation_a, below_a) = range(la, ra);
                 Masking::new(
                     quote! {{
                         translation_a: V,
@@ -284,21 +301,19 @@ impl From<Switch> for Masking {
                     }},
                     quote! {
                         Self::Escapes {
-                            translation_a: V::splat(#translation_a as u8),
-                            below_a: V::splat(#below_a as u8),
-                            b: V::splat(#b as u8),
+                            translation_a: V::splat(#translation_a),
+                            below_a: V::splat(#below_a),
+                            b: V::splat(#b),
                         }
                     },
                     quote! {
                         vector2.add(self.translation_a).gt(self.below_a)
                             .or(vector2.cmpeq(self.b))
                     },
-                    false,
                 )
             }
             Ar { la, ra } => {
-                let translation_a: i8 = i8::MAX - ra;
-                let below_a: i8 = i8::MAX - (ra - la) - 1;
+                let (translation_a, below_a) = range(la, ra);
                 Masking::new(
                     quote! {{
                         translation_a: V,
@@ -306,16 +321,66 @@ impl From<Switch> for Masking {
                     }},
                     quote! {
                         Self::Escapes {
-                            translation_a: V::splat(#translation_a as u8),
-                            below_a: V::splat(#below_a as u8),
+                            translation_a: V::splat(#translation_a),
+                            below_a: V::splat(#below_a),
                         }
                     },
                     quote! {
                         vector2.add(self.translation_a).gt(self.below_a)
                     },
-                    false,
                 )
             }
+            Nibble { low, high } => Masking {
+                shuffle: true,
+                ..Masking::new(
+                    quote! {{
+                        low: V,
+                        high: V,
+                        nibble: V,
+                        zero: V
+                    }},
+                    quote! {
+                        Self::Escapes {
+                            low: V::from_table(&[#(#low),*]),
+                            high: V::from_table(&[#(#high),*]),
+                            nibble: V::splat(0x0F),
+                            zero: V::splat(0),
+                        }
+                    },
+                    // The lanes of the escaped bytes are the non-zero ones
+                    quote! {
+                        self.low.shuffle(vector2.and(self.nibble))
+                            .and(self.high.shuffle(vector2.high_nibble()))
+                            .cmpeq(self.zero)
+                            .cmpeq(self.zero)
+                    },
+                )
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let ranges = [
+            (0, 0),
+            (0, 31),
+            (32, 126),
+            (127, 127),
+            (100, 200),
+            (128, 255),
+            (0, 254),
+        ];
+        for (la, ra) in ranges {
+            let (translation, below) = range(la, ra);
+            for b in 0..=u8::MAX {
+                let masked = b.wrapping_add(translation) as i8 > below as i8;
+                assert_eq!(masked, (la..=ra).contains(&b), "{b} in {la}..={ra}");
+            }
         }
     }
 }
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::quote;
+use syn::{LitByte, LitByteStr};
+
+use crate::pairs::{Pair, Sequence};
+
+/// A trie of the replacements, used to decode them back into the escaped bytes.
+///
+/// Every replacement ends at a leaf: tables where a replacement is a prefix of
+/// another one are rejected, since they can not be decoded unambiguously.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Trie {
+    /// The escaped character or sequence of the replacement ending at this node.
+    ch: Option<String>,
+    children: BTreeMap<u8, Trie>,
+}
+
+impl Trie {
+    pub fn new(pairs: &[Pair], sequences: &[Sequence]) -> syn::Result<Self> {
+        let error = |msg: String| syn::Error::new(Span::call_site(), msg);
+
+        if let Some(pair) = pairs.iter().find(|p| !p.ch.is_ascii()) {
+            return Err(error(format!(
+                "Byte 0x{:02X} can not be unescaped into a string",
+                pair.ch
+            )));
+        }
+        let mut all: Vec<(String, &str)> = pairs
+            .iter()
+            .map(|p| (char::from(p.ch).to_string(), p.quote.as_str()))
+            .chain(sequences.iter().map(|p| {
+                let ch = String::from_utf8(p.bytes.clone()).expect("sequences are valid UTF-8");
+                (ch, p.quote.as_str())
+            }))
+            .collect();
+
+        all.sort_by_key(|(_, quote)| *quote);
+        // In lexicographic order a prefix always comes right before one of its extensions
+        for w in all.windows(2) {
+            if w[1].1.starts_with(w[0].1) {
+                return Err(error(format!(
+                    "Ambiguous replacements: {:?} is a prefix of {:?}",
+                    w[0].1, w[1].1
+                )));
+            }
+        }
+
+        let mut trie = Trie::default();
+        for (ch, quote) in all {
+            match quote.as_bytes().first() {
+                None => return Err(error("Empty replacements can not be unescaped".into())),
+                Some(c) if !c.is_ascii() => {
+                    return Err(error(format!(
+                        "Replacement {quote:?} should start with an ASCII character"
+                    )));
+                }
+                Some(c) if pairs.iter().all(|p| p.ch != *c) => {
+                    return Err(error(format!(
+                        "Replacement {quote:?} should start with an escaped character"
+                    )));
+                }
+                Some(_) => (),
+            }
+            let node = quote
+                .bytes()
+                .fold(&mut trie, |node, b| node.children.entry(b).or_default());
+            node.ch = Some(ch);
+        }
+
+        Ok(trie)
+    }
+
+    /// Returns the first bytes of the replacements, in ascending order.
+    pub fn leads(&self) -> impl Iterator<Item = u8> + '_ {
+        self.children.keys().copied()
+    }
+
+    /// Returns the replacements and their escaped characters, in trie order.
+    pub fn replacements(&self) -> Vec<(Vec<u8>, &str)> {
+        fn walk<'a>(node: &'a Trie, path: &mut Vec<u8>, out: &mut Vec<(Vec<u8>, &'a str)>) {
+            if let Some(ch) = &node.ch {
+                out.push((path.clone(), ch));
+            }
+            for (b, child) in &node.children {
+                path.push(*b);
+                walk(child, path, out);
+                path.pop();
+            }
+        }
+
+        let mut out = Vec::new();
+        walk(self, &mut Vec::new(), &mut out);
+        out
+    }
+
+    /// Emits the match decoding the replacement at the start of `haystack`.
+    ///
+    /// Every replacement gets a slice pattern, so the compiler dispatches on
+    /// the first byte and walks the same trie.
+    pub fn decode(&self) -> TokenStream {
+        let span = Span::call_site();
+        let replacements = self.replacements();
+        let arms = replacements.iter().map(|(quote, ch)| {
+            let bytes = quote.iter().map(|b| LitByte::new(*b, span));
+            let len = Literal::usize_unsuffixed(quote.len());
+            quote!([#(#bytes,)* ..] => Ok((#len, Unescaped::Str(#ch))),)
+        });
+        let quotes = replacements
+            .iter()
+            .map(|(quote, _)| LitByteStr::new(quote, span));
+        quote! {
+            let replacements: &[&[u8]] = &[#(#quotes),*];
+            match haystack {
+                #(#arms)*
+                _ if replacements.iter().any(|q| q.starts_with(haystack)) => {
+                    Err(UnescapeError::new(0, UnescapeErrorKind::Incomplete))
+                }
+                _ => Err(UnescapeError::new(0, UnescapeErrorKind::Unknown)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    macro_rules! pair {
+        ($($l:literal -> $q:literal),*) => { &[$(Pair::new($l, $q)),*] };
+    }
+
+    fn err(pairs: &[Pair]) -> String {
+        Trie::new(pairs, &[]).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_trie() {
+        let trie = Trie::new(pair!(38 -> "&amp;", 60 -> "&lt;", 62 -> "&gt;"), &[]).unwrap();
+
+        assert_eq!(trie.leads().collect::<Vec<_>>(), b"&");
+        assert_eq!(
+            trie.replacements(),
+            [
+                (b"&amp;".to_vec(), "&"),
+                (b"&gt;".to_vec(), ">"),
+                (b"&lt;".to_vec(), "<")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trie_sequences() {
+        let sequences = &[
+            Sequence::new("\u{A0}", "&nbsp;"),
+            Sequence::new("]]>", "&rsqb;]>"),
+        ];
+        let trie = Trie::new(pair!(38 -> "&amp;"), sequences).unwrap();
+
+        assert_eq!(trie.leads().collect::<Vec<_>>(), b"&");
+        assert_eq!(
+            trie.replacements(),
+            [
+                (b"&amp;".to_vec(), "&"),
+                (b"&nbsp;".to_vec(), "\u{A0}"),
+                (b"&rsqb;]>".to_vec(), "]]>")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trie_leads() {
+        let trie = Trie::new(pair!(35 -> "\\#", 36 -> "$$", 92 -> "\\\\"), &[]).unwrap();
+
+        assert_eq!(trie.leads().collect::<Vec<_>>(), b"$\\");
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        assert_eq!(
+            err(pair!(38 -> "&amp;", 60 -> "&amp;lt;")),
+            "Ambiguous replacements: \"&amp;\" is a prefix of \"&amp;lt;\""
+        );
+        assert_eq!(
+            err(pair!(38 -> "&x", 60 -> "&b", 62 -> "&xy")),
+            "Ambiguous replacements: \"&x\" is a prefix of \"&xy\""
+        );
+        assert_eq!(
+            err(pair!(38 -> "&a", 60 -> "&a")),
+            "Ambiguous replacements: \"&a\" is a prefix of \"&a\""
+        );
+    }
+
+    #[test]
+    fn test_invalid_lead() {
+        assert_eq!(
+            err(pair!(38 -> "")),
+            "Empty replacements can not be unescaped"
+        );
+        assert_eq!(
+            err(pair!(38 -> "é")),
+            "Replacement \"é\" should start with an ASCII character"
+        );
+        assert_eq!(
+            err(pair!(38 -> "&amp;", 60 -> "[lt]")),
+            "Replacement \"[lt]\" should start with an escaped character"
+        );
+        assert_eq!(
+            err(pair!(38 -> "&amp;", 0xFF -> "&xff;")),
+            "Byte 0xFF can not be unescaped into a string"
+        );
+    }
+}
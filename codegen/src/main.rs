@@ -8,13 +8,14 @@ use std::{
 };
 
 use proc_macro2::{Ident, Span, TokenStream};
+use quote::ToTokens;
 use serde::Serialize;
 use tests::build_tests;
 use toml::Value;
 
 use clap::Parser;
 
-use v_escape_codegen_base::generate as generate_base;
+use v_escape_codegen_base::{Mapping, generate as generate_base, generate_unescape, plan};
 mod tests;
 
 fn ident(s: &str) -> Ident {
@@ -52,6 +53,9 @@ struct Args {
     /// Input directory containing the crate to generate
     #[clap(short, long, default_value = "./", value_name = "DIR")]
     pub input_dir: PathBuf,
+    /// Print the masking planned for each backend and its score, without generating
+    #[clap(long)]
+    pub plan: bool,
 }
 
 #[derive(Serialize)]
@@ -151,26 +155,61 @@ fn _generate(dir: &Path) -> anyhow::Result<()> {
     let template = src.join("_lib.rs");
     let template_src = fs::read_to_string(&template)?;
 
+    let (table, items) = split_template(&template_src)?;
+    // Templates declaring an `unescape` module provide their own unescaper
+    let derive_unescape = !items
+        .iter()
+        .any(|item| matches!(item, syn::Item::Mod(m) if m.ident == "unescape"));
+
     // Generate code
-    let (code, mappings) = generate_base(
-        template_src
-            .parse::<TokenStream>()
-            .map_err(|e| anyhow::anyhow!("Failed to parse template source: {}", e))?,
+    let escaper = escaper_name(&name);
+    let (mut code, mappings) = generate_base(
+        table.clone(),
         "v_escape_base",
+        Some(&format!("pub {escaper}")),
     )?;
+    code.extend(quote::quote!(
+        pub use v_escape_base::Escaper;
+    ));
+    if derive_unescape {
+        code.extend(generate_unescape(table, "v_escape_base")?);
+    }
 
-    // Prettify code
-    let code_pretty = prettyplease::unparse(
-        &syn::parse2(code)
-            .map_err(|e| anyhow::anyhow!("Failed to parse code to TokenStream: {}", e))?,
-    );
+    // Prettify code, keeping the hand-written items of the template after the generated ones
+    let mut file: syn::File = syn::parse2(code)
+        .map_err(|e| anyhow::anyhow!("Failed to parse code to TokenStream: {}", e))?;
+    file.items.extend(items);
+    let code_pretty = prettyplease::unparse(&file);
 
     // Build header and module-level documentation describing the generated module.
-    let escapes_bytes: Vec<u8> = mappings.iter().map(|(c, _)| *c).collect();
-    let escapes = String::from_utf8(escapes_bytes.clone())
+    // The escaped sequences of more than one character, the longest first
+    let mut sequences: Vec<&str> = mappings
+        .iter()
+        .filter_map(|(c, _)| std::str::from_utf8(c).ok())
+        .filter(|c| 1 < c.chars().count())
+        .collect();
+    sequences.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    // Adjacent sequences could match across each other, a byte none of them
+    // contains keeps them apart
+    let separator = if !sequences.is_empty() {
+        let b = (b'a'..=b'z')
+            .find(|b| mappings.iter().all(|(c, _)| !c.contains(b)))
+            .ok_or_else(|| anyhow::anyhow!("no separator for the escaped sequences"))?;
+        char::from(b).to_string()
+    } else {
+        String::new()
+    };
+    let escapes_bytes: Vec<u8> = mappings
+        .iter()
+        .flat_map(|(c, _)| c.iter().chain(separator.as_bytes()).copied())
+        .collect();
+    let escapes = String::from_utf8(escapes_bytes)
         .map_err(|e| anyhow::anyhow!("escape characters must be valid UTF-8: {}", e))?;
-    let escaped: String = mappings.iter().map(|(_, q)| q.as_str()).collect();
-    let module_doc = render_module_doc(&name, &mappings);
+    let escaped: String = mappings
+        .iter()
+        .map(|(_, q)| [q.as_str(), &separator].concat())
+        .collect();
+    let module_doc = render_module_doc(&name, &escaper, &mappings, derive_unescape);
     let head = format!(
         "//! autogenerated by {pkg}@{version}\n{module_doc}",
         pkg = env!("CARGO_PKG_NAME"),
@@ -178,7 +217,14 @@ fn _generate(dir: &Path) -> anyhow::Result<()> {
     );
 
     // Generate tests
-    let code_test = build_tests(&ident(&name), &escapes, &escaped);
+    let code_test = build_tests(
+        &ident(&name),
+        &ident(&escaper),
+        &escapes,
+        &escaped,
+        &sequences,
+        derive_unescape,
+    );
     let code_test_pretty = prettyplease::unparse(
         &syn::parse2(code_test)
             .map_err(|e| anyhow::anyhow!("Failed to parse code to TokenStream: {}", e))?,
@@ -200,12 +246,54 @@ fn _generate(dir: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Name the unit struct implementing `Escaper` after the crate, as
+/// `HtmlEscaper` for `v_htmlescape`.
+fn escaper_name(crate_name: &str) -> String {
+    let name = crate_name.strip_prefix("v_").unwrap_or(crate_name);
+    let name = name.strip_suffix("escape").unwrap_or(name);
+    let mut out: String = name
+        .split(['_', '-'])
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect();
+    out.push_str("Escaper");
+    out
+}
+
+/// Split a template into its `new!` invocation and the rest of its items.
+///
+/// Any item besides the escape table, such as `mod` declarations and
+/// re-exports of hand-written modules, is copied into the generated `lib.rs`.
+fn split_template(src: &str) -> anyhow::Result<(TokenStream, Vec<syn::Item>)> {
+    let template: syn::File = syn::parse_str(src)
+        .map_err(|e| anyhow::anyhow!("Failed to parse template source: {}", e))?;
+    let (tables, items): (Vec<_>, Vec<_>) = template
+        .items
+        .into_iter()
+        .partition(|item| matches!(item, syn::Item::Macro(m) if m.mac.path.is_ident("new")));
+    let [table] = <[syn::Item; 1]>::try_from(tables)
+        .map_err(|_| anyhow::anyhow!("Template should contain exactly one `new!` invocation"))?;
+
+    Ok((table.into_token_stream(), items))
+}
+
 /// Render an inner-doc (`//!`) block describing the generated escape module.
 ///
 /// The output is meant to be prepended to the `lib.rs` file produced by codegen so
 /// that `cargo doc` / docs.rs renders an explanation of what the crate does and
 /// which characters get rewritten.
-fn render_module_doc(crate_name: &str, mappings: &[(u8, String)]) -> String {
+fn render_module_doc(
+    crate_name: &str,
+    escaper: &str,
+    mappings: &[Mapping],
+    unescape: bool,
+) -> String {
     let mut out = String::new();
     out.push_str("//!\n");
     out.push_str(&format!("//! # `{crate_name}`\n"));
@@ -231,10 +319,11 @@ fn render_module_doc(crate_name: &str, mappings: &[(u8, String)]) -> String {
     out.push_str("//! | Byte (hex) | Source | Replacement |\n");
     out.push_str("//! | ---------- | ------ | ----------- |\n");
     for (b, q) in mappings {
+        let hex: Vec<String> = b.iter().map(|b| format!("0x{b:02X}")).collect();
         out.push_str(&format!(
-            "//! | `0x{:02X}` | {} | `{}` |\n",
-            b,
-            render_source_byte(*b),
+            "//! | `{}` | {} | `{}` |\n",
+            hex.join(" "),
+            render_source(b),
             escape_md_inline_code(q),
         ));
     }
@@ -243,7 +332,10 @@ fn render_module_doc(crate_name: &str, mappings: &[(u8, String)]) -> String {
     out.push_str("//!\n");
     out.push_str(
         "//! The following functions are emitted, gated by their respective Cargo\n\
-         //! features (all enabled by default):\n",
+         //! features (all enabled by default). `escape_slice`, `escape_iter`,\n\
+         //! `escaped_len`, `max_escaped_len`, `needs_escape` and `first_escape_position`\n\
+         //! never allocate and are always available. A `MAX_REPLACEMENT_LEN` constant\n\
+         //! holding the length of the longest replacement is emitted as well:\n",
     );
     out.push_str("//!\n");
     out.push_str(
@@ -251,9 +343,44 @@ fn render_module_doc(crate_name: &str, mappings: &[(u8, String)]) -> String {
          //! | -------- | ------- | --------- |\n\
          //! | `escape_string` | `string` | `fn(&str, &mut String)` |\n\
          //! | `escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |\n\
-         //! | `escape_fmt`    | `fmt`    | `fn(&str) -> impl Display + '_` |\n",
+         //! | `escape_string_reserved` | `string` | `fn(&str, &mut String)` |\n\
+         //! | `escape_bytes_reserved`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |\n\
+         //! | `try_escape_string` | `string` | `fn(&str, &mut String) -> Result<(), TryReserveError>` |\n\
+         //! | `try_escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>) -> Result<(), TryReserveError>` |\n\
+         //! | `escape_cow`    | `string` | `fn(&str) -> Cow<'_, str>` |\n\
+         //! | `escape_fmt`    | `fmt`    | `fn(&str) -> impl Display + '_` |\n\
+         //! | `escape_fmt_into` | `fmt` | `fn(&str, &mut impl fmt::Write) -> fmt::Result` |\n\
+         //! | `escape_slice`  | -        | `fn(&str, &mut [u8]) -> Result<usize, Overflow>` |\n\
+         //! | `escape_iter`   | -        | `fn(&str) -> impl Iterator<Item = &str>` |\n\
+         //! | `escaped_len`   | -        | `fn(&str) -> usize` |\n\
+         //! | `max_escaped_len` | -      | `const fn(usize) -> usize` |\n\
+         //! | `needs_escape`  | -        | `fn(&str) -> bool` |\n\
+         //! | `first_escape_position` | - | `fn(&str) -> Option<usize>` |\n\
+         //! | `escape_io`     | `std`    | `fn(&str, &mut impl io::Write) -> io::Result<()>` |\n\
+         //! | `escape_vectored` | `std`  | `fn(&str, &mut impl io::Write) -> io::Result<()>` |\n",
     );
     out.push_str("//!\n");
+    out.push_str(&format!(
+        "//! The unit struct [`{escaper}`] implements [`Escaper`] with these functions,\n\
+         //! so generic code can take it as a type parameter, as `fn render<E: Escaper>()`.\n\
+         //! It exposes the escape table as well, in `MAPPINGS` and `SEQUENCES`.\n",
+    ));
+    out.push_str("//!\n");
+    if unescape {
+        out.push_str(
+            "//! The inverse functions decode exactly the replacements of the table and\n\
+             //! fail with the byte offset of any other sequence starting like one:\n",
+        );
+        out.push_str("//!\n");
+        out.push_str(
+            "//! | Function | Feature | Signature |\n\
+             //! | -------- | ------- | --------- |\n\
+             //! | `unescape_string` | `string` | `fn(&str, &mut String) -> Result<(), UnescapeError>` |\n\
+             //! | `unescape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>) -> Result<(), UnescapeError>` |\n\
+             //! | `unescape_fmt`    | `fmt`    | `fn(&str) -> impl Display + '_` |\n",
+        );
+        out.push_str("//!\n");
+    }
     out.push_str(
         "//! At runtime the implementation dispatches to the best SIMD backend\n\
          //! available on the current CPU (AVX2/SSE2 on x86_64, NEON on aarch64,\n\
@@ -263,13 +390,20 @@ fn render_module_doc(crate_name: &str, mappings: &[(u8, String)]) -> String {
     out
 }
 
-/// Render the byte that triggers an escape in a doc-friendly form.
-fn render_source_byte(b: u8) -> String {
-    match b {
-        b'`' => "`` ` ``".to_string(),
-        b'|' => "`\\|`".to_string(),
-        0x20..=0x7E => format!("`{}`", b as char),
-        _ => format!("`<0x{:02X}>`", b),
+/// Render the byte or the character that triggers an escape in a doc-friendly form.
+fn render_source(source: &[u8]) -> String {
+    match *source {
+        [b'`'] => "`` ` ``".to_string(),
+        [b'|'] => "`\\|`".to_string(),
+        [b @ 0x20..=0x7E] => format!("`{}`", b as char),
+        [b] => format!("`<0x{:02X}>`", b),
+        _ => match std::str::from_utf8(source) {
+            Ok(s) if 1 < s.chars().count() => format!("`{}`", escape_md_inline_code(s)),
+            _ => String::from_utf8_lossy(source)
+                .chars()
+                .map(|c| format!("`U+{:04X}`", c as u32))
+                .collect(),
+        },
     }
 }
 
@@ -287,9 +421,59 @@ fn escape_md_inline_code(s: &str) -> String {
     out
 }
 
+/// Print the maskings planned for the template of the crate at `dir`.
+fn print_plan(dir: &Path) -> anyhow::Result<()> {
+    let template_src = fs::read_to_string(dir.join("src").join("_lib.rs"))?;
+    let (table, _) = split_template(&template_src)?;
+    println!("{}", plan(table)?);
+
+    Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
     let dir: PathBuf = args.input_dir;
 
-    generate(dir)
+    if args.plan {
+        print_plan(&dir)
+    } else {
+        generate(dir)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unescape_ambiguous() {
+        let dir = std::env::temp_dir().join(format!("v_escape_codegen-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"v_ambiguousescape\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let table = "new!('&' -> \"&amp;\", '<' -> \"&amp;lt;\");\n";
+
+        // The unescaper is derived from the table, which can not be decoded
+        fs::write(dir.join("src").join("_lib.rs"), table).unwrap();
+        let err = generate(&dir).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Ambiguous replacements: \"&amp;\" is a prefix of \"&amp;lt;\""
+        );
+        assert!(!dir.join("src").join("lib.rs").exists());
+
+        // A hand-written unescaper leaves the table alone
+        fs::write(
+            dir.join("src").join("_lib.rs"),
+            [table, "mod unescape;\n"].concat(),
+        )
+        .unwrap();
+        generate(&dir).unwrap();
+        assert!(dir.join("src").join("lib.rs").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -7,20 +7,14 @@ pub fn all_utf8_less() -> TokenStream {
         fn all_utf8_less(less: &str) -> String {
             use std::char::from_u32;
 
-            let less = less.as_bytes();
             let mut buf = String::with_capacity(204_672 - less.len());
 
-            for i in 0..0x80u8 {
-                if !less.contains(&i) {
-                    buf.push(from_u32(i as u32).unwrap())
+            for i in (0..0xD800).chain(0xE000..0x11000) {
+                let c = from_u32(i).unwrap();
+                if !less.contains(c) {
+                    buf.push(c)
                 }
             }
-            for i in 0x80..0xD800 {
-                buf.push(from_u32(i).unwrap());
-            }
-            for i in 0xE000..0x11000 {
-                buf.push(from_u32(i).unwrap());
-            }
 
             buf
         }
@@ -47,6 +41,108 @@ fn result_string() -> TokenStream {
     }
 }
 
+fn result_cow() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let cow = escape_cow(haystack);
+            assert_eq!(
+                matches!(cow, std::borrow::Cow::Borrowed(_)),
+                first_escape_position(haystack).is_none()
+            );
+            cow.into_owned()
+        }
+    }
+}
+
+fn result_escaper(escaper: &Ident) -> TokenStream {
+    quote! {
+        fn escape<E: Escaper>(haystack: &str) -> String {
+            let mut buf = String::new();
+            E::escape_string(haystack, &mut buf);
+            let mut bytes = Vec::new();
+            E::escape_bytes(haystack, &mut bytes);
+            assert_eq!(bytes, buf.as_bytes());
+            assert_eq!(E::escape_fmt(haystack).to_string(), buf);
+            assert_eq!(E::escaped_len(haystack), buf.len());
+            assert!(buf.len() <= E::max_escaped_len(haystack.len()));
+            assert_eq!(
+                E::needs_escape(haystack),
+                E::first_escape_position(haystack).is_some()
+            );
+            buf
+        }
+
+        fn result(haystack: &str) -> String {
+            escape::<#escaper>(haystack)
+        }
+
+        #[test]
+        fn mappings() {
+            for (c, quote) in #escaper::MAPPINGS {
+                assert_eq!(result(&char::from(*c).to_string()), *quote);
+            }
+            for (sequence, quote) in #escaper::SEQUENCES {
+                assert_eq!(result(std::str::from_utf8(sequence).unwrap()), *quote);
+            }
+            let len = #escaper::MAPPINGS.iter().map(|(_, quote)| quote.len());
+            let len = len.chain(#escaper::SEQUENCES.iter().map(|(_, quote)| quote.len()));
+            assert_eq!(len.max(), Some(#escaper::MAX_REPLACEMENT_LEN));
+        }
+    }
+}
+
+fn result_iter() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let spans = escape_iter(haystack).collect::<Vec<_>>();
+            assert!(spans.iter().all(|span| !span.is_empty()));
+            spans.concat()
+        }
+    }
+}
+
+fn result_string_reserved() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = String::new();
+            escape_string_reserved(haystack, &mut buf);
+            assert!(buf.len() <= max_escaped_len(haystack.len()));
+            buf
+        }
+    }
+}
+
+fn result_bytes_reserved() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = Vec::new();
+            escape_bytes_reserved(haystack, &mut buf);
+            assert!(buf.len() <= max_escaped_len(haystack.len()));
+            String::from_utf8(buf).unwrap()
+        }
+    }
+}
+
+fn result_try_string() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = String::new();
+            try_escape_string(haystack, &mut buf).unwrap();
+            buf
+        }
+    }
+}
+
+fn result_try_bytes() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = Vec::new();
+            try_escape_bytes(haystack, &mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        }
+    }
+}
+
 fn result_fmt() -> TokenStream {
     quote! {
         fn result(haystack: &str) -> String {
@@ -55,6 +151,102 @@ fn result_fmt() -> TokenStream {
     }
 }
 
+fn result_fmt_into() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = String::new();
+            escape_fmt_into(haystack, &mut buf).unwrap();
+            buf
+        }
+    }
+}
+
+fn result_slice() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = [0u8; 256];
+            let mut rest = haystack;
+            let mut out = Vec::new();
+            loop {
+                match escape_slice(rest, &mut buf) {
+                    Ok(written) => {
+                        out.extend_from_slice(&buf[..written]);
+                        break;
+                    }
+                    Err(err) => {
+                        out.extend_from_slice(&buf[..err.written]);
+                        rest = &rest[err.consumed..];
+                    }
+                }
+            }
+            String::from_utf8(out).unwrap()
+        }
+    }
+}
+
+fn result_len() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let len = escaped_len(haystack);
+            let mut buf = vec![0u8; len];
+            assert_eq!(escape_slice(haystack, &mut buf), Ok(len));
+            String::from_utf8(buf).unwrap()
+        }
+    }
+}
+
+fn result_position(sequences: &[&str]) -> TokenStream {
+    let char_len = quote!(haystack[i..].chars().next().unwrap().len_utf8());
+    // The escaped sequence at `i`, the longest first, or its character
+    let len = if sequences.is_empty() {
+        char_len
+    } else {
+        quote! {
+            [#(#sequences),*]
+                .into_iter()
+                .find(|s| haystack[i..].starts_with(s))
+                .map_or_else(|| #char_len, str::len)
+        }
+    };
+    quote! {
+        fn result(mut haystack: &str) -> String {
+            let mut out = String::new();
+            while let Some(i) = first_escape_position(haystack) {
+                assert!(needs_escape(haystack));
+                out.push_str(&haystack[..i]);
+                let next = i + #len;
+                let mut buf = vec![0u8; escaped_len(&haystack[i..next])];
+                escape_slice(&haystack[i..next], &mut buf).unwrap();
+                out.push_str(std::str::from_utf8(&buf).unwrap());
+                haystack = &haystack[next..];
+            }
+            assert!(!needs_escape(haystack));
+            out.push_str(haystack);
+            out
+        }
+    }
+}
+
+fn result_vectored() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = Vec::new();
+            escape_vectored(haystack, &mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        }
+    }
+}
+
+fn result_io() -> TokenStream {
+    quote! {
+        fn result(haystack: &str) -> String {
+            let mut buf = Vec::new();
+            escape_io(haystack, &mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        }
+    }
+}
+
 fn tests(escapes: &str, escaped: &str) -> TokenStream {
     quote! {
         #[test]
@@ -175,12 +367,124 @@ fn tests(escapes: &str, escaped: &str) -> TokenStream {
     }
 }
 
-pub fn build_tests(package: &Ident, escapes: &str, escaped: &str) -> TokenStream {
+fn round_trip_string() -> TokenStream {
+    quote! {
+        fn round_trip(haystack: &str) -> String {
+            let escaped: String = escape_iter(haystack).collect();
+            let mut buf = String::new();
+            unescape_string(&escaped, &mut buf).unwrap();
+            buf
+        }
+    }
+}
+
+fn round_trip_bytes() -> TokenStream {
+    quote! {
+        fn round_trip(haystack: &str) -> String {
+            let escaped: String = escape_iter(haystack).collect();
+            let mut buf = Vec::new();
+            unescape_bytes(&escaped, &mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        }
+    }
+}
+
+fn round_trip_fmt() -> TokenStream {
+    quote! {
+        fn round_trip(haystack: &str) -> String {
+            let escaped: String = escape_iter(haystack).collect();
+            unescape_fmt(&escaped).to_string()
+        }
+    }
+}
+
+fn round_trip_tests(escapes: &str) -> TokenStream {
+    quote! {
+        #[test]
+        fn round_trip_tests() {
+            let escapes = #escapes;
+            let utf8: &str = &all_utf8_less("");
+            let short = "foobar";
+
+            for haystack in [
+                "",
+                short,
+                escapes,
+                utf8,
+                &[short, escapes, short].join(""),
+                &[escapes, short].join(""),
+                &["f", escapes].join(""),
+                &escapes.repeat(1024),
+                &[short.repeat(1024), escapes.repeat(13)].concat().repeat(16),
+                &[utf8, escapes, utf8].join(""),
+            ] {
+                assert_eq!(round_trip(haystack), haystack);
+            }
+        }
+    }
+}
+
+fn build_round_trip_tests(package: &Ident, escapes: &str) -> TokenStream {
+    let tests = round_trip_tests(escapes);
+    let round_trip_string = round_trip_string();
+    let round_trip_bytes = round_trip_bytes();
+    let round_trip_fmt = round_trip_fmt();
+    quote! {
+        #[cfg(feature = "string")]
+        mod unescape_string {
+            use super::*;
+            use #package::{escape_iter, unescape_string};
+            #round_trip_string
+            #tests
+        }
+        #[cfg(feature = "bytes")]
+        mod unescape_bytes {
+            use super::*;
+            use #package::{escape_iter, unescape_bytes};
+            #round_trip_bytes
+            #tests
+        }
+        #[cfg(feature = "fmt")]
+        mod unescape_fmt {
+            use super::*;
+            use #package::{escape_iter, unescape_fmt};
+            #round_trip_fmt
+            #tests
+        }
+    }
+}
+
+pub fn build_tests(
+    package: &Ident,
+    escaper: &Ident,
+    escapes: &str,
+    escaped: &str,
+    sequences: &[&str],
+    unescape: bool,
+) -> TokenStream {
     let all_utf8_less = all_utf8_less();
     let tests = tests(escapes, escaped);
     let result_string = result_string();
     let result_fmt = result_fmt();
     let result_bytes = result_bytes();
+    let result_fmt_into = result_fmt_into();
+    let result_slice = result_slice();
+    let result_len = result_len();
+    let result_string_reserved = result_string_reserved();
+    let result_bytes_reserved = result_bytes_reserved();
+    let result_try_string = result_try_string();
+    let result_try_bytes = result_try_bytes();
+    let result_iter = result_iter();
+    let result_cow = result_cow();
+    let result_position = result_position(sequences);
+    let result_io = result_io();
+    let result_vectored = result_vectored();
+    let result_escaper = result_escaper(escaper);
+    let round_trip_tests = if unescape {
+        build_round_trip_tests(package, escapes)
+    } else {
+        TokenStream::new()
+    };
     quote! {
         #all_utf8_less
         #[cfg(feature = "string")]
@@ -197,6 +501,48 @@ pub fn build_tests(package: &Ident, escapes: &str, escaped: &str) -> TokenStream
             #result_fmt
             #tests
         }
+        #[cfg(feature = "string")]
+        mod string_reserved {
+            use super::*;
+            use #package::{escape_string_reserved, max_escaped_len};
+            #result_string_reserved
+            #tests
+        }
+        #[cfg(feature = "bytes")]
+        mod bytes_reserved {
+            use super::*;
+            use #package::{escape_bytes_reserved, max_escaped_len};
+            #result_bytes_reserved
+            #tests
+        }
+        #[cfg(feature = "string")]
+        mod try_string {
+            use super::*;
+            use #package::try_escape_string;
+            #result_try_string
+            #tests
+        }
+        #[cfg(feature = "bytes")]
+        mod try_bytes {
+            use super::*;
+            use #package::try_escape_bytes;
+            #result_try_bytes
+            #tests
+        }
+        #[cfg(feature = "string")]
+        mod cow {
+            use super::*;
+            use #package::{escape_cow, first_escape_position};
+            #result_cow
+            #tests
+        }
+        #[cfg(feature = "fmt")]
+        mod fmt_into {
+            use super::*;
+            use #package::escape_fmt_into;
+            #result_fmt_into
+            #tests
+        }
         #[cfg(feature = "bytes")]
         mod bytes {
             use super::*;
@@ -204,5 +550,51 @@ pub fn build_tests(package: &Ident, escapes: &str, escaped: &str) -> TokenStream
             #result_bytes
             #tests
         }
+        mod slice {
+            use super::*;
+            use #package::escape_slice;
+            #result_slice
+            #tests
+        }
+        mod len {
+            use super::*;
+            use #package::{escape_slice, escaped_len};
+            #result_len
+            #tests
+        }
+        mod iter {
+            use super::*;
+            use #package::escape_iter;
+            #result_iter
+            #tests
+        }
+        mod position {
+            use super::*;
+            use #package::{escape_slice, escaped_len, first_escape_position, needs_escape};
+            #result_position
+            #tests
+        }
+        #[cfg(feature = "std")]
+        mod io {
+            use super::*;
+            use #package::escape_io;
+            #result_io
+            #tests
+        }
+        #[cfg(feature = "std")]
+        mod vectored {
+            use super::*;
+            use #package::escape_vectored;
+            #result_vectored
+            #tests
+        }
+        #[cfg(all(feature = "string", feature = "bytes", feature = "fmt"))]
+        mod escaper {
+            use super::*;
+            use #package::{Escaper, #escaper};
+            #result_escaper
+            #tests
+        }
+        #round_trip_tests
     }
 }
@@ -7,9 +7,83 @@
 //! and `escape_fmt` functions that can be used for efficient string escaping.
 
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{ToTokens, quote};
+use syn::{
+    Ident, Path, Token, Visibility,
+    parse::{Parse, ParseStream},
+};
 use v_escape_codegen_base::generate;
 
+/// The input of `escape!`: the options followed by the table
+struct Input {
+    krate: Option<Path>,
+    name: Option<(Visibility, Ident)>,
+    escaper: Option<(Visibility, Ident)>,
+    table: TokenStream,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut krate = None;
+        let mut name = None;
+        let mut escaper = None;
+        // The table starts with literals, options with `key =`
+        loop {
+            if input.peek(Token![crate]) && input.peek2(Token![=]) {
+                let key = input.parse::<Token![crate]>()?;
+                input.parse::<Token![=]>()?;
+                if krate.is_some() {
+                    return Err(syn::Error::new(key.span, "Repeated option `crate`"));
+                }
+                krate = Some(Path::parse_mod_style(input)?);
+            } else if input.peek(Ident) && input.peek2(Token![=]) {
+                let key: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                match key.to_string().as_str() {
+                    "name" if name.is_none() => name = Some((input.parse()?, input.parse()?)),
+                    "name" => return Err(syn::Error::new(key.span(), "Repeated option `name`")),
+                    "escaper" if escaper.is_none() => {
+                        escaper = Some((input.parse()?, input.parse()?));
+                    }
+                    "escaper" => {
+                        return Err(syn::Error::new(key.span(), "Repeated option `escaper`"));
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "Unknown option, expected `crate`, `name` or `escaper`",
+                        ));
+                    }
+                }
+            } else {
+                break;
+            }
+            input.parse::<Token![;]>()?;
+        }
+
+        Ok(Input {
+            krate,
+            name,
+            escaper,
+            table: input.parse()?,
+        })
+    }
+}
+
+/// Rebases a path relative to the current module onto a module nested in it
+fn nested(mut path: Path) -> Path {
+    if path.leading_colon.is_none() {
+        let first = &mut path.segments[0].ident;
+        if first == "self" {
+            *first = Ident::new("super", first.span());
+        } else if first == "super" {
+            let span = first.span();
+            path.segments.insert(0, Ident::new("super", span).into());
+        }
+    }
+    path
+}
+
 /// Generate escape functions from a list of character mappings.
 ///
 /// This procedural macro generates SIMD-optimized escape functions for efficiently
@@ -31,24 +105,147 @@ use v_escape_codegen_base::generate;
 ///
 /// - `character`: A character to be escaped, specified as:
 ///   - A byte literal: `b'"'`, `b'<'`, `b'&'`
-///   - A char literal: `'"'`, `'<'`, `'&'`
-///   - An integer literal: `34`, `60`, `38` (ASCII values)
+///   - A char literal: `'"'`, `'<'`, `'&'`, `'\u{2028}'`
+///   - An integer literal: `34`, `60`, `38`, `0x7F`
+///   - A string or byte string literal for a sequence: `"]]>"`, `b"--"`
 /// - `escape_sequence`: A string literal containing the replacement text
 ///
+/// Any byte from `0x00` to `0xFF` can be escaped. A char literal outside ASCII escapes the
+/// whole character: its lead byte is searched for and the rest of its UTF-8 sequence is
+/// checked before replacing it, so characters only sharing the lead byte are kept.
+/// Such characters can not be mixed with bytes above `0x7F`.
+///
+/// A sequence such as `"</script"` is escaped as a whole in the same way, from its first
+/// byte, and takes precedence over the shorter sequences and bytes it starts with.
+/// Overlapping matches are resolved from left to right, so `"---"` with `"--"` escaped
+/// keeps its last `-`. Byte strings should be valid UTF-8.
+///
+/// Escaping a byte above `0x7F` replaces part of a multi-byte character, so tables with
+/// such bytes only generate the functions writing bytes: `escape_bytes`,
+/// `escape_bytes_reserved`, `try_escape_bytes`, `escape_io`, `escape_vectored`,
+/// `escaped_len`, `max_escaped_len`, `needs_escape` and `first_escape_position`.
+/// They take `&[u8]` instead of `&str`, so input that is not valid UTF-8 is escaped too.
+///
+/// A range of characters `start..=end` maps every character of the range to its own
+/// replacement, formatted from the `escape_sequence` with the character as argument:
+/// `{}` renders it in decimal and `{:x}`, `{:X}`, `{:o}` or `{:b}` in another radix,
+/// optionally padded as in `{:04x}`. Braces are escaped as `{{` and `}}`. Single
+/// characters take precedence over ranges:
+///
+/// ```rust,ignore
+/// escape! {
+///     0x00..=0x1F -> "\\u{:04x}",
+///     0x0A -> "\\n",
+/// }
+/// ```
+///
+/// # Named Escapers
+///
+/// The table can be preceded by `name = ident;`, optionally with a visibility as in
+/// `name = pub(crate) ident;`, to generate the functions in a module of that name
+/// instead of the current module, so several escapers can be defined side by side:
+///
+/// ```rust,ignore
+/// escape! {
+///     name = html_text;
+///     '&' -> "&amp;",
+///     '<' -> "&lt;",
+/// }
+///
+/// escape! {
+///     name = pub html_attr;
+///     '&' -> "&amp;",
+///     '"' -> "&quot;",
+/// }
+///
+/// let mut buffer = String::new();
+/// html_text::escape_string("a < b", &mut buffer);
+/// html_attr::escape_string("\"a\" & b", &mut buffer);
+/// ```
+///
+/// # Crate Path
+///
+/// The generated code refers to the items of `v_escape` by the crate name, which fails
+/// when the macro is re-exported by another crate or the dependency is renamed. The
+/// path of the crate can be given with `crate = path;` before the table:
+///
+/// ```rust,ignore
+/// escape! {
+///     crate = ::my_facade::v_escape;
+///     '<' -> "&lt;",
+/// }
+/// ```
+///
+/// Paths starting with `self` or `super` are relative to the current module, even
+/// when the functions are generated in the module given by `name`.
+///
+/// # Escaper Type
+///
+/// With `escaper = Name;`, optionally with a visibility as in `escaper = pub Name;`, a
+/// unit struct `Name` implementing `v_escape::Escaper` is generated along the functions,
+/// so generic code can be written over several escapers:
+///
+/// ```rust,ignore
+/// escape! {
+///     escaper = pub Html;
+///     '<' -> "&lt;",
+/// }
+///
+/// fn render<E: v_escape::Escaper>(input: &str) -> String {
+///     E::escape_fmt(input).to_string()
+/// }
+///
+/// render::<Html>("a < b");
+/// ```
+///
+/// It can not be generated when bytes above `0x7F` are escaped.
+///
 /// # Generated Functions
 ///
-/// The macro generates the following functions in the current module:
+/// The macro generates the following functions in the current module, or in the
+/// module given by `name`:
 ///
 /// - `escape_string(input: &str, buffer: &mut String)`: Escapes the input string and
 ///   appends the result to the provided buffer
+/// - `escape_string_reserved(input: &str, buffer: &mut String)` and
+///   `escape_bytes_reserved(input: &str, buffer: &mut Vec<u8>)`: Same as `escape_string` and
+///   `escape_bytes`, but reserve `max_escaped_len` once and write into the spare capacity
+/// - `try_escape_string(input: &str, buffer: &mut String) -> Result<(), TryReserveError>`:
+///   Same as `escape_string`, but reports allocation failures instead of aborting
+/// - `try_escape_bytes(input: &str, buffer: &mut Vec<u8>) -> Result<(), TryReserveError>`:
+///   Same as `escape_bytes`, but reports allocation failures instead of aborting
+/// - `escape_cow(input: &str) -> std::borrow::Cow<'_, str>`: Returns the input borrowed when
+///   nothing needs to be escaped and an escaped copy otherwise
 /// - `escape_fmt(input: &str) -> impl std::fmt::Display`: Returns a displayable object
 ///   that formats the escaped string
+/// - `escape_fmt_into(input: &str, writer: &mut impl std::fmt::Write) -> std::fmt::Result`:
+///   Escapes the input string into any `fmt::Write` implementation
+/// - `escape_slice(input: &str, buffer: &mut [u8]) -> Result<usize, Overflow>`: Escapes the
+///   input string into a fixed buffer without allocating, reporting how much was consumed
+///   when the buffer is too small
+/// - `escape_iter(input: &str) -> impl Iterator<Item = &str>`: Returns an iterator over
+///   borrowed input spans and `&'static str` replacements that make up the escaped input
+/// - `escaped_len(input: &str) -> usize`: Returns the exact length of the escaped input
+///   without producing any output
+/// - `max_escaped_len(len: usize) -> usize`: Returns the worst-case escaped length of an input
+///   of `len` bytes, based on the `MAX_REPLACEMENT_LEN` constant also generated
+/// - `needs_escape(input: &str) -> bool`: Returns whether the input contains any character
+///   that needs to be escaped, stopping at the first match
+/// - `first_escape_position(input: &str) -> Option<usize>`: Returns the byte offset of the
+///   first character that needs to be escaped
+/// - `escape_io(input: &str, writer: &mut impl std::io::Write) -> std::io::Result<()>`:
+///   Escapes the input string into the provided writer
+/// - `escape_vectored(input: &str, writer: &mut impl std::io::Write) -> std::io::Result<()>`:
+///   Escapes the input string into the provided writer with `write_vectored`, without copying
+///   the unchanged input
 ///
 /// # Features
 ///
 /// The generated functions require specific features to be enabled:
-/// - `string` feature: Enables `escape_string` function
-/// - `fmt` feature: Enables `escape_fmt` function
+/// - `string` feature: Enables `escape_string`, `escape_string_reserved`, `try_escape_string` and
+///   `escape_cow` functions
+/// - `fmt` feature: Enables `escape_fmt` and `escape_fmt_into` functions
+/// - `std` feature: Enables `escape_io` and `escape_vectored` functions
 ///
 /// # Performance
 ///
@@ -62,8 +259,14 @@ use v_escape_codegen_base::generate;
 ///
 /// The macro will fail to compile if:
 /// - Character mappings are not properly formatted
-/// - Duplicate characters are specified
-/// - Non-ASCII characters are used (only ASCII characters 0-127 are supported)
+/// - An option is unknown or repeated, or the crate path is not a module path
+/// - An escaper type is asked for along with bytes above `0x7F`
+/// - Duplicate characters are specified, or ranges overlap
+/// - A range replacement is not a valid format
+/// - Integer literals above `0xFF` are used, or non-ASCII char literals in a range
+/// - Characters outside ASCII are mixed with bytes above `0x7F`
+/// - A sequence is empty, or a byte string is not valid UTF-8
+/// - Every byte from `0x00` to `0xFF` is escaped
 /// - Escape sequences are not valid string literals
 ///
 /// # Implementation Details
@@ -75,9 +278,33 @@ use v_escape_codegen_base::generate;
 /// - Proper trait implementations for the v_escape framework
 #[proc_macro]
 pub fn escape(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input: TokenStream = input.into();
-    match generate(quote!(new!( #input );), "v_escape") {
-        Ok((code, _)) => code.into(),
-        Err(e) => e.to_compile_error().into(),
+    let Input {
+        krate,
+        name,
+        escaper,
+        table,
+    } = match syn::parse(input) {
+        Ok(input) => input,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let krate = match (krate, &name) {
+        (Some(krate), Some(_)) => nested(krate).to_token_stream().to_string(),
+        (Some(krate), None) => krate.to_token_stream().to_string(),
+        (None, _) => "v_escape".to_string(),
+    };
+    let escaper = escaper.map(|(vis, escaper)| quote!(#vis #escaper).to_string());
+    let code = match generate(quote!(new!( #table );), &krate, escaper.as_deref()) {
+        Ok((code, _)) => code,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    match name {
+        Some((vis, name)) => quote! {
+            #[doc = concat!("Escape functions generated by `escape!` as `", stringify!(#name), "`")]
+            #vis mod #name {
+                #code
+            }
+        }
+        .into(),
+        None => code.into(),
     }
 }
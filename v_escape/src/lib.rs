@@ -1,5 +1,9 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
 
-pub use v_escape_base::{Escapes, EscapesBuilder, Vector, escape_builder};
+pub use v_escape_base::{
+    EscapeIter, Escaper, Escapes, EscapesBuilder, Overflow, UnescapeError, UnescapeErrorKind,
+    UnescapeFailure, Unescaped, Unescapes, UnescapesBuilder, Vector, escape_builder, escaper,
+    unescape_builder,
+};
 pub use v_escape_proc_macro::escape;
@@ -15,4 +15,473 @@ fn test() {
     let mut escaped = String::with_capacity(s.len());
     escape_string(s, &mut escaped);
     assert_eq!(escaped, "Hello,&lt; world!&quot;");
+    let mut escaped = String::new();
+    let writer: &mut dyn core::fmt::Write = &mut escaped;
+    escape_fmt_into(s, writer).unwrap();
+    assert_eq!(escaped, "Hello,&lt; world!&quot;");
+}
+
+mod range {
+    use v_escape::escape;
+
+    escape! {
+        0x00..=0x1F -> "\\u{:04x}",
+        0x0A -> "\\n",
+        '"' -> "\\\""
+    }
+
+    #[test]
+    fn test_range() {
+        let mut escaped = String::new();
+        escape_string("a\0\u{1f}\n\"b", &mut escaped);
+        assert_eq!(escaped, "a\\u0000\\u001f\\n\\\"b");
+    }
+}
+
+mod del {
+    use v_escape::escape;
+
+    escape! {
+        0x00..=0x1F -> "\\x{:02x}",
+        0x7F -> "\\x7f",
+        '\\' -> "\\\\"
+    }
+
+    #[test]
+    fn test_del() {
+        let haystack = ["a\u{7F}b\\", &"c".repeat(64), "\u{7F}é\n\u{7F}"].concat();
+        let mut escaped = String::new();
+        escape_string(&haystack, &mut escaped);
+        assert_eq!(
+            escaped,
+            ["a\\x7fb\\\\", &"c".repeat(64), "\\x7fé\\x0a\\x7f"].concat()
+        );
+        assert_eq!(escaped_len(&haystack), escaped.len());
+        assert_eq!(first_escape_position(&haystack[2..]), Some(1));
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod high {
+    use v_escape::escape;
+
+    escape! {
+        b'\\' -> "\\\\",
+        0x7F..=0x9F -> "\\x{:02x}",
+        0xFF -> "\\xff"
+    }
+
+    #[test]
+    fn test_high_bytes() {
+        for size in [0, 1, 15, 16, 31, 32, 33, 63, 64, 65] {
+            let clean = "b".repeat(size);
+            // U+00E9 is C3 A9, U+0080 is C2 80 and U+03FF is CF BF
+            let haystack = [&clean, "é\u{80}", &clean, "\\\u{3FF}", &clean].concat();
+            let haystack = haystack.as_bytes();
+            let mut escaped = Vec::new();
+            escape_bytes(haystack, &mut escaped);
+            let expected = [
+                clean.as_bytes(),
+                b"\xC3\xA9\xC2\\x80",
+                clean.as_bytes(),
+                b"\\\\\xCF\xBF",
+                clean.as_bytes(),
+            ]
+            .concat();
+            assert_eq!(escaped, expected);
+            assert_eq!(escaped_len(haystack), expected.len());
+            assert_eq!(first_escape_position(haystack), Some(size + 3));
+        }
+        assert!(!needs_escape("aé\u{7E}".as_bytes()));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        for size in [0, 1, 15, 16, 31, 32, 33, 63, 64, 65] {
+            let clean = vec![b'b'; size];
+            // A lone continuation byte, an escaped byte and a truncated character
+            let haystack = [&clean[..], b"\x80\xFE\\", &clean, b"\xFF\xC3"].concat();
+            let expected = [&clean[..], b"\\x80\xFE\\\\", &clean, b"\\xff\xC3"].concat();
+
+            let mut escaped = Vec::new();
+            escape_bytes(&haystack, &mut escaped);
+            assert_eq!(escaped, expected);
+
+            let mut escaped = Vec::new();
+            escape_bytes_reserved(&haystack, &mut escaped);
+            assert_eq!(escaped, expected);
+
+            let mut escaped = Vec::new();
+            try_escape_bytes(&haystack, &mut escaped).unwrap();
+            assert_eq!(escaped, expected);
+
+            #[cfg(feature = "std")]
+            {
+                let mut escaped = Vec::new();
+                escape_io(&haystack, &mut escaped).unwrap();
+                assert_eq!(escaped, expected);
+
+                let mut escaped = Vec::new();
+                let writer: &mut dyn std::io::Write = &mut escaped;
+                escape_io(&haystack, writer).unwrap();
+                assert_eq!(escaped, expected);
+
+                let mut escaped = Vec::new();
+                let writer: &mut dyn std::io::Write = &mut escaped;
+                escape_vectored(&haystack, writer).unwrap();
+                assert_eq!(escaped, expected);
+            }
+
+            assert_eq!(escaped_len(&haystack), expected.len());
+            assert_eq!(first_escape_position(&haystack), Some(size));
+            assert!(needs_escape(&haystack));
+            assert!(!needs_escape(&[0xFE, 0xC3]));
+        }
+    }
+}
+
+mod chars {
+    use v_escape::escape;
+
+    escape! {
+        '<' -> "&lt;",
+        '\u{A0}' -> "&nbsp;",
+        '\u{2028}' -> "\\u2028",
+        '\u{2029}' -> "\\u2029",
+        '🚀' -> ":rocket:"
+    }
+
+    #[test]
+    fn test_chars() {
+        let mut escaped = String::new();
+        escape_string("a\u{2028}b\u{A0}<\u{2029}🚀", &mut escaped);
+        assert_eq!(escaped, "a\\u2028b&nbsp;&lt;\\u2029:rocket:");
+    }
+
+    #[test]
+    fn test_chars_shared_lead() {
+        // Sharing the lead byte with an escaped character: U+2020, U+20AC,
+        // U+00A9 and U+1F680's neighbour U+1F681
+        let haystack = "\u{2020}€©🚁\u{2027}\u{202A}";
+        let mut escaped = String::new();
+        escape_string(haystack, &mut escaped);
+        assert_eq!(escaped, haystack);
+        assert_eq!(escaped_len(haystack), haystack.len());
+        assert!(!needs_escape(&haystack.repeat(8)));
+    }
+
+    #[test]
+    fn test_chars_boundaries() {
+        let candidates = ["\u{2028}", "\u{2020}", "\u{A0}", "©", "🚀", "🚁", "<"];
+        let escaped_candidates = [
+            "\\u2028", "\u{2020}", "&nbsp;", "©", ":rocket:", "🚁", "&lt;",
+        ];
+        let tail = "b".repeat(300);
+        for size in 0..70 {
+            let clean = "b".repeat(size);
+            for (c, e) in candidates.iter().zip(escaped_candidates) {
+                let haystack = [&clean, *c, &clean, *c, &tail].concat();
+                let expected = [&clean, e, &clean, e, &tail].concat();
+
+                let mut escaped = String::new();
+                escape_string(&haystack, &mut escaped);
+                assert_eq!(escaped, expected, "{size} {c}");
+                assert_eq!(escape_fmt(&haystack).to_string(), expected);
+                assert_eq!(escaped_len(&haystack), expected.len());
+                assert_eq!(escape_iter(&haystack).collect::<String>(), expected);
+                let first = (*c != e).then_some(size);
+                assert_eq!(first_escape_position(&haystack), first);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chars_slice() {
+        let mut buf = [0u8; 16];
+        assert_eq!(escape_slice("ab\u{2028}", &mut buf), Ok(8));
+        assert_eq!(&buf[..8], b"ab\\u2028");
+
+        let mut buf = [0u8; 4];
+        let err = escape_slice("ab\u{2028}", &mut buf).unwrap_err();
+        assert_eq!((err.consumed, err.written), (2, 2));
+    }
+
+    mod only_chars {
+        use v_escape::escape;
+
+        escape! {
+            '\u{2028}' -> "\\u2028",
+            '\u{2029}' -> "\\u2029"
+        }
+
+        #[test]
+        fn test_only_chars() {
+            let haystack = ["a\u{2028}", &"\u{2027}".repeat(30), "\u{2029}"].concat();
+            let expected = ["a\\u2028", &"\u{2027}".repeat(30), "\\u2029"].concat();
+            let mut escaped = String::new();
+            escape_string(&haystack, &mut escaped);
+            assert_eq!(escaped, expected);
+            assert_eq!(escaped_len(&haystack), expected.len());
+            assert_eq!(first_escape_position("<>\u{2029}"), Some(2));
+        }
+    }
+}
+
+mod sequences {
+    use v_escape::escape;
+
+    escape! {
+        '<' -> "&lt;",
+        "</script" -> "<\\/script",
+        "]]>" -> "]]]]><![CDATA[>",
+        b"--" -> "&#45;&#45;"
+    }
+
+    #[test]
+    fn test_sequences() {
+        let mut escaped = String::new();
+        escape_string("a]]>b</script><!-- c -->", &mut escaped);
+        assert_eq!(
+            escaped,
+            "a]]]]><![CDATA[>b<\\/script>&lt;!&#45;&#45; c &#45;&#45;>"
+        );
+    }
+
+    #[test]
+    fn test_sequences_overlap() {
+        let mut escaped = String::new();
+        escape_string("---|----|]]]>|<</script", &mut escaped);
+        assert_eq!(
+            escaped,
+            "&#45;&#45;-|&#45;&#45;&#45;&#45;|]]]]]><![CDATA[>|&lt;<\\/script"
+        );
+        assert_eq!(escaped_len("---"), "&#45;&#45;-".len());
+        assert_eq!(
+            escape_iter("a---").collect::<Vec<_>>(),
+            ["a", "&#45;&#45;", "-"]
+        );
+    }
+
+    #[test]
+    fn test_sequences_partial() {
+        // Sharing the first bytes with an escaped sequence, or cut by the end of the haystack
+        let haystack = "]] ]>-</SCRIPT]]";
+        let mut escaped = String::new();
+        escape_string(haystack, &mut escaped);
+        assert_eq!(escaped, "]] ]>-&lt;/SCRIPT]]");
+        assert_eq!(first_escape_position(haystack), Some(6));
+        for end in ["]", "]]", "-", "</scrip"] {
+            let haystack = ["b".repeat(40), end.to_string()].concat();
+            let mut escaped = String::new();
+            escape_string(&haystack, &mut escaped);
+            assert_eq!(escaped, haystack.replace('<', "&lt;"));
+        }
+        assert!(!needs_escape(&"]]-]".repeat(32)));
+    }
+
+    #[test]
+    fn test_sequences_boundaries() {
+        let candidates = ["]]>", "</script", "--!", "]]", "</scrip", "<"];
+        let escaped_candidates = [
+            "]]]]><![CDATA[>",
+            "<\\/script",
+            "&#45;&#45;!",
+            "]]",
+            "&lt;/scrip",
+            "&lt;",
+        ];
+        let tail = "b".repeat(300);
+        for size in 0..70 {
+            let clean = "b".repeat(size);
+            for (c, e) in candidates.iter().zip(escaped_candidates) {
+                for tail in ["", &tail] {
+                    let haystack = [&clean, *c, &clean, *c, tail].concat();
+                    let expected = [&clean, e, &clean, e, tail].concat();
+
+                    let mut escaped = String::new();
+                    escape_string(&haystack, &mut escaped);
+                    assert_eq!(escaped, expected, "{size} {c}");
+                    assert_eq!(escape_fmt(&haystack).to_string(), expected);
+                    assert_eq!(escaped_len(&haystack), expected.len());
+                    assert_eq!(escape_iter(&haystack).collect::<String>(), expected);
+                    let first = (*c != e).then_some(size);
+                    assert_eq!(first_escape_position(&haystack), first);
+                }
+            }
+        }
+    }
+}
+
+mod named {
+    use v_escape::escape;
+
+    escape! {
+        name = text;
+        '&' -> "&amp;",
+        '<' -> "&lt;"
+    }
+
+    escape! {
+        name = pub(crate) attr;
+        '&' -> "&amp;",
+        '"' -> "&quot;"
+    }
+
+    escape! {
+        '<' -> "\\u003c"
+    }
+
+    #[test]
+    fn test_named() {
+        let haystack = "<a title=\"&\">";
+        assert_eq!(
+            text::escape_fmt(haystack).to_string(),
+            "&lt;a title=\"&amp;\">"
+        );
+        assert_eq!(
+            attr::escape_fmt(haystack).to_string(),
+            "<a title=&quot;&amp;&quot;>"
+        );
+        assert_eq!(escape_fmt(haystack).to_string(), "\\u003ca title=\"&\">");
+        assert_eq!(text::MAX_REPLACEMENT_LEN, 5);
+        assert_eq!(attr::MAX_REPLACEMENT_LEN, 6);
+        assert_eq!(attr::first_escape_position(haystack), Some(9));
+    }
+}
+
+mod facade {
+    pub use v_escape as inner;
+}
+
+mod krate {
+    use v_escape::escape;
+
+    escape! {
+        crate = crate::facade::inner;
+        '<' -> "&lt;"
+    }
+
+    escape! {
+        crate = super::facade::inner;
+        name = quote;
+        '"' -> "&quot;"
+    }
+
+    escape! {
+        crate = ::v_escape;
+        name = amp;
+        '&' -> "&amp;"
+    }
+
+    #[test]
+    fn test_crate() {
+        let haystack = "<\"&";
+        assert_eq!(escape_fmt(haystack).to_string(), "&lt;\"&");
+        assert_eq!(quote::escape_fmt(haystack).to_string(), "<&quot;&");
+        assert_eq!(amp::escape_fmt(haystack).to_string(), "<\"&amp;");
+    }
+}
+
+mod escaper {
+    use v_escape::{Escaper, escape};
+
+    escape! {
+        escaper = pub(crate) Text;
+        '&' -> "&amp;",
+        '<' -> "&lt;"
+    }
+
+    escape! {
+        name = attr;
+        escaper = pub Attr;
+        '"' -> "&quot;",
+        "</script" -> "<\\/script"
+    }
+
+    fn render<E: Escaper>(haystack: &str) -> String {
+        let mut escaped = String::new();
+        E::escape_string(haystack, &mut escaped);
+        assert_eq!(E::escape_fmt(haystack).to_string(), escaped);
+        assert_eq!(E::escaped_len(haystack), escaped.len());
+        escaped
+    }
+
+    #[test]
+    fn test_escaper() {
+        let haystack = "<a title=\"&\"></script>";
+        assert_eq!(
+            render::<Text>(haystack),
+            "&lt;a title=\"&amp;\">&lt;/script>"
+        );
+        assert_eq!(
+            render::<attr::Attr>(haystack),
+            "<a title=&quot;&&quot;><\\/script>"
+        );
+        assert_eq!(Text::MAPPINGS, &[(b'&', "&amp;"), (b'<', "&lt;")]);
+        assert!(Text::SEQUENCES.is_empty());
+        assert_eq!(attr::Attr::MAPPINGS, &[(b'"', "&quot;")]);
+        assert_eq!(attr::Attr::SEQUENCES, &[(&b"</script"[..], "<\\/script")]);
+        assert_eq!(attr::Attr::MAX_REPLACEMENT_LEN, 9);
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+mod kernels {
+    use v_escape::escape;
+    use v_escape_base::{arch::x86_64::sse, writer::WriterVec};
+
+    // Scattered bytes, masked exactly by the nibble tables and with false
+    // positives by the ranges
+    escape! {
+        '"' -> "&quot;",
+        '&' -> "&amp;",
+        '\'' -> "&#x27;",
+        '/' -> "&#x2f;",
+        '<' -> "&lt;",
+        '>' -> "&gt;"
+    }
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn escape_ssse3(haystack: &str) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let _ = sse::escape::<Builder, false, _>(haystack.as_bytes(), WriterVec::new(&mut buffer));
+        buffer
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn escape_sse2(haystack: &str) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let _ =
+            sse::escape::<RangeBuilder, false, _>(haystack.as_bytes(), WriterVec::new(&mut buffer));
+        buffer
+    }
+
+    fn shuffle<B: v_escape::EscapesBuilder>() -> bool {
+        <B::Escapes<()> as v_escape::Escapes>::SHUFFLE
+    }
+
+    #[test]
+    fn test_kernels() {
+        assert!(shuffle::<Builder>());
+        assert!(!shuffle::<RangeBuilder>());
+        for size in [0, 1, 15, 16, 17, 31, 32, 33, 63, 64, 65] {
+            let clean = "b".repeat(size);
+            // `#`, `$`, `%` and `=` are inside the ranges of the SSE2 masking
+            let haystack = [&clean, "%<a href='/'>", &clean, "#\"&\"$=", &clean].concat();
+            let expected = [
+                &clean,
+                "%&lt;a href=&#x27;&#x2f;&#x27;&gt;",
+                &clean,
+                "#&quot;&amp;&quot;$=",
+                &clean,
+            ]
+            .concat();
+            if std::is_x86_feature_detected!("ssse3") {
+                // SAFETY: SSSE3 is available
+                assert_eq!(unsafe { escape_ssse3(&haystack) }, expected.as_bytes());
+            }
+            // SAFETY: SSE2 is enabled at compile time
+            assert_eq!(unsafe { escape_sse2(&haystack) }, expected.as_bytes());
+        }
+    }
 }
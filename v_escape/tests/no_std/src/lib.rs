@@ -0,0 +1,15 @@
+//! `escape!` expanded in a `no_std` crate, naming the items of `alloc`
+//! without any import.
+#![no_std]
+
+v_escape::escape! {
+    escaper = Html;
+    '"' -> "&quot;",
+    '<' -> "&lt;"
+}
+
+pub mod bytes {
+    v_escape::escape! {
+        0x80..=0xFF -> "\\x{:02x}"
+    }
+}
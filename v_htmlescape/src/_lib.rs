@@ -6,3 +6,8 @@ new!(
     39 -> "&#x27;",
     47 -> "&#x2f;"
 );
+
+mod entities;
+mod unescape;
+
+pub use unescape::*;
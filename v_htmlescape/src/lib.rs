@@ -27,13 +27,34 @@
 //! ## Public API
 //!
 //! The following functions are emitted, gated by their respective Cargo
-//! features (all enabled by default):
+//! features (all enabled by default). `escape_slice`, `escape_iter`,
+//! `escaped_len`, `max_escaped_len`, `needs_escape` and `first_escape_position`
+//! never allocate and are always available. A `MAX_REPLACEMENT_LEN` constant
+//! holding the length of the longest replacement is emitted as well:
 //!
 //! | Function | Feature | Signature |
 //! | -------- | ------- | --------- |
 //! | `escape_string` | `string` | `fn(&str, &mut String)` |
 //! | `escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |
+//! | `escape_string_reserved` | `string` | `fn(&str, &mut String)` |
+//! | `escape_bytes_reserved`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |
+//! | `try_escape_string` | `string` | `fn(&str, &mut String) -> Result<(), TryReserveError>` |
+//! | `try_escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>) -> Result<(), TryReserveError>` |
+//! | `escape_cow`    | `string` | `fn(&str) -> Cow<'_, str>` |
 //! | `escape_fmt`    | `fmt`    | `fn(&str) -> impl Display + '_` |
+//! | `escape_fmt_into` | `fmt` | `fn(&str, &mut impl fmt::Write) -> fmt::Result` |
+//! | `escape_slice`  | -        | `fn(&str, &mut [u8]) -> Result<usize, Overflow>` |
+//! | `escape_iter`   | -        | `fn(&str) -> impl Iterator<Item = &str>` |
+//! | `escaped_len`   | -        | `fn(&str) -> usize` |
+//! | `max_escaped_len` | -      | `const fn(usize) -> usize` |
+//! | `needs_escape`  | -        | `fn(&str) -> bool` |
+//! | `first_escape_position` | - | `fn(&str) -> Option<usize>` |
+//! | `escape_io`     | `std`    | `fn(&str, &mut impl io::Write) -> io::Result<()>` |
+//! | `escape_vectored` | `std`  | `fn(&str, &mut impl io::Write) -> io::Result<()>` |
+//!
+//! The unit struct [`HtmlEscaper`] implements [`Escaper`] with these functions,
+//! so generic code can take it as a type parameter, as `fn render<E: Escaper>()`.
+//! It exposes the escape table as well, in `MAPPINGS` and `SEQUENCES`.
 //!
 //! At runtime the implementation dispatches to the best SIMD backend
 //! available on the current CPU (AVX2/SSE2 on x86_64, NEON on aarch64,
@@ -60,6 +81,68 @@ const V_ESCAPE_LEN: usize = 6usize;
 use v_escape_base::{Escapes, EscapesBuilder, Vector, escape_builder};
 #[derive(Debug, Clone, Copy)]
 struct Escape<V: Vector> {
+    low: V,
+    high: V,
+    nibble: V,
+    zero: V,
+}
+#[allow(dead_code)]
+struct Builder;
+impl EscapesBuilder for Builder {
+    type Escapes<V: Vector> = Escape<V>;
+    fn new<V: Vector>() -> Self::Escapes<V> {
+        Self::Escapes {
+            low: V::from_table(&[
+                0u8, 0u8, 2u8, 0u8, 0u8, 0u8, 2u8, 2u8, 0u8, 0u8, 0u8, 0u8, 1u8, 0u8, 1u8, 2u8,
+            ]),
+            high: V::from_table(&[
+                0u8, 0u8, 2u8, 1u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8,
+            ]),
+            nibble: V::splat(0x0F),
+            zero: V::splat(0),
+        }
+    }
+}
+impl<V: Vector> Escapes for Escape<V> {
+    const ESCAPE_LEN: usize = 6usize;
+    const FALSE_POSITIVE: bool = false;
+    const MAX_REPLACEMENT_LEN: usize = 6usize;
+    const SHUFFLE: bool = true;
+    const UTF8: bool = true;
+    const MAPPINGS: &'static [(u8, &'static str)] = &[
+        (0x22, "&quot;"),
+        (0x26, "&amp;"),
+        (0x27, "&#x27;"),
+        (0x2F, "&#x2f;"),
+        (0x3C, "&lt;"),
+        (0x3E, "&gt;"),
+    ];
+    const SEQUENCES: &'static [(&'static [u8], &'static str)] = &[];
+    type Vector = V;
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        self.low
+            .shuffle(vector2.and(self.nibble))
+            .and(self.high.shuffle(vector2.high_nibble()))
+            .cmpeq(self.zero)
+            .cmpeq(self.zero)
+    }
+    #[inline(always)]
+    fn escape(i: usize) -> &'static str {
+        V_ESCAPE_QUOTES[i]
+    }
+    #[inline(always)]
+    fn position(i: u8) -> usize {
+        V_ESCAPE_CHARS[i as usize] as usize
+    }
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        (V_ESCAPE_CHARS[c as usize] as usize) < V_ESCAPE_LEN
+    }
+}
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+struct RangeEscape<V: Vector> {
     translation_a: V,
     below_a: V,
     translation_b: V,
@@ -67,22 +150,34 @@ struct Escape<V: Vector> {
     c: V,
 }
 #[allow(dead_code)]
-struct Builder;
-impl EscapesBuilder for Builder {
-    type Escapes<V: Vector> = Escape<V>;
+struct RangeBuilder;
+impl EscapesBuilder for RangeBuilder {
+    type Escapes<V: Vector> = RangeEscape<V>;
     fn new<V: Vector>() -> Self::Escapes<V> {
         Self::Escapes {
-            translation_a: V::splat(88i8 as u8),
-            below_a: V::splat(121i8 as u8),
-            translation_b: V::splat(65i8 as u8),
-            below_b: V::splat(124i8 as u8),
-            c: V::splat(47i8 as u8),
+            translation_a: V::splat(88u8),
+            below_a: V::splat(121u8),
+            translation_b: V::splat(65u8),
+            below_b: V::splat(124u8),
+            c: V::splat(47u8),
         }
     }
 }
-impl<V: Vector> Escapes for Escape<V> {
+impl<V: Vector> Escapes for RangeEscape<V> {
     const ESCAPE_LEN: usize = 6usize;
     const FALSE_POSITIVE: bool = true;
+    const MAX_REPLACEMENT_LEN: usize = 6usize;
+    const SHUFFLE: bool = false;
+    const UTF8: bool = true;
+    const MAPPINGS: &'static [(u8, &'static str)] = &[
+        (0x22, "&quot;"),
+        (0x26, "&amp;"),
+        (0x27, "&#x27;"),
+        (0x2F, "&#x2f;"),
+        (0x3C, "&lt;"),
+        (0x3E, "&gt;"),
+    ];
+    const SEQUENCES: &'static [(&'static [u8], &'static str)] = &[];
     type Vector = V;
     #[inline(always)]
     fn masking(&self, vector2: V) -> V {
@@ -105,4 +200,9 @@ impl<V: Vector> Escapes for Escape<V> {
         (V_ESCAPE_CHARS[c as usize] as usize) < V_ESCAPE_LEN
     }
 }
-escape_builder!(Builder);
+escape_builder!(Builder, RangeBuilder);
+v_escape_base::escaper!(pub HtmlEscaper, Builder);
+pub use v_escape_base::Escaper;
+mod entities;
+mod unescape;
+pub use unescape::*;
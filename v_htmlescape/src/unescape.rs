@@ -0,0 +1,121 @@
+//! Decoding of HTML character references.
+//!
+//! Follows the WHATWG tokenizer rules for character references in text: a
+//! reference that does not decode is forwarded verbatim, so decoding never
+//! fails.
+use v_escape_base::{
+    UnescapeError, Unescaped, Unescapes, UnescapesBuilder, Vector, unescape_builder,
+};
+
+use crate::entities::{ENTITIES, MAX_LEGACY_NAME_LEN, MAX_NAME_LEN};
+
+/// The replacements of the numeric references to C1 control characters,
+/// from `0x80` to `0x9F`, that windows-1252 maps to printable characters.
+static C1_REPLACEMENTS: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+#[derive(Debug, Clone, Copy)]
+struct CharRef<V: Vector> {
+    amp: V,
+}
+
+struct Builder;
+impl UnescapesBuilder for Builder {
+    type Unescapes<V: Vector> = CharRef<V>;
+
+    fn new<V: Vector>() -> Self::Unescapes<V> {
+        CharRef {
+            amp: V::splat(b'&'),
+        }
+    }
+}
+
+impl<V: Vector> Unescapes for CharRef<V> {
+    type Vector = V;
+
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        self.amp.cmpeq(vector2)
+    }
+
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        c == b'&'
+    }
+
+    fn decode(haystack: &str) -> Result<(usize, Unescaped), UnescapeError> {
+        let haystack = haystack.as_bytes();
+        let decoded = match haystack.get(1) {
+            Some(b'#') => numeric(haystack),
+            Some(c) if c.is_ascii_alphanumeric() => named(haystack),
+            _ => None,
+        };
+        Ok(decoded.unwrap_or((1, Unescaped::Str("&"))))
+    }
+}
+
+/// Decodes the longest named reference at the start of `haystack`.
+fn named(haystack: &[u8]) -> Option<(usize, Unescaped)> {
+    let name = &haystack[1..];
+    let run = name
+        .iter()
+        .take(MAX_NAME_LEN - 1)
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .count();
+    let terminated = name.get(run) == Some(&b';');
+    if let Some(s) = terminated.then(|| lookup(&name[..=run])).flatten() {
+        return Some((run + 2, Unescaped::Str(s)));
+    }
+    // Only the legacy names may omit the `;`, or be followed by more
+    // alphanumerics, as in `&notit;`
+    (1..=run.min(MAX_LEGACY_NAME_LEN))
+        .rev()
+        .find_map(|len| lookup(&name[..len]).map(|s| (len + 1, Unescaped::Str(s))))
+}
+
+fn lookup(name: &[u8]) -> Option<&'static str> {
+    ENTITIES
+        .binary_search_by(|(entity, _)| entity.as_bytes().cmp(name))
+        .ok()
+        .map(|i| ENTITIES[i].1)
+}
+
+/// Decodes the decimal or hexadecimal reference at the start of `haystack`.
+fn numeric(haystack: &[u8]) -> Option<(usize, Unescaped)> {
+    let (radix, start) = match haystack.get(2) {
+        Some(b'x' | b'X') => (16, 3),
+        _ => (10, 2),
+    };
+    let digits = haystack[start..]
+        .iter()
+        .take_while(|&&c| char::from(c).is_digit(radix))
+        .count();
+    if digits == 0 {
+        return None;
+    }
+    let end = start + digits;
+    let n = haystack[start..end].iter().fold(0u32, |n, &c| {
+        let digit = char::from(c).to_digit(radix).unwrap_or_default();
+        n.saturating_mul(radix).saturating_add(digit)
+    });
+    let end = end + (haystack.get(end) == Some(&b';')) as usize;
+    Some((end, Unescaped::Char(code_point(n))))
+}
+
+/// Applies the replacement rules for numeric references.
+///
+/// The null character, surrogates and numbers beyond the Unicode range
+/// become U+FFFD; C1 controls are read as windows-1252.
+fn code_point(n: u32) -> char {
+    match n {
+        0 => char::REPLACEMENT_CHARACTER,
+        0x80..=0x9F => C1_REPLACEMENTS[(n - 0x80) as usize],
+        _ => char::from_u32(n).unwrap_or(char::REPLACEMENT_CHARACTER),
+    }
+}
+
+unescape_builder!(Builder);
@@ -0,0 +1,95 @@
+#![cfg(feature = "string")]
+use v_htmlescape::{escape_string, unescape_string};
+
+fn unescape(haystack: &str) -> String {
+    let mut buffer = String::new();
+    unescape_string(haystack, &mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn test_unescape_named() {
+    assert_eq!(unescape(""), "");
+    assert_eq!(unescape("foo"), "foo");
+    assert_eq!(unescape("&lt;&gt;&amp;&quot;&apos;"), "<>&\"'");
+    assert_eq!(unescape("a&nbsp;b&eacute;c"), "a\u{A0}béc");
+    assert_eq!(unescape("&CounterClockwiseContourIntegral;"), "∳");
+    assert_eq!(unescape("&NotEqualTilde;"), "≂\u{338}");
+    assert_eq!(unescape("&fjlig;"), "fj");
+}
+
+#[test]
+fn test_unescape_legacy() {
+    assert_eq!(unescape("&amp &lt&gt"), "& <>");
+    assert_eq!(unescape("&copy2024"), "©2024");
+    assert_eq!(unescape("&notit;"), "¬it;");
+    assert_eq!(unescape("&notin;"), "∉");
+    assert_eq!(unescape("&ampfoo;"), "&foo;");
+    // Only the legacy names may omit the `;`
+    assert_eq!(unescape("&hellip &hellip;"), "&hellip …");
+}
+
+#[test]
+fn test_unescape_numeric() {
+    assert_eq!(unescape("&#60;&#x3C;&#X3c;&#060"), "<<<<");
+    assert_eq!(unescape("&#x1F680;&#128640;"), "🚀🚀");
+    assert_eq!(unescape("&#x27;&#x2f;"), "'/");
+    assert_eq!(unescape("&#x41B"), "\u{41B}");
+}
+
+#[test]
+fn test_unescape_numeric_replacements() {
+    assert_eq!(unescape("&#0;"), "\u{FFFD}");
+    assert_eq!(unescape("&#xD800;&#xDFFF;"), "\u{FFFD}\u{FFFD}");
+    assert_eq!(unescape("&#x110000;"), "\u{FFFD}");
+    assert_eq!(unescape("&#99999999999999999999;"), "\u{FFFD}");
+    assert_eq!(unescape("&#x80;&#x9F;&#150;"), "€Ÿ–");
+    assert_eq!(unescape("&#x81;&#x8D;"), "\u{81}\u{8D}");
+    // Noncharacters and other controls are kept
+    assert_eq!(unescape("&#xFFFF;&#x1;&#x7F;"), "\u{FFFF}\u{1}\u{7F}");
+}
+
+#[test]
+fn test_unescape_verbatim() {
+    assert_eq!(unescape("&"), "&");
+    assert_eq!(unescape("a & b"), "a & b");
+    assert_eq!(unescape("&;&#;&#x;&#xg;"), "&;&#;&#x;&#xg;");
+    assert_eq!(unescape("&foo;&Amp;"), "&foo;&Amp;");
+    assert_eq!(unescape("&&amp;"), "&&");
+    assert_eq!(unescape("🚀&🚀"), "🚀&🚀");
+}
+
+#[test]
+fn test_unescape_sizes() {
+    for size in [1, 15, 16, 31, 32, 33, 63, 64, 65, 1000] {
+        let clean = "b".repeat(size);
+        assert_eq!(unescape(&clean), clean);
+
+        let haystack = [&clean, "&lt;", &clean, "&#47", &clean].join("");
+        assert_eq!(
+            unescape(&haystack),
+            [&clean, "<", &clean, "/", &clean].join("")
+        );
+    }
+}
+
+#[test]
+fn test_unescape_round_trip() {
+    let haystacks = [
+        "<script>alert('foo' / \"bar\")</script>",
+        "&amp; &lt &#60; & ; 🚀",
+        &"a<b>&'\"/c".repeat(100),
+    ];
+    for haystack in haystacks {
+        let mut escaped = String::new();
+        escape_string(haystack, &mut escaped);
+        assert_eq!(unescape(&escaped), haystack);
+    }
+}
+
+#[test]
+fn test_unescape_appends() {
+    let mut buffer = String::from("foo");
+    unescape_string("&lt;bar&gt;", &mut buffer).unwrap();
+    assert_eq!(buffer, "foo<bar>");
+}
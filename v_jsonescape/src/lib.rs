@@ -55,13 +55,34 @@
 //! ## Public API
 //!
 //! The following functions are emitted, gated by their respective Cargo
-//! features (all enabled by default):
+//! features (all enabled by default). `escape_slice`, `escape_iter`,
+//! `escaped_len`, `max_escaped_len`, `needs_escape` and `first_escape_position`
+//! never allocate and are always available. A `MAX_REPLACEMENT_LEN` constant
+//! holding the length of the longest replacement is emitted as well:
 //!
 //! | Function | Feature | Signature |
 //! | -------- | ------- | --------- |
 //! | `escape_string` | `string` | `fn(&str, &mut String)` |
 //! | `escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |
+//! | `escape_string_reserved` | `string` | `fn(&str, &mut String)` |
+//! | `escape_bytes_reserved`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |
+//! | `try_escape_string` | `string` | `fn(&str, &mut String) -> Result<(), TryReserveError>` |
+//! | `try_escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>) -> Result<(), TryReserveError>` |
+//! | `escape_cow`    | `string` | `fn(&str) -> Cow<'_, str>` |
 //! | `escape_fmt`    | `fmt`    | `fn(&str) -> impl Display + '_` |
+//! | `escape_fmt_into` | `fmt` | `fn(&str, &mut impl fmt::Write) -> fmt::Result` |
+//! | `escape_slice`  | -        | `fn(&str, &mut [u8]) -> Result<usize, Overflow>` |
+//! | `escape_iter`   | -        | `fn(&str) -> impl Iterator<Item = &str>` |
+//! | `escaped_len`   | -        | `fn(&str) -> usize` |
+//! | `max_escaped_len` | -      | `const fn(usize) -> usize` |
+//! | `needs_escape`  | -        | `fn(&str) -> bool` |
+//! | `first_escape_position` | - | `fn(&str) -> Option<usize>` |
+//! | `escape_io`     | `std`    | `fn(&str, &mut impl io::Write) -> io::Result<()>` |
+//! | `escape_vectored` | `std`  | `fn(&str, &mut impl io::Write) -> io::Result<()>` |
+//!
+//! The unit struct [`JsonEscaper`] implements [`Escaper`] with these functions,
+//! so generic code can take it as a type parameter, as `fn render<E: Escaper>()`.
+//! It exposes the escape table as well, in `MAPPINGS` and `SEQUENCES`.
 //!
 //! At runtime the implementation dispatches to the best SIMD backend
 //! available on the current CPU (AVX2/SSE2 on x86_64, NEON on aarch64,
@@ -106,16 +127,56 @@ impl EscapesBuilder for Builder {
     type Escapes<V: Vector> = Escape<V>;
     fn new<V: Vector>() -> Self::Escapes<V> {
         Self::Escapes {
-            translation_a: V::splat(96i8 as u8),
-            below_a: V::splat(95i8 as u8),
-            b: V::splat(34i8 as u8),
-            c: V::splat(92i8 as u8),
+            translation_a: V::splat(96u8),
+            below_a: V::splat(95u8),
+            b: V::splat(34u8),
+            c: V::splat(92u8),
         }
     }
 }
 impl<V: Vector> Escapes for Escape<V> {
     const ESCAPE_LEN: usize = 34usize;
     const FALSE_POSITIVE: bool = false;
+    const MAX_REPLACEMENT_LEN: usize = 6usize;
+    const SHUFFLE: bool = false;
+    const UTF8: bool = true;
+    const MAPPINGS: &'static [(u8, &'static str)] = &[
+        (0x00, "\\u0000"),
+        (0x01, "\\u0001"),
+        (0x02, "\\u0002"),
+        (0x03, "\\u0003"),
+        (0x04, "\\u0004"),
+        (0x05, "\\u0005"),
+        (0x06, "\\u0006"),
+        (0x07, "\\u0007"),
+        (0x08, "\\b"),
+        (0x09, "\\t"),
+        (0x0A, "\\n"),
+        (0x0B, "\\u000b"),
+        (0x0C, "\\f"),
+        (0x0D, "\\r"),
+        (0x0E, "\\u000e"),
+        (0x0F, "\\u000f"),
+        (0x10, "\\u0010"),
+        (0x11, "\\u0011"),
+        (0x12, "\\u0012"),
+        (0x13, "\\u0013"),
+        (0x14, "\\u0014"),
+        (0x15, "\\u0015"),
+        (0x16, "\\u0016"),
+        (0x17, "\\u0017"),
+        (0x18, "\\u0018"),
+        (0x19, "\\u0019"),
+        (0x1A, "\\u001a"),
+        (0x1B, "\\u001b"),
+        (0x1C, "\\u001c"),
+        (0x1D, "\\u001d"),
+        (0x1E, "\\u001e"),
+        (0x1F, "\\u001f"),
+        (0x22, "\\\""),
+        (0x5C, "\\\\"),
+    ];
+    const SEQUENCES: &'static [(&'static [u8], &'static str)] = &[];
     type Vector = V;
     #[inline(always)]
     fn masking(&self, vector2: V) -> V {
@@ -139,3 +200,7 @@ impl<V: Vector> Escapes for Escape<V> {
     }
 }
 escape_builder!(Builder);
+v_escape_base::escaper!(pub JsonEscaper, Builder);
+pub use v_escape_base::Escaper;
+mod unescape;
+pub use unescape::*;
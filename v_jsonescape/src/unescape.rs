@@ -0,0 +1,191 @@
+//! Decoding of JSON string escapes.
+//!
+//! Decodes every escape sequence of RFC 8259, combining UTF-16 surrogate
+//! pairs, and rejects the control characters that must be escaped.
+use core::fmt;
+
+use v_escape_base::{
+    UnescapeError, UnescapeErrorKind, Unescaped, Unescapes, UnescapesBuilder, Vector,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct Escape<V: Vector> {
+    translation_a: V,
+    below_a: V,
+    b: V,
+}
+
+struct Builder;
+impl UnescapesBuilder for Builder {
+    type Unescapes<V: Vector> = Escape<V>;
+
+    fn new<V: Vector>() -> Self::Unescapes<V> {
+        Escape {
+            translation_a: V::splat(0x60),
+            below_a: V::splat(0x5F),
+            b: V::splat(b'\\'),
+        }
+    }
+}
+
+impl<V: Vector> Unescapes for Escape<V> {
+    type Vector = V;
+
+    // Control characters from `0x00` to `0x1F`, translated above `0x5F`, and `\`
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        vector2
+            .add(self.translation_a)
+            .gt(self.below_a)
+            .or(vector2.cmpeq(self.b))
+    }
+
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        c < 0x20 || c == b'\\'
+    }
+
+    fn decode(haystack: &str) -> Result<(usize, Unescaped), UnescapeError> {
+        let haystack = haystack.as_bytes();
+        let unescaped = match haystack {
+            [b'\\', b'"', ..] => "\"",
+            [b'\\', b'\\', ..] => "\\",
+            [b'\\', b'/', ..] => "/",
+            [b'\\', b'b', ..] => "\u{8}",
+            [b'\\', b'f', ..] => "\u{C}",
+            [b'\\', b'n', ..] => "\n",
+            [b'\\', b'r', ..] => "\r",
+            [b'\\', b't', ..] => "\t",
+            [b'\\', b'u', ..] => return unicode(haystack),
+            [b'\\'] => return Err(UnescapeError::new(0, UnescapeErrorKind::Incomplete)),
+            _ => return Err(UnescapeError::new(0, UnescapeErrorKind::Unknown)),
+        };
+        Ok((2, Unescaped::Str(unescaped)))
+    }
+}
+
+/// Decodes the `\uXXXX` sequence at the start of `haystack`, and the low
+/// surrogate following a high one.
+fn unicode(haystack: &[u8]) -> Result<(usize, Unescaped), UnescapeError> {
+    let lone = || UnescapeError::new(0, UnescapeErrorKind::InvalidCodePoint);
+    let high = hex4(haystack, 2)?;
+    let (consumed, n) = match high {
+        0xD800..=0xDBFF => {
+            if !haystack[6..].starts_with(b"\\u") {
+                return Err(lone());
+            }
+            let low = hex4(haystack, 8)?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(lone());
+            }
+            (12, 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+        }
+        0xDC00..=0xDFFF => return Err(lone()),
+        _ => (6, high),
+    };
+    let c = char::from_u32(n).ok_or_else(lone)?;
+    Ok((consumed, Unescaped::Char(c)))
+}
+
+/// Parses the four hexadecimal digits at `at`.
+fn hex4(haystack: &[u8], at: usize) -> Result<u32, UnescapeError> {
+    (at..at + 4).try_fold(0, |n, i| {
+        let c = haystack
+            .get(i)
+            .ok_or(UnescapeError::new(0, UnescapeErrorKind::Incomplete))?;
+        let digit = char::from(*c)
+            .to_digit(16)
+            .ok_or(UnescapeError::new(i, UnescapeErrorKind::Unknown))?;
+        Ok(n << 4 | digit)
+    })
+}
+
+// Only `unescape_string` is exposed, with a JSON specific error
+#[allow(dead_code)]
+mod raw {
+    use super::Builder;
+
+    v_escape_base::unescape_builder!(Builder);
+}
+
+/// The reason a JSON string could not be unescaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonUnescapeErrorKind {
+    /// A `\` followed by a character that does not start an escape sequence,
+    /// or a `\u` followed by a non hexadecimal digit.
+    InvalidEscape,
+    /// The input ends inside an escape sequence.
+    UnexpectedEnd,
+    /// A `\u` escaped UTF-16 surrogate that is not part of a pair.
+    LoneSurrogate,
+    /// A control character from `U+0000` to `U+001F` that is not escaped.
+    ControlCharacter,
+}
+
+impl fmt::Display for JsonUnescapeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JsonUnescapeErrorKind::InvalidEscape => "invalid escape sequence",
+            JsonUnescapeErrorKind::UnexpectedEnd => "unexpected end of input",
+            JsonUnescapeErrorKind::LoneSurrogate => "lone surrogate",
+            JsonUnescapeErrorKind::ControlCharacter => "unescaped control character",
+        })
+    }
+}
+
+/// A malformed JSON string found while unescaping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonUnescapeError {
+    offset: usize,
+    kind: JsonUnescapeErrorKind,
+}
+
+impl JsonUnescapeError {
+    #[cfg(feature = "string")]
+    fn new(haystack: &str, err: UnescapeError) -> Self {
+        let offset = err.offset();
+        let kind = match err.kind() {
+            UnescapeErrorKind::Incomplete => JsonUnescapeErrorKind::UnexpectedEnd,
+            UnescapeErrorKind::InvalidCodePoint => JsonUnescapeErrorKind::LoneSurrogate,
+            _ if haystack.as_bytes()[offset] < 0x20 => JsonUnescapeErrorKind::ControlCharacter,
+            _ => JsonUnescapeErrorKind::InvalidEscape,
+        };
+        Self { offset, kind }
+    }
+
+    /// Returns the byte offset of the error in the input.
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the reason the input could not be unescaped.
+    #[inline]
+    pub const fn kind(&self) -> JsonUnescapeErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for JsonUnescapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl core::error::Error for JsonUnescapeError {}
+
+/// Unescapes the contents of a JSON string, without the surrounding quotes.
+///
+/// Every escape sequence of RFC 8259 is decoded and `\u` escaped UTF-16
+/// surrogate pairs are combined. Lone surrogates, unknown escape sequences
+/// and unescaped control characters are rejected with the byte offset of the
+/// malformed sequence.
+#[cfg(feature = "string")]
+pub fn unescape_string(haystack: &str) -> Result<String, JsonUnescapeError> {
+    // Unescaping never makes the input longer
+    let mut buffer = String::with_capacity(haystack.len());
+    raw::unescape_string(haystack, &mut buffer)
+        .map(|()| buffer)
+        .map_err(|err| JsonUnescapeError::new(haystack, err))
+}
@@ -2,19 +2,13 @@
 #![allow(unused)]
 fn all_utf8_less(less: &str) -> String {
     use std::char::from_u32;
-    let less = less.as_bytes();
     let mut buf = String::with_capacity(204_672 - less.len());
-    for i in 0..0x80u8 {
-        if !less.contains(&i) {
-            buf.push(from_u32(i as u32).unwrap())
+    for i in (0..0xD800).chain(0xE000..0x11000) {
+        let c = from_u32(i).unwrap();
+        if !less.contains(c) {
+            buf.push(c)
         }
     }
-    for i in 0x80..0xD800 {
-        buf.push(from_u32(i).unwrap());
-    }
-    for i in 0xE000..0x11000 {
-        buf.push(from_u32(i).unwrap());
-    }
     buf
 }
 #[cfg(feature = "string")]
@@ -277,13 +271,146 @@ mod fmt {
         );
     }
 }
+#[cfg(feature = "string")]
+mod string_reserved {
+    use super::*;
+    use v_jsonescape::{escape_string_reserved, max_escaped_len};
+    fn result(haystack: &str) -> String {
+        let mut buf = String::new();
+        escape_string_reserved(haystack, &mut buf);
+        assert!(buf.len() <= max_escaped_len(haystack.len()));
+        buf
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
 #[cfg(feature = "bytes")]
-mod bytes {
+mod bytes_reserved {
     use super::*;
-    use v_jsonescape::escape_bytes;
+    use v_jsonescape::{escape_bytes_reserved, max_escaped_len};
     fn result(haystack: &str) -> String {
         let mut buf = Vec::new();
-        escape_bytes(haystack, &mut buf);
+        escape_bytes_reserved(haystack, &mut buf);
+        assert!(buf.len() <= max_escaped_len(haystack.len()));
         String::from_utf8(buf).unwrap()
     }
     #[test]
@@ -408,3 +535,1623 @@ mod bytes {
         );
     }
 }
+#[cfg(feature = "string")]
+mod try_string {
+    use super::*;
+    use v_jsonescape::try_escape_string;
+    fn result(haystack: &str) -> String {
+        let mut buf = String::new();
+        try_escape_string(haystack, &mut buf).unwrap();
+        buf
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+#[cfg(feature = "bytes")]
+mod try_bytes {
+    use super::*;
+    use v_jsonescape::try_escape_bytes;
+    fn result(haystack: &str) -> String {
+        let mut buf = Vec::new();
+        try_escape_bytes(haystack, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+#[cfg(feature = "string")]
+mod cow {
+    use super::*;
+    use v_jsonescape::{escape_cow, first_escape_position};
+    fn result(haystack: &str) -> String {
+        let cow = escape_cow(haystack);
+        assert_eq!(
+            matches!(cow, std::borrow::Cow::Borrowed(_)),
+            first_escape_position(haystack).is_none()
+        );
+        cow.into_owned()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+#[cfg(feature = "fmt")]
+mod fmt_into {
+    use super::*;
+    use v_jsonescape::escape_fmt_into;
+    fn result(haystack: &str) -> String {
+        let mut buf = String::new();
+        escape_fmt_into(haystack, &mut buf).unwrap();
+        buf
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+#[cfg(feature = "bytes")]
+mod bytes {
+    use super::*;
+    use v_jsonescape::escape_bytes;
+    fn result(haystack: &str) -> String {
+        let mut buf = Vec::new();
+        escape_bytes(haystack, &mut buf);
+        String::from_utf8(buf).unwrap()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+mod slice {
+    use super::*;
+    use v_jsonescape::escape_slice;
+    fn result(haystack: &str) -> String {
+        let mut buf = [0u8; 256];
+        let mut rest = haystack;
+        let mut out = Vec::new();
+        loop {
+            match escape_slice(rest, &mut buf) {
+                Ok(written) => {
+                    out.extend_from_slice(&buf[..written]);
+                    break;
+                }
+                Err(err) => {
+                    out.extend_from_slice(&buf[..err.written]);
+                    rest = &rest[err.consumed..];
+                }
+            }
+        }
+        String::from_utf8(out).unwrap()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+mod len {
+    use super::*;
+    use v_jsonescape::{escape_slice, escaped_len};
+    fn result(haystack: &str) -> String {
+        let len = escaped_len(haystack);
+        let mut buf = vec![0u8; len];
+        assert_eq!(escape_slice(haystack, &mut buf), Ok(len));
+        String::from_utf8(buf).unwrap()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+mod iter {
+    use super::*;
+    use v_jsonescape::escape_iter;
+    fn result(haystack: &str) -> String {
+        let spans = escape_iter(haystack).collect::<Vec<_>>();
+        assert!(spans.iter().all(|span| !span.is_empty()));
+        spans.concat()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+mod position {
+    use super::*;
+    use v_jsonescape::{escape_slice, escaped_len, first_escape_position, needs_escape};
+    fn result(mut haystack: &str) -> String {
+        let mut out = String::new();
+        while let Some(i) = first_escape_position(haystack) {
+            assert!(needs_escape(haystack));
+            out.push_str(&haystack[..i]);
+            let next = i + haystack[i..].chars().next().unwrap().len_utf8();
+            let mut buf = vec![0u8; escaped_len(&haystack[i..next])];
+            escape_slice(&haystack[i..next], &mut buf).unwrap();
+            out.push_str(std::str::from_utf8(&buf).unwrap());
+            haystack = &haystack[next..];
+        }
+        assert!(!needs_escape(haystack));
+        out.push_str(haystack);
+        out
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+#[cfg(feature = "std")]
+mod io {
+    use super::*;
+    use v_jsonescape::escape_io;
+    fn result(haystack: &str) -> String {
+        let mut buf = Vec::new();
+        escape_io(haystack, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+#[cfg(feature = "std")]
+mod vectored {
+    use super::*;
+    use v_jsonescape::escape_vectored;
+    fn result(haystack: &str) -> String {
+        let mut buf = Vec::new();
+        escape_vectored(haystack, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
+#[cfg(all(feature = "string", feature = "bytes", feature = "fmt"))]
+mod escaper {
+    use super::*;
+    use v_jsonescape::{Escaper, JsonEscaper};
+    fn escape<E: Escaper>(haystack: &str) -> String {
+        let mut buf = String::new();
+        E::escape_string(haystack, &mut buf);
+        let mut bytes = Vec::new();
+        E::escape_bytes(haystack, &mut bytes);
+        assert_eq!(bytes, buf.as_bytes());
+        assert_eq!(E::escape_fmt(haystack).to_string(), buf);
+        assert_eq!(E::escaped_len(haystack), buf.len());
+        assert!(buf.len() <= E::max_escaped_len(haystack.len()));
+        assert_eq!(
+            E::needs_escape(haystack),
+            E::first_escape_position(haystack).is_some()
+        );
+        buf
+    }
+    fn result(haystack: &str) -> String {
+        escape::<JsonEscaper>(haystack)
+    }
+    #[test]
+    fn mappings() {
+        for (c, quote) in JsonEscaper::MAPPINGS {
+            assert_eq!(result(&char::from(*c).to_string()), *quote);
+        }
+        for (sequence, quote) in JsonEscaper::SEQUENCES {
+            assert_eq!(result(std::str::from_utf8(sequence).unwrap()), *quote);
+        }
+        let len = JsonEscaper::MAPPINGS.iter().map(|(_, quote)| quote.len());
+        let len = len.chain(JsonEscaper::SEQUENCES.iter().map(|(_, quote)| quote.len()));
+        assert_eq!(len.max(), Some(JsonEscaper::MAX_REPLACEMENT_LEN));
+    }
+    #[test]
+    fn tests() {
+        use std::borrow::Cow;
+        let empty = "";
+        let escapes = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let escaped = "\\u0000\\u0001\\u0002\\u0003\\u0004\\u0005\\u0006\\u0007\\b\\t\\n\\u000b\\f\\r\\u000e\\u000f\\u0010\\u0011\\u0012\\u0013\\u0014\\u0015\\u0016\\u0017\\u0018\\u0019\\u001a\\u001b\\u001c\\u001d\\u001e\\u001f\\\"\\\\";
+        let utf8: &str = &all_utf8_less(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\",
+        );
+        let empty_heap = String::new();
+        let short = "foobar";
+        let string_long: &str = &short.repeat(1024);
+        let string = "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\";
+        let cow = Cow::Owned(
+            "\0\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\t\n\u{b}\u{c}\r\u{e}\u{f}\u{10}\u{11}\u{12}\u{13}\u{14}\u{15}\u{16}\u{17}\u{18}\u{19}\u{1a}\u{1b}\u{1c}\u{1d}\u{1e}\u{1f}\"\\"
+                .to_string(),
+        );
+        assert_eq!(
+            result(&[short, escapes, short].join("")),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(result(empty), empty);
+        assert_eq!(result(escapes), escaped);
+        assert_eq!(result(&empty_heap), empty);
+        assert_eq!(result(&cow), escaped);
+        assert_eq!(result(string), escaped);
+        assert_eq!(result(utf8), utf8);
+        assert_eq!(result(string_long), string_long);
+        assert_eq!(result(escapes.repeat(1024).as_ref()), escaped.repeat(1024));
+        assert_eq!(
+            result([short, escapes, short].join("").as_ref()),
+            [short, escaped, short].join("")
+        );
+        assert_eq!(
+            result([escapes, short].join("").as_ref()),
+            [escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes, short].join("").as_ref()),
+            ["f", escaped, short].join("")
+        );
+        assert_eq!(
+            result(["f", escapes].join("").as_ref()),
+            ["f", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes].join("").as_ref()),
+            ["do", escaped].join("")
+        );
+        assert_eq!(
+            result(["do", escapes, "b"].join("").as_ref()),
+            ["do", escaped, "b"].join("")
+        );
+        assert_eq!(result(escapes.repeat(2).as_ref()), escaped.repeat(2));
+        assert_eq!(result(escapes.repeat(3).as_ref()), escaped.repeat(3));
+        assert_eq!(
+            result(["f", &escapes.repeat(2)].join("").as_ref()),
+            ["f", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2)].join("").as_ref()),
+            ["do", &escaped.repeat(2)].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(2), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(2), "bar"].join("")
+        );
+        assert_eq!(
+            result(["do", &escapes.repeat(3), "bar"].join("").as_ref()),
+            ["do", &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([&escapes.repeat(3), "bar"].join("").as_ref()),
+            [&escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(3), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(3), "bar"].join("")
+        );
+        assert_eq!(
+            result([short, &escapes.repeat(5), "bar"].join("").as_ref()),
+            [short, &escaped.repeat(5), "bar"].join("")
+        );
+        assert_eq!(
+            result(
+                [string_long, &escapes.repeat(13)]
+                    .join("")
+                    .repeat(1024)
+                    .as_ref()
+            ),
+            [string_long, &escaped.repeat(13)].join("").repeat(1024)
+        );
+        assert_eq!(
+            result([utf8, escapes, short].join("").as_ref()),
+            [utf8, escaped, short].join("")
+        );
+        assert_eq!(
+            result([utf8, escapes, utf8].join("").as_ref()),
+            [utf8, escaped, utf8].join("")
+        );
+        assert_eq!(
+            result([&utf8.repeat(124), escapes, utf8].join("").as_ref()),
+            [&utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8].join("")
+        );
+        assert_eq!(
+            result(
+                [escapes, &utf8.repeat(124), escapes, utf8, escapes]
+                    .join("")
+                    .as_ref()
+            ),
+            [escaped, &utf8.repeat(124), escaped, utf8, escaped].join("")
+        );
+    }
+}
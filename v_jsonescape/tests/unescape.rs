@@ -0,0 +1,107 @@
+#![cfg(feature = "string")]
+use v_jsonescape::{JsonUnescapeErrorKind, escape_string, unescape_string};
+
+fn error(haystack: &str) -> (usize, JsonUnescapeErrorKind) {
+    let err = unescape_string(haystack).unwrap_err();
+    (err.offset(), err.kind())
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape_string("").unwrap(), "");
+    assert_eq!(unescape_string("foo").unwrap(), "foo");
+    assert_eq!(
+        unescape_string(r#"\"\\\/\b\f\n\r\t"#).unwrap(),
+        "\"\\/\u{8}\u{C}\n\r\t"
+    );
+    assert_eq!(
+        unescape_string(r"\u0000\u001f\u00e9\u00E9\u20ac").unwrap(),
+        "\0\u{1F}éé€"
+    );
+    assert_eq!(unescape_string("é🚀\\n").unwrap(), "é🚀\n");
+}
+
+#[test]
+fn test_unescape_surrogate_pairs() {
+    assert_eq!(unescape_string(r"\ud83d\ude80").unwrap(), "🚀");
+    assert_eq!(unescape_string(r"a\uD834\uDD1Eb").unwrap(), "a𝄞b");
+    assert_eq!(unescape_string(r"\udbff\udfff").unwrap(), "\u{10FFFF}");
+}
+
+#[test]
+fn test_unescape_lone_surrogates() {
+    use JsonUnescapeErrorKind::LoneSurrogate;
+
+    assert_eq!(error(r"ab\ud83d"), (2, LoneSurrogate));
+    assert_eq!(error(r"\ud83dfoo"), (0, LoneSurrogate));
+    assert_eq!(error(r"\ud83d\n"), (0, LoneSurrogate));
+    assert_eq!(error(r"\ud83d\ud83d"), (0, LoneSurrogate));
+    assert_eq!(error(r"\ude80"), (0, LoneSurrogate));
+}
+
+#[test]
+fn test_unescape_invalid() {
+    use JsonUnescapeErrorKind::*;
+
+    assert_eq!(error(r"\a"), (0, InvalidEscape));
+    assert_eq!(error(r"foo\'"), (3, InvalidEscape));
+    assert_eq!(error(r"\u00g0"), (4, InvalidEscape));
+    assert_eq!(error(r"\ud83d\uz"), (8, InvalidEscape));
+    assert_eq!(error("\\é"), (0, InvalidEscape));
+    assert_eq!(error("\\"), (0, UnexpectedEnd));
+    assert_eq!(error(r"foo\u00"), (3, UnexpectedEnd));
+    assert_eq!(error(r"\ud83d\ude"), (0, UnexpectedEnd));
+}
+
+#[test]
+fn test_unescape_control_characters() {
+    use JsonUnescapeErrorKind::ControlCharacter;
+
+    assert_eq!(error("\0"), (0, ControlCharacter));
+    assert_eq!(error("foo\nbar"), (3, ControlCharacter));
+    assert_eq!(error("\u{1F}"), (0, ControlCharacter));
+    assert_eq!(error(r"\u00"), (0, JsonUnescapeErrorKind::UnexpectedEnd));
+    assert_eq!(unescape_string("\u{7F} ").unwrap(), "\u{7F} ");
+}
+
+#[test]
+fn test_unescape_error_display() {
+    assert_eq!(
+        unescape_string(r"ab\ud83d").unwrap_err().to_string(),
+        "lone surrogate at byte 2"
+    );
+}
+
+#[test]
+fn test_unescape_sizes() {
+    for size in [1, 15, 16, 31, 32, 33, 63, 64, 65, 1000] {
+        let clean = "b".repeat(size);
+        assert_eq!(unescape_string(&clean).unwrap(), clean);
+
+        let haystack = [&clean, r"\n", &clean, r"\ud83d\ude80", &clean].join("");
+        assert_eq!(
+            unescape_string(&haystack).unwrap(),
+            [&clean, "\n", &clean, "🚀", &clean].join("")
+        );
+
+        let haystack = [&clean, "\t", &clean].join("");
+        assert_eq!(
+            error(&haystack),
+            (size, JsonUnescapeErrorKind::ControlCharacter)
+        );
+    }
+}
+
+#[test]
+fn test_unescape_round_trip() {
+    let haystacks = [
+        "\"foo\"\\bar/\u{8}\u{C}\n\r\t",
+        "\0\u{1}\u{1F}\u{7F} é🚀",
+        &(0u8..0x80).map(char::from).collect::<String>().repeat(10),
+    ];
+    for haystack in haystacks {
+        let mut escaped = String::new();
+        escape_string(haystack, &mut escaped);
+        assert_eq!(unescape_string(&escaped).unwrap(), haystack);
+    }
+}
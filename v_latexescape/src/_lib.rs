@@ -10,3 +10,7 @@ new!(
     125 -> "\\}",
     126 -> "\\textasciitilde{}"
 );
+
+mod unescape;
+
+pub use unescape::*;
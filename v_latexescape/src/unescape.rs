@@ -0,0 +1,84 @@
+//! Decoding of the LaTeX escape sequences.
+//!
+//! Reverses the escape table of this crate. As in TeX, the whitespace after
+//! a control word is skipped, and so is an empty group `{}` following it.
+use v_escape_base::{
+    UnescapeError, UnescapeErrorKind, Unescaped, Unescapes, UnescapesBuilder, Vector,
+    unescape_builder,
+};
+
+/// The control words of the escape table and their replacements.
+static CONTROL_WORDS: [(&[u8], &str); 3] = [
+    (b"textasciicircum", "^"),
+    (b"textasciitilde", "~"),
+    (b"textbackslash", "\\"),
+];
+
+#[derive(Debug, Clone, Copy)]
+struct Escape<V: Vector> {
+    a: V,
+}
+
+struct Builder;
+impl UnescapesBuilder for Builder {
+    type Unescapes<V: Vector> = Escape<V>;
+
+    fn new<V: Vector>() -> Self::Unescapes<V> {
+        Escape { a: V::splat(b'\\') }
+    }
+}
+
+impl<V: Vector> Unescapes for Escape<V> {
+    type Vector = V;
+
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        self.a.cmpeq(vector2)
+    }
+
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        c == b'\\'
+    }
+
+    fn decode(haystack: &str) -> Result<(usize, Unescaped), UnescapeError> {
+        let unescaped = match haystack.as_bytes().get(1) {
+            Some(b'#') => "#",
+            Some(b'$') => "$",
+            Some(b'%') => "%",
+            Some(b'&') => "&",
+            Some(b'_') => "_",
+            Some(b'{') => "{",
+            Some(b'}') => "}",
+            Some(c) if c.is_ascii_alphabetic() => return control_word(haystack.as_bytes()),
+            Some(_) => return Err(UnescapeError::new(0, UnescapeErrorKind::Unknown)),
+            None => return Err(UnescapeError::new(0, UnescapeErrorKind::Incomplete)),
+        };
+        Ok((2, Unescaped::Str(unescaped)))
+    }
+}
+
+/// Decodes the control word at the start of `haystack` with the whitespace
+/// and the empty group following it.
+fn control_word(haystack: &[u8]) -> Result<(usize, Unescaped), UnescapeError> {
+    let end = 1 + haystack[1..]
+        .iter()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .count();
+    let (_, unescaped) = CONTROL_WORDS
+        .iter()
+        .find(|(word, _)| *word == &haystack[1..end])
+        .ok_or(UnescapeError::new(0, UnescapeErrorKind::Unknown))?;
+    let spaces = haystack[end..]
+        .iter()
+        .take_while(|c| c.is_ascii_whitespace())
+        .count();
+    let consumed = if haystack[end + spaces..].starts_with(b"{}") {
+        end + spaces + 2
+    } else {
+        end + spaces
+    };
+    Ok((consumed, Unescaped::Str(unescaped)))
+}
+
+unescape_builder!(Builder);
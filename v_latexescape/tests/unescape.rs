@@ -0,0 +1,77 @@
+#![cfg(feature = "string")]
+use v_escape_base::{UnescapeError, UnescapeErrorKind};
+use v_latexescape::{escape_string, unescape_string};
+
+fn unescape(haystack: &str) -> Result<String, UnescapeError> {
+    let mut buffer = String::new();
+    unescape_string(haystack, &mut buffer).map(|()| buffer)
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape("").unwrap(), "");
+    assert_eq!(unescape("foo").unwrap(), "foo");
+    assert_eq!(unescape(r"\#\$\%\&\_\{\}").unwrap(), "#$%&_{}");
+    assert_eq!(
+        unescape(r"\textbackslash{}\textasciicircum{}\textasciitilde{}").unwrap(),
+        "\\^~"
+    );
+    assert_eq!(unescape(r"caf\'e").unwrap_err().offset(), 3);
+}
+
+#[test]
+fn test_unescape_control_words() {
+    assert_eq!(unescape(r"a\textasciitilde b").unwrap(), "a~b");
+    assert_eq!(unescape(r"a\textasciitilde  {}b").unwrap(), "a~b");
+    assert_eq!(unescape("a\\textasciitilde\n\t{} b").unwrap(), "a~ b");
+    assert_eq!(unescape(r"a\textasciitilde{} b").unwrap(), "a~ b");
+    assert_eq!(unescape(r"a\textasciitilde{}{}b").unwrap(), "a~{}b");
+    assert_eq!(unescape(r"a\textasciitilde.b").unwrap(), "a~.b");
+    assert_eq!(unescape(r"a\textasciitilde").unwrap(), "a~");
+    assert_eq!(unescape(r"\textbackslash\_").unwrap(), "\\_");
+    assert_eq!(unescape(r"\textasciitilde{ }").unwrap(), "~{ }");
+}
+
+#[test]
+fn test_unescape_invalid() {
+    let error = |haystack| {
+        let err = unescape(haystack).unwrap_err();
+        (err.offset(), err.kind())
+    };
+    assert_eq!(error(r"\emph{foo}"), (0, UnescapeErrorKind::Unknown));
+    assert_eq!(
+        error(r"foo\textbackslashfoo"),
+        (3, UnescapeErrorKind::Unknown)
+    );
+    assert_eq!(error(r"\TextBackslash{}"), (0, UnescapeErrorKind::Unknown));
+    assert_eq!(error(r"\\"), (0, UnescapeErrorKind::Unknown));
+    assert_eq!(error("é\\"), (2, UnescapeErrorKind::Incomplete));
+}
+
+#[test]
+fn test_unescape_sizes() {
+    for size in [1, 15, 16, 31, 32, 33, 63, 64, 65, 1000] {
+        let clean = "b".repeat(size);
+        assert_eq!(unescape(&clean).unwrap(), clean);
+
+        let haystack = [&clean, r"\#", &clean, r"\textasciicircum{}", &clean].join("");
+        assert_eq!(
+            unescape(&haystack).unwrap(),
+            [&clean, "#", &clean, "^", &clean].join("")
+        );
+    }
+}
+
+#[test]
+fn test_unescape_round_trip() {
+    let haystacks = [
+        r"# $1 50% & a_b {c} \d ^e ~f",
+        "\\~^ foo\\ bar~ baz^{}",
+        &"\\textbackslash{}#$%&_{}~^ é🚀".repeat(100),
+    ];
+    for haystack in haystacks {
+        let mut escaped = String::new();
+        escape_string(haystack, &mut escaped);
+        assert_eq!(unescape(&escaped).unwrap(), haystack);
+    }
+}
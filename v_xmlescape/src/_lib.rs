@@ -0,0 +1,8 @@
+new!(
+    '&' -> "&amp;",
+    '<' -> "&lt;",
+    '>' -> "&gt;",
+    '"' -> "&quot;",
+    '\'' -> "&apos;",
+    '\u{A0}' -> "&#xA0;"
+);
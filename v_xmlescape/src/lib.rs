@@ -0,0 +1,289 @@
+//! autogenerated by v_escape_codegen@0.2.1
+//!
+//! # `v_xmlescape`
+//!
+//! Autogenerated escape crate produced by
+//! [`v_escape_codegen`](https://crates.io/crates/v_escape_codegen) on top of the
+//! [`v_escape-base`](https://crates.io/crates/v_escape-base) runtime.
+//!
+//! ## Behavior
+//!
+//! Each call rewrites the characters listed in the table below into their
+//! replacement string; every other byte of the input is forwarded verbatim.
+//! All public entry points take a `&str` (UTF-8 guaranteed at the type level),
+//! so they cannot be used to construct invalid UTF-8.
+//!
+//! ## Escape table
+//!
+//! | Byte (hex) | Source | Replacement |
+//! | ---------- | ------ | ----------- |
+//! | `0x22` | `"` | `&quot;` |
+//! | `0x26` | `&` | `&amp;` |
+//! | `0x27` | `'` | `&apos;` |
+//! | `0x3C` | `<` | `&lt;` |
+//! | `0x3E` | `>` | `&gt;` |
+//! | `0xC2 0xA0` | `U+00A0` | `&#xA0;` |
+//!
+//! ## Public API
+//!
+//! The following functions are emitted, gated by their respective Cargo
+//! features (all enabled by default). `escape_slice`, `escape_iter`,
+//! `escaped_len`, `max_escaped_len`, `needs_escape` and `first_escape_position`
+//! never allocate and are always available. A `MAX_REPLACEMENT_LEN` constant
+//! holding the length of the longest replacement is emitted as well:
+//!
+//! | Function | Feature | Signature |
+//! | -------- | ------- | --------- |
+//! | `escape_string` | `string` | `fn(&str, &mut String)` |
+//! | `escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |
+//! | `escape_string_reserved` | `string` | `fn(&str, &mut String)` |
+//! | `escape_bytes_reserved`  | `bytes`  | `fn(&str, &mut Vec<u8>)` |
+//! | `try_escape_string` | `string` | `fn(&str, &mut String) -> Result<(), TryReserveError>` |
+//! | `try_escape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>) -> Result<(), TryReserveError>` |
+//! | `escape_cow`    | `string` | `fn(&str) -> Cow<'_, str>` |
+//! | `escape_fmt`    | `fmt`    | `fn(&str) -> impl Display + '_` |
+//! | `escape_fmt_into` | `fmt` | `fn(&str, &mut impl fmt::Write) -> fmt::Result` |
+//! | `escape_slice`  | -        | `fn(&str, &mut [u8]) -> Result<usize, Overflow>` |
+//! | `escape_iter`   | -        | `fn(&str) -> impl Iterator<Item = &str>` |
+//! | `escaped_len`   | -        | `fn(&str) -> usize` |
+//! | `max_escaped_len` | -      | `const fn(usize) -> usize` |
+//! | `needs_escape`  | -        | `fn(&str) -> bool` |
+//! | `first_escape_position` | - | `fn(&str) -> Option<usize>` |
+//! | `escape_io`     | `std`    | `fn(&str, &mut impl io::Write) -> io::Result<()>` |
+//! | `escape_vectored` | `std`  | `fn(&str, &mut impl io::Write) -> io::Result<()>` |
+//!
+//! The unit struct [`XmlEscaper`] implements [`Escaper`] with these functions,
+//! so generic code can take it as a type parameter, as `fn render<E: Escaper>()`.
+//! It exposes the escape table as well, in `MAPPINGS` and `SEQUENCES`.
+//!
+//! The inverse functions decode exactly the replacements of the table and
+//! fail with the byte offset of any other sequence starting like one:
+//!
+//! | Function | Feature | Signature |
+//! | -------- | ------- | --------- |
+//! | `unescape_string` | `string` | `fn(&str, &mut String) -> Result<(), UnescapeError>` |
+//! | `unescape_bytes`  | `bytes`  | `fn(&str, &mut Vec<u8>) -> Result<(), UnescapeError>` |
+//! | `unescape_fmt`    | `fmt`    | `fn(&str) -> impl Display + '_` |
+//!
+//! At runtime the implementation dispatches to the best SIMD backend
+//! available on the current CPU (AVX2/SSE2 on x86_64, NEON on aarch64,
+//! `simd128` on wasm32) and falls back to a scalar loop otherwise.
+//!
+static V_ESCAPE_CHARS: [u8; 256] = [
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 0u8, 5u8, 5u8, 5u8,
+    1u8, 2u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 3u8, 5u8, 4u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+    5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8, 5u8,
+];
+static V_ESCAPE_QUOTES: [&str; 5usize] = ["&quot;", "&amp;", "&apos;", "&lt;", "&gt;"];
+const V_ESCAPE_LEN: usize = 5usize;
+use v_escape_base::{Escapes, EscapesBuilder, Vector, escape_builder};
+#[derive(Debug, Clone, Copy)]
+struct Escape<V: Vector> {
+    low: V,
+    high: V,
+    nibble: V,
+    zero: V,
+}
+#[allow(dead_code)]
+struct Builder;
+impl EscapesBuilder for Builder {
+    type Escapes<V: Vector> = Escape<V>;
+    fn new<V: Vector>() -> Self::Escapes<V> {
+        Self::Escapes {
+            low: V::from_table(&[
+                0u8, 0u8, 3u8, 0u8, 0u8, 0u8, 2u8, 2u8, 0u8, 0u8, 0u8, 0u8, 4u8, 0u8, 4u8, 0u8,
+            ]),
+            high: V::from_table(&[
+                0u8, 0u8, 2u8, 4u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 1u8, 0u8, 0u8, 0u8,
+            ]),
+            nibble: V::splat(0x0F),
+            zero: V::splat(0),
+        }
+    }
+}
+impl<V: Vector> Escapes for Escape<V> {
+    const ESCAPE_LEN: usize = 5usize;
+    const FALSE_POSITIVE: bool = true;
+    const MAX_REPLACEMENT_LEN: usize = 6usize;
+    const SHUFFLE: bool = true;
+    const UTF8: bool = true;
+    const MAPPINGS: &'static [(u8, &'static str)] = &[
+        (0x22, "&quot;"),
+        (0x26, "&amp;"),
+        (0x27, "&apos;"),
+        (0x3C, "&lt;"),
+        (0x3E, "&gt;"),
+    ];
+    const SEQUENCES: &'static [(&'static [u8], &'static str)] = &[(b"\xC2\xA0", "&#xA0;")];
+    type Vector = V;
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        self.low
+            .shuffle(vector2.and(self.nibble))
+            .and(self.high.shuffle(vector2.high_nibble()))
+            .cmpeq(self.zero)
+            .cmpeq(self.zero)
+    }
+    #[inline(always)]
+    fn escape(i: usize) -> &'static str {
+        V_ESCAPE_QUOTES[i]
+    }
+    #[inline(always)]
+    fn position(i: u8) -> usize {
+        V_ESCAPE_CHARS[i as usize] as usize
+    }
+    #[inline(always)]
+    unsafe fn escape_at(at: *const u8, end: *const u8) -> Option<(&'static str, usize)> {
+        let haystack = unsafe { core::slice::from_raw_parts(at, end.offset_from(at) as usize) };
+        match haystack {
+            [0xC2, 0xA0, ..] => Some(("&#xA0;", 2usize)),
+            [c, ..] => {
+                let c = V_ESCAPE_CHARS[*c as usize] as usize;
+                if c < V_ESCAPE_LEN {
+                    Some((V_ESCAPE_QUOTES[c], 1))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        (V_ESCAPE_CHARS[c as usize] as usize) < V_ESCAPE_LEN || matches!(c, 0xC2)
+    }
+}
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+struct RangeEscape<V: Vector> {
+    translation_a: V,
+    below_a: V,
+    translation_b: V,
+    below_b: V,
+    c: V,
+}
+#[allow(dead_code)]
+struct RangeBuilder;
+impl EscapesBuilder for RangeBuilder {
+    type Escapes<V: Vector> = RangeEscape<V>;
+    fn new<V: Vector>() -> Self::Escapes<V> {
+        Self::Escapes {
+            translation_a: V::splat(88u8),
+            below_a: V::splat(121u8),
+            translation_b: V::splat(65u8),
+            below_b: V::splat(124u8),
+            c: V::splat(194u8),
+        }
+    }
+}
+impl<V: Vector> Escapes for RangeEscape<V> {
+    const ESCAPE_LEN: usize = 5usize;
+    const FALSE_POSITIVE: bool = true;
+    const MAX_REPLACEMENT_LEN: usize = 6usize;
+    const SHUFFLE: bool = false;
+    const UTF8: bool = true;
+    const MAPPINGS: &'static [(u8, &'static str)] = &[
+        (0x22, "&quot;"),
+        (0x26, "&amp;"),
+        (0x27, "&apos;"),
+        (0x3C, "&lt;"),
+        (0x3E, "&gt;"),
+    ];
+    const SEQUENCES: &'static [(&'static [u8], &'static str)] = &[(b"\xC2\xA0", "&#xA0;")];
+    type Vector = V;
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        vector2
+            .add(self.translation_a)
+            .gt(self.below_a)
+            .or(vector2.add(self.translation_b).gt(self.below_b))
+            .or(vector2.cmpeq(self.c))
+    }
+    #[inline(always)]
+    fn escape(i: usize) -> &'static str {
+        V_ESCAPE_QUOTES[i]
+    }
+    #[inline(always)]
+    fn position(i: u8) -> usize {
+        V_ESCAPE_CHARS[i as usize] as usize
+    }
+    #[inline(always)]
+    unsafe fn escape_at(at: *const u8, end: *const u8) -> Option<(&'static str, usize)> {
+        let haystack = unsafe { core::slice::from_raw_parts(at, end.offset_from(at) as usize) };
+        match haystack {
+            [0xC2, 0xA0, ..] => Some(("&#xA0;", 2usize)),
+            [c, ..] => {
+                let c = V_ESCAPE_CHARS[*c as usize] as usize;
+                if c < V_ESCAPE_LEN {
+                    Some((V_ESCAPE_QUOTES[c], 1))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        (V_ESCAPE_CHARS[c as usize] as usize) < V_ESCAPE_LEN || matches!(c, 0xC2)
+    }
+}
+escape_builder!(Builder, RangeBuilder);
+v_escape_base::escaper!(pub XmlEscaper, Builder);
+pub use v_escape_base::Escaper;
+use v_escape_base::{
+    UnescapeError, UnescapeErrorKind, Unescaped, Unescapes, UnescapesBuilder, unescape_builder,
+};
+#[derive(Debug, Clone, Copy)]
+struct Unescape<V: Vector> {
+    lead_0: V,
+}
+#[allow(dead_code)]
+struct UnescapeBuilder;
+impl UnescapesBuilder for UnescapeBuilder {
+    type Unescapes<V: Vector> = Unescape<V>;
+    fn new<V: Vector>() -> Self::Unescapes<V> {
+        Unescape {
+            lead_0: V::splat(b'&'),
+        }
+    }
+}
+impl<V: Vector> Unescapes for Unescape<V> {
+    type Vector = V;
+    #[inline(always)]
+    fn masking(&self, vector2: V) -> V {
+        vector2.cmpeq(self.lead_0)
+    }
+    #[inline(always)]
+    fn byte_byte_compare(c: u8) -> bool {
+        matches!(c, b'&')
+    }
+    fn decode(haystack: &str) -> Result<(usize, Unescaped), UnescapeError> {
+        let haystack = haystack.as_bytes();
+        let replacements: &[&[u8]] = &[b"&#xA0;", b"&amp;", b"&apos;", b"&gt;", b"&lt;", b"&quot;"];
+        match haystack {
+            [b'&', b'#', b'x', b'A', b'0', b';', ..] => Ok((6, Unescaped::Str("\u{a0}"))),
+            [b'&', b'a', b'm', b'p', b';', ..] => Ok((5, Unescaped::Str("&"))),
+            [b'&', b'a', b'p', b'o', b's', b';', ..] => Ok((6, Unescaped::Str("'"))),
+            [b'&', b'g', b't', b';', ..] => Ok((4, Unescaped::Str(">"))),
+            [b'&', b'l', b't', b';', ..] => Ok((4, Unescaped::Str("<"))),
+            [b'&', b'q', b'u', b'o', b't', b';', ..] => Ok((6, Unescaped::Str("\""))),
+            _ if replacements.iter().any(|q| q.starts_with(haystack)) => {
+                Err(UnescapeError::new(0, UnescapeErrorKind::Incomplete))
+            }
+            _ => Err(UnescapeError::new(0, UnescapeErrorKind::Unknown)),
+        }
+    }
+}
+unescape_builder!(UnescapeBuilder);
@@ -0,0 +1,62 @@
+#![cfg(feature = "string")]
+use v_escape_base::{UnescapeError, UnescapeErrorKind};
+use v_xmlescape::{escape_string, unescape_string};
+
+fn unescape(haystack: &str) -> Result<String, UnescapeError> {
+    let mut buffer = String::new();
+    unescape_string(haystack, &mut buffer).map(|()| buffer)
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape("").unwrap(), "");
+    assert_eq!(unescape("foo").unwrap(), "foo");
+    assert_eq!(
+        unescape("&lt;a title=&quot;Tom &amp; &apos;Jerry&apos;&quot;&gt;").unwrap(),
+        "<a title=\"Tom & 'Jerry'\">"
+    );
+    assert_eq!(unescape("a&#xA0;b").unwrap(), "a\u{A0}b");
+}
+
+#[test]
+fn test_unescape_invalid() {
+    let error = |haystack| {
+        let err = unescape(haystack).unwrap_err();
+        (err.offset(), err.kind())
+    };
+    // Only the replacements of the table are decoded
+    assert_eq!(error("a &nbsp; b"), (2, UnescapeErrorKind::Unknown));
+    assert_eq!(error("&#60;"), (0, UnescapeErrorKind::Unknown));
+    assert_eq!(error("&#xa0;"), (0, UnescapeErrorKind::Unknown));
+    assert_eq!(error("&LT;"), (0, UnescapeErrorKind::Unknown));
+    assert_eq!(error("&amp"), (0, UnescapeErrorKind::Incomplete));
+    assert_eq!(error("é&ap"), (2, UnescapeErrorKind::Incomplete));
+    assert_eq!(error("&"), (0, UnescapeErrorKind::Incomplete));
+}
+
+#[test]
+fn test_unescape_sizes() {
+    for size in [1, 15, 16, 31, 32, 33, 63, 64, 65, 1000] {
+        let clean = "b".repeat(size);
+        assert_eq!(unescape(&clean).unwrap(), clean);
+
+        let haystack = [&clean, "&amp;", &clean, "&#xA0;", &clean].join("");
+        assert_eq!(
+            unescape(&haystack).unwrap(),
+            [&clean, "&", &clean, "\u{A0}", &clean].join("")
+        );
+        assert_eq!(
+            unescape(&[&clean, "&am;"].join("")).unwrap_err().offset(),
+            size
+        );
+    }
+}
+
+#[test]
+fn test_unescape_round_trip() {
+    for haystack in ["&amp;lt; &#xA0; \u{A0}&", &"<'\"é🚀\u{A0}>&".repeat(100)] {
+        let mut escaped = String::new();
+        escape_string(haystack, &mut escaped);
+        assert_eq!(unescape(&escaped).unwrap(), haystack);
+    }
+}